@@ -1,4 +1,47 @@
+pub const SEED_ACTIVITY: &[u8] = b"activity";
+pub const SEED_ALLOWED_MINT: &[u8] = b"allowed_mint";
+pub const SEED_BALANCE_HISTORY: &[u8] = b"balance_history";
+pub const SEED_CONFIG: &[u8] = b"config";
+pub const SEED_CONSENT: &[u8] = b"consent";
+pub const SEED_CREDIT_HISTORY: &[u8] = b"credit_history";
+pub const SEED_HOLDING_REGISTRY: &[u8] = b"holding_registry";
 pub const SEED_IDENTITY: &[u8] = b"identity";
+pub const SEED_PENALTY: &[u8] = b"penalty";
+pub const SEED_PENALTY_INDEX: &[u8] = b"penalty_index";
+pub const SEED_REFERRAL_STATS: &[u8] = b"referral_stats";
+pub const SEED_REPORTER: &[u8] = b"reporter";
+/// Seed of the PDA a lending program signs with when reporting credit events
+pub const SEED_REPORTER_AUTHORITY: &[u8] = b"credit_reporter";
+pub const SEED_REPORTER_LOANS: &[u8] = b"reporter_loans";
 pub const SEED_SCORE: &[u8] = b"score";
+/// Seed of the PDA a consumer program signs `assert_min_score` calls with
+pub const SEED_SCORE_GATE: &[u8] = b"score_gate";
+pub const SEED_SCORE_MODEL: &[u8] = b"score_model";
+pub const SEED_SCORE_HISTORY: &[u8] = b"score_history";
+pub const SEED_STATS: &[u8] = b"stats";
+pub const SEED_TIP_VAULT: &[u8] = b"tip_vault";
+pub const SEED_TOMBSTONE: &[u8] = b"tombstone";
 pub const SEED_TRANSFER_REQUEST: &[u8] = b"transfer_request";
+/// Longest deletion cooldown the admin can configure
+pub const MAX_DELETION_COOLDOWN_SECONDS: i64 = 365 * 24 * 60 * 60;
+/// Longest minimum interval between two score calculations the admin can configure
+pub const MAX_SCORE_INTERVAL_SECONDS: i64 = 30 * 24 * 60 * 60;
 pub const TRANSFER_EXPIRY_SECONDS: i64 = 7 * 24 * 60 * 60;
+pub const TOMBSTONE_WAITING_PERIOD_SECONDS: i64 = 30 * 24 * 60 * 60;
+pub const TOMBSTONE_PENALTY_PER_DELETION: u64 = 5;
+pub const TOMBSTONE_PENALTY_PER_MARK: u64 = 10;
+pub const TOMBSTONE_MAX_PENALTY: u64 = 30;
+pub const SCORE_HISTORY_LEN: usize = 16;
+pub const SCORE_DECAY_SECONDS: i64 = 180 * 24 * 60 * 60;
+pub const PENALTY_REASON_MAX_LEN: usize = 64;
+/// Open penalties tracked per owner; expired ones are pruned to make room
+pub const MAX_OPEN_PENALTIES: usize = 16;
+pub const BALANCE_HISTORY_LEN: usize = 32;
+/// Minimum spacing between two balance checkpoints of the same owner
+pub const BALANCE_CHECKPOINT_INTERVAL_SECONDS: i64 = 60 * 60;
+/// Length of the day check-ins are counted in, aligned to the Unix epoch
+pub const ACTIVITY_DAY_SECONDS: i64 = 24 * 60 * 60;
+/// Consent scope letting the grantee gate on the score through `assert_min_score`
+pub const CONSENT_SCOPE_MIN_SCORE: u8 = 1 << 0;
+/// Every consent scope bit defined so far
+pub const CONSENT_SCOPE_ALL: u8 = CONSENT_SCOPE_MIN_SCORE;
@@ -12,4 +12,90 @@ pub enum IdentityScoreError {
     IdentityTransferFailed,
     #[msg("The transfer request has expired.")]
     TransferExpired,
+    #[msg("The referral accounts do not match the identity's referrer.")]
+    InvalidReferrer,
+    #[msg("The identity has been deactivated.")]
+    IdentityDeactivated,
+    #[msg("The identity must be deactivated first.")]
+    IdentityNotDeactivated,
+    #[msg("The deletion cooldown has not elapsed yet.")]
+    DeletionCooldownActive,
+    #[msg("The reactivation window has elapsed.")]
+    ReactivationWindowElapsed,
+    #[msg("The identity is still in its waiting period.")]
+    IdentityInWaitingPeriod,
+    #[msg("The score was calculated too recently.")]
+    ScoreCooldownActive,
+    #[msg("A holding account is invalid or not owned by the identity owner.")]
+    InvalidHoldingAccount,
+    #[msg("The token mint is not on the allow list.")]
+    MintNotAllowed,
+    #[msg("A holding account was passed more than once.")]
+    DuplicateHoldingAccount,
+    #[msg("Too many holding accounts were passed.")]
+    TooManyHoldingAccounts,
+    #[msg("The price feed is missing, unknown or not trading.")]
+    InvalidPriceFeed,
+    #[msg("The price feed has not been updated recently enough.")]
+    StalePriceFeed,
+    #[msg("The price feed's confidence interval is too wide.")]
+    PriceConfidenceTooWide,
+    #[msg("The identity has no outstanding loan to resolve.")]
+    NoOutstandingLoan,
+    #[msg("The penalty reason is too long.")]
+    PenaltyReasonTooLong,
+    #[msg("The penalty must expire in the future.")]
+    InvalidPenaltyExpiry,
+    #[msg("The identity has too many open penalties.")]
+    PenaltyLimitReached,
+    #[msg("Only the issuing reporter can close a penalty before it expires.")]
+    PenaltyStillActive,
+    #[msg("The identity has active penalties.")]
+    ActivePenaltiesExist,
+    #[msg("The model's base weights must sum to 10000 basis points.")]
+    InvalidModelParams,
+    #[msg("The score model has been retired.")]
+    ScoreModelRetired,
+    #[msg("The active score model cannot be retired.")]
+    ScoreModelInUse,
+    #[msg("The score model account does not match the score.")]
+    ScoreModelMismatch,
+    #[msg("The score was produced by a model older than required.")]
+    ScoreModelTooOld,
+    #[msg("The score is below the required minimum.")]
+    ScoreBelowThreshold,
+    #[msg("The score was calculated too long ago.")]
+    ScoreTooOld,
+    #[msg("Level cutoffs must be non-zero and strictly ascending.")]
+    InvalidLevelCutoffs,
+    #[msg("Balance checkpoints require a positive averaging window and must fit in the history.")]
+    InvalidBalanceWindow,
+    #[msg("Not enough balance checkpoints in the averaging window.")]
+    InsufficientBalanceCheckpoints,
+    #[msg("The transaction contains instructions that may credit the owner.")]
+    SuspiciousTransaction,
+    #[msg("The owner has already checked in today.")]
+    AlreadyCheckedIn,
+    #[msg("The owner has not opted in to keeper refreshes.")]
+    KeeperRefreshDisabled,
+    #[msg("The batch accounts do not match the holding counts.")]
+    InvalidBatchAccounts,
+    #[msg("Consent scope must be non-empty and use only known bits.")]
+    InvalidConsentScope,
+    #[msg("Consent must expire in the future.")]
+    InvalidConsentExpiry,
+    #[msg("The owner did not sign and no valid consent was presented.")]
+    ConsentRequired,
+    #[msg("The deletion cooldown must be between zero and one year.")]
+    InvalidDeletionCooldown,
+    #[msg("The transfer request has not expired yet.")]
+    TransferNotExpired,
+    #[msg("The holding accounts do not match the owner's registered holdings.")]
+    UnregisteredHoldings,
+    #[msg("An owner appears more than once in the batch.")]
+    DuplicateBatchOwner,
+    #[msg("The minimum score interval must be between zero and thirty days.")]
+    InvalidScoreInterval,
+    #[msg("The identity cannot be transferred while it has outstanding loans.")]
+    OutstandingLoansExist,
 }
@@ -1,6 +1,90 @@
-use crate::state::ScoreLevel;
+use crate::state::{CreditEventKind, ModelParams, PenaltySeverity, ScoreBreakdown, ScoreLevel};
 use anchor_lang::prelude::*;
 
+#[event]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub config: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AllowedMintUpdated {
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub weight: u64,
+    pub usd_weight: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AllowedMintRemoved {
+    pub mint: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReporterAdded {
+    pub program: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReporterRemoved {
+    pub program: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreditEventReported {
+    pub owner: Pubkey,
+    pub identity: Pubkey,
+    pub reporter: Pubkey,
+    pub kind: CreditEventKind,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PenaltyAdded {
+    pub owner: Pubkey,
+    pub penalty: Pubkey,
+    pub index: u32,
+    pub reporter: Pubkey,
+    pub severity: PenaltySeverity,
+    pub reason: String,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PenaltyClosed {
+    pub owner: Pubkey,
+    pub penalty: Pubkey,
+    pub index: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ScoreModelCreated {
+    pub version: u32,
+    pub params: ModelParams,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ScoreModelActivated {
+    pub version: u32,
+    pub previous_version: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ScoreModelRetired {
+    pub version: u32,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct IdentityCreated {
     pub owner: Pubkey,
@@ -8,6 +92,24 @@ pub struct IdentityCreated {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct ReferralRecorded {
+    pub referrer_identity: Pubkey,
+    pub referee_owner: Pubkey,
+    pub referee_identity: Pubkey,
+    pub referred_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReferralVerified {
+    pub referrer_identity: Pubkey,
+    pub referee_owner: Pubkey,
+    pub referee_identity: Pubkey,
+    pub verified_referred_count: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct IdentityVerified {
     pub owner: Pubkey,
@@ -22,6 +124,54 @@ pub struct IdentityUnverified {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct IdentityDeactivated {
+    pub owner: Pubkey,
+    pub identity: Pubkey,
+    pub deletable_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct IdentityReactivated {
+    pub owner: Pubkey,
+    pub identity: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConsentGranted {
+    pub owner: Pubkey,
+    pub grantee: Pubkey,
+    pub scope: u8,
+    pub expires_at: Option<i64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConsentRevoked {
+    pub owner: Pubkey,
+    pub grantee: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CheckedIn {
+    pub owner: Pubkey,
+    pub identity: Pubkey,
+    pub streak: u32,
+    pub total_days: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BalanceCheckpointed {
+    pub owner: Pubkey,
+    pub identity: Pubkey,
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct ScoreCalculated {
     pub owner: Pubkey,
@@ -29,6 +179,71 @@ pub struct ScoreCalculated {
     pub score_account: Pubkey,
     pub score: u8,
     pub score_level: ScoreLevel,
+    pub model_version: u32,
+    pub breakdown: ScoreBreakdown,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ScoreRefreshed {
+    pub owner: Pubkey,
+    pub identity: Pubkey,
+    pub keeper: Pubkey,
+    /// Lamports paid to the keeper from the owner's tip vault
+    pub tip: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ScoresBatchRefreshed {
+    pub keeper: Pubkey,
+    pub refreshed: u16,
+    pub skipped: u16,
+    /// Lamports paid to the keeper across the batch
+    pub tips: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct KeeperRefreshUpdated {
+    pub owner: Pubkey,
+    pub identity: Pubkey,
+    pub enabled: bool,
+    pub holdings: Vec<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TipVaultFunded {
+    pub owner: Pubkey,
+    pub tip_vault: Pubkey,
+    pub amount: u64,
+    pub tip_lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TipVaultClosed {
+    pub owner: Pubkey,
+    pub tip_vault: Pubkey,
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ScoreLevelMigrated {
+    pub owner: Pubkey,
+    pub score_account: Pubkey,
+    pub previous_level: ScoreLevel,
+    pub score_level: ScoreLevel,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct IdentityMigrated {
+    pub owner: Pubkey,
+    pub identity: Pubkey,
     pub timestamp: i64,
 }
 
@@ -39,6 +254,18 @@ pub struct IdentityDeleted {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct TombstoneApplied {
+    pub owner: Pubkey,
+    pub identity: Pubkey,
+    pub tombstone: Pubkey,
+    pub deletion_count: u32,
+    pub negative_marks: u32,
+    pub penalty_points: u8,
+    pub eligible_at: i64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct ScoreDeleted {
     pub owner: Pubkey,
@@ -79,6 +306,15 @@ pub struct TransferClaimed {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct TransferRequestExpired {
+    pub from_owner: Pubkey,
+    pub to_owner: Pubkey,
+    pub identity: Pubkey,
+    pub transfer_request: Pubkey,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct TransferCancelled {
     pub from_owner: Pubkey,
@@ -0,0 +1,129 @@
+//! Typed wrapper for consumer programs that gate on a credit score.
+//!
+//! The owner must first grant the consumer program consent with the
+//! `CONSENT_SCOPE_MIN_SCORE` scope. The consumer proves it is the grantee by
+//! signing with its `[SEED_SCORE_GATE]` PDA, so it passes that PDA as
+//! `gate_authority`. Enable the `cpi` feature, then:
+//!
+//! ```ignore
+//! identity_score::gate::assert_min_score(
+//!     identity_score::gate::GateAccounts { /* ... */ },
+//!     identity_score::gate::ScoreRequirement {
+//!         min_score: 60,
+//!         max_age_seconds: 7 * 24 * 60 * 60,
+//!         min_model_version: 1,
+//!     },
+//!     ctx.program_id,
+//! )?;
+//! ```
+
+use crate::constants::*;
+use anchor_lang::prelude::*;
+
+/// Thresholds the consumer enforces
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScoreRequirement {
+    /// Minimum score, after decay and the owner's currently active penalties
+    pub min_score: u8,
+    /// Maximum seconds since the score was calculated
+    pub max_age_seconds: i64,
+    /// Oldest score model accepted
+    pub min_model_version: u32,
+}
+
+/// Accounts of the gate; derive the addresses with the helpers below
+pub struct GateAccounts<'info> {
+    pub identity_score_program: AccountInfo<'info>,
+    pub identity: AccountInfo<'info>,
+    pub owner: AccountInfo<'info>,
+    pub score_account: AccountInfo<'info>,
+    pub score_model: AccountInfo<'info>,
+    pub config: AccountInfo<'info>,
+    /// May not exist yet when the owner was never penalized
+    pub penalty_index: AccountInfo<'info>,
+    /// The consumer's `[SEED_SCORE_GATE]` PDA, signed for by the wrapper
+    pub gate_authority: AccountInfo<'info>,
+    pub consent: AccountInfo<'info>,
+}
+
+/// CPIs into `assert_min_score` on behalf of `consumer_program`, the program
+/// calling this wrapper, failing its instruction unless the owner meets
+/// `requirement`
+pub fn assert_min_score(
+    accounts: GateAccounts,
+    requirement: ScoreRequirement,
+    consumer_program: &Pubkey,
+) -> Result<()> {
+    require_keys_eq!(
+        accounts.identity_score_program.key(),
+        crate::ID,
+        ErrorCode::InvalidProgramId
+    );
+    let (gate_authority, bump) = Pubkey::find_program_address(&[SEED_SCORE_GATE], consumer_program);
+    require_keys_eq!(
+        accounts.gate_authority.key(),
+        gate_authority,
+        ErrorCode::ConstraintSeeds
+    );
+
+    let signer_seeds: &[&[&[u8]]] = &[&[SEED_SCORE_GATE, &[bump]]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        accounts.identity_score_program,
+        crate::cpi::accounts::AssertMinScore {
+            identity: accounts.identity,
+            owner: accounts.owner,
+            score_account: accounts.score_account,
+            score_model: accounts.score_model,
+            config: accounts.config,
+            penalty_index: accounts.penalty_index,
+            gate_authority: Some(accounts.gate_authority),
+            consent: Some(accounts.consent),
+        },
+        signer_seeds,
+    );
+    crate::cpi::assert_min_score(
+        cpi_ctx,
+        requirement.min_score,
+        requirement.max_age_seconds,
+        requirement.min_model_version,
+    )
+}
+
+pub fn identity_address(owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[SEED_IDENTITY, owner.as_ref()], &crate::ID).0
+}
+
+pub fn score_address(owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[SEED_SCORE, owner.as_ref()], &crate::ID).0
+}
+
+/// The model is the one recorded in the score account's `model_version`
+pub fn score_model_address(model_version: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[SEED_SCORE_MODEL, model_version.to_le_bytes().as_ref()],
+        &crate::ID,
+    )
+    .0
+}
+
+pub fn config_address() -> Pubkey {
+    Pubkey::find_program_address(&[SEED_CONFIG], &crate::ID).0
+}
+
+pub fn penalty_index_address(owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[SEED_PENALTY_INDEX, owner.as_ref()], &crate::ID).0
+}
+
+/// PDA `consumer_program` signs gate calls with
+pub fn gate_authority_address(consumer_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[SEED_SCORE_GATE], consumer_program).0
+}
+
+/// Consent the owner grants to `grantee`, the consumer program
+pub fn consent_address(owner: &Pubkey, grantee: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[SEED_CONSENT, owner.as_ref(), grantee.as_ref()],
+        &crate::ID,
+    )
+    .0
+}
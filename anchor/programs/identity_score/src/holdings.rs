@@ -0,0 +1,234 @@
+//! Off-wallet holdings passed to the scoring instructions via `remaining_accounts`.
+//!
+//! Token accounts are passed as `[token_account, allowed_mint]` pairs. Both the
+//! legacy SPL Token and Token-2022 layouts share the same 165-byte base account.
+//! Native stake accounts are passed on their own and count when the owner is
+//! their withdraw authority.
+
+use crate::errors::IdentityScoreError;
+use crate::state::AllowedMint;
+use anchor_lang::prelude::*;
+
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+pub const STAKE_PROGRAM_ID: Pubkey = pubkey!("Stake11111111111111111111111111111111111111");
+
+/// Upper bound on holding accounts per instruction, keeping compute predictable
+pub const MAX_HOLDING_ACCOUNTS: usize = 8;
+
+/// Largest mint decimals `AllowedMint::value_of` can scale by, as 10^38 is
+/// the largest power of ten that fits a u128
+pub const MAX_MINT_DECIMALS: u8 = 38;
+
+const MINT_LEN: usize = 82;
+const MINT_DECIMALS_OFFSET: usize = 44;
+const MINT_INITIALIZED_OFFSET: usize = 45;
+
+const TOKEN_ACCOUNT_LEN: usize = 165;
+const TOKEN_2022_ACCOUNT_TYPE_OFFSET: usize = TOKEN_ACCOUNT_LEN;
+const TOKEN_2022_ACCOUNT_TYPE_MINT: u8 = 1;
+const TOKEN_2022_ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+const TOKEN_STATE_OFFSET: usize = 108;
+const TOKEN_STATE_UNINITIALIZED: u8 = 0;
+
+/// `StakeStateV2` is bincode-encoded into a fixed 200-byte account
+const STAKE_ACCOUNT_LEN: usize = 200;
+const STAKE_STATE_DELEGATED: u32 = 2;
+const STAKE_WITHDRAWER_OFFSET: usize = 44;
+const STAKE_DELEGATED_AMOUNT_OFFSET: usize = 156;
+const STAKE_ACTIVATION_EPOCH_OFFSET: usize = 164;
+const STAKE_DEACTIVATION_EPOCH_OFFSET: usize = 172;
+
+/// Verified holdings of one owner
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Holdings {
+    /// Weighted lamport-equivalent value of allow-listed token balances
+    pub token_value: u64,
+    /// Micro-USD value of the same balances
+    pub token_usd_value: u64,
+    /// Lamports delegated from stake accounts the owner can withdraw
+    pub staked_lamports: u64,
+    /// Stake-weighted average number of epochs since activation
+    pub stake_age_epochs: u64,
+    /// Rent-exempt minimum of the holding accounts themselves
+    pub account_rent: u64,
+}
+
+/// Fields of a token account shared by SPL Token and Token-2022
+struct TokenAccountBase {
+    mint: Pubkey,
+    owner: Pubkey,
+    amount: u64,
+}
+
+/// Decimals of an initialized SPL Token or Token-2022 mint
+pub fn parse_mint_decimals(account: &AccountInfo) -> Result<u8> {
+    require!(
+        *account.owner == TOKEN_PROGRAM_ID || *account.owner == TOKEN_2022_PROGRAM_ID,
+        IdentityScoreError::MintNotAllowed
+    );
+    let data = account.try_borrow_data()?;
+
+    let layout_ok = if *account.owner == TOKEN_PROGRAM_ID {
+        data.len() == MINT_LEN
+    } else {
+        // Token-2022 mints with extensions are padded to the token account
+        // size and tagged with the mint account type
+        data.len() == MINT_LEN
+            || (data.len() > TOKEN_2022_ACCOUNT_TYPE_OFFSET
+                && data[TOKEN_2022_ACCOUNT_TYPE_OFFSET] == TOKEN_2022_ACCOUNT_TYPE_MINT)
+    };
+    require!(
+        layout_ok && data[MINT_INITIALIZED_OFFSET] == 1,
+        IdentityScoreError::MintNotAllowed
+    );
+
+    Ok(data[MINT_DECIMALS_OFFSET])
+}
+
+fn parse_token_account(account: &AccountInfo) -> Result<TokenAccountBase> {
+    let data = account.try_borrow_data()?;
+
+    let layout_ok = if *account.owner == TOKEN_PROGRAM_ID {
+        data.len() == TOKEN_ACCOUNT_LEN
+    } else {
+        // Token-2022 accounts with extensions carry an account-type byte after
+        // the base layout; that byte tells them apart from mints.
+        data.len() == TOKEN_ACCOUNT_LEN
+            || (data.len() > TOKEN_2022_ACCOUNT_TYPE_OFFSET
+                && data[TOKEN_2022_ACCOUNT_TYPE_OFFSET] == TOKEN_2022_ACCOUNT_TYPE_ACCOUNT)
+    };
+    require!(
+        layout_ok && data[TOKEN_STATE_OFFSET] != TOKEN_STATE_UNINITIALIZED,
+        IdentityScoreError::InvalidHoldingAccount
+    );
+
+    Ok(TokenAccountBase {
+        mint: Pubkey::try_from(&data[0..32]).unwrap(),
+        owner: Pubkey::try_from(&data[32..64]).unwrap(),
+        amount: u64::from_le_bytes(data[64..72].try_into().unwrap()),
+    })
+}
+
+/// Fields of an active delegation
+struct StakeDelegation {
+    withdrawer: Pubkey,
+    stake: u64,
+    activation_epoch: u64,
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+/// Only delegated stake that is not winding down is accepted
+fn parse_stake_account(account: &AccountInfo, current_epoch: u64) -> Result<StakeDelegation> {
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() == STAKE_ACCOUNT_LEN
+            && u32::from_le_bytes(data[0..4].try_into().unwrap()) == STAKE_STATE_DELEGATED,
+        IdentityScoreError::InvalidHoldingAccount
+    );
+
+    let activation_epoch = read_u64(&data, STAKE_ACTIVATION_EPOCH_OFFSET);
+    require!(
+        activation_epoch <= current_epoch
+            && read_u64(&data, STAKE_DEACTIVATION_EPOCH_OFFSET) == u64::MAX,
+        IdentityScoreError::InvalidHoldingAccount
+    );
+
+    Ok(StakeDelegation {
+        withdrawer: Pubkey::try_from(&data[STAKE_WITHDRAWER_OFFSET..STAKE_WITHDRAWER_OFFSET + 32])
+            .unwrap(),
+        stake: read_u64(&data, STAKE_DELEGATED_AMOUNT_OFFSET),
+        activation_epoch,
+    })
+}
+
+/// Sums the holdings `owner` proved through `remaining_accounts`. When
+/// `registered` is given, the holding accounts passed must be exactly those.
+pub fn collect_holdings(
+    owner: &Pubkey,
+    remaining_accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    current_epoch: u64,
+    registered: Option<&[Pubkey]>,
+) -> Result<Holdings> {
+    let rent = Rent::get()?;
+    let mut holdings = Holdings::default();
+    let mut stake_epochs_weighted: u128 = 0;
+    let mut seen: Vec<Pubkey> = Vec::new();
+    let mut accounts = remaining_accounts.iter();
+
+    while let Some(account) = accounts.next() {
+        require!(
+            !seen.contains(account.key),
+            IdentityScoreError::DuplicateHoldingAccount
+        );
+        require!(
+            seen.len() < MAX_HOLDING_ACCOUNTS,
+            IdentityScoreError::TooManyHoldingAccounts
+        );
+        seen.push(account.key());
+        holdings.account_rent = holdings
+            .account_rent
+            .saturating_add(rent.minimum_balance(account.data_len()));
+
+        if *account.owner == TOKEN_PROGRAM_ID || *account.owner == TOKEN_2022_PROGRAM_ID {
+            let token_account = parse_token_account(account)?;
+            require_keys_eq!(
+                token_account.owner,
+                *owner,
+                IdentityScoreError::InvalidHoldingAccount
+            );
+
+            let allowed_mint_info = accounts.next().ok_or(IdentityScoreError::MintNotAllowed)?;
+            require_keys_eq!(
+                *allowed_mint_info.owner,
+                *program_id,
+                IdentityScoreError::MintNotAllowed
+            );
+            let mut data_slice = &allowed_mint_info.data.borrow()[..];
+            let allowed_mint = AllowedMint::try_deserialize(&mut data_slice)?;
+            require_keys_eq!(
+                allowed_mint.mint,
+                token_account.mint,
+                IdentityScoreError::MintNotAllowed
+            );
+
+            holdings.token_value = holdings
+                .token_value
+                .saturating_add(allowed_mint.value_of(token_account.amount));
+            holdings.token_usd_value = holdings
+                .token_usd_value
+                .saturating_add(allowed_mint.usd_value_of(token_account.amount));
+        } else if *account.owner == STAKE_PROGRAM_ID {
+            let delegation = parse_stake_account(account, current_epoch)?;
+            require_keys_eq!(
+                delegation.withdrawer,
+                *owner,
+                IdentityScoreError::InvalidHoldingAccount
+            );
+
+            holdings.staked_lamports = holdings.staked_lamports.saturating_add(delegation.stake);
+            stake_epochs_weighted +=
+                delegation.stake as u128 * (current_epoch - delegation.activation_epoch) as u128;
+        } else {
+            return err!(IdentityScoreError::InvalidHoldingAccount);
+        }
+    }
+
+    if let Some(registered) = registered {
+        require!(
+            seen.len() == registered.len() && registered.iter().all(|key| seen.contains(key)),
+            IdentityScoreError::UnregisteredHoldings
+        );
+    }
+
+    if holdings.staked_lamports > 0 {
+        holdings.stake_age_epochs =
+            (stake_epochs_weighted / holdings.staked_lamports as u128) as u64;
+    }
+
+    Ok(holdings)
+}
@@ -0,0 +1,51 @@
+use crate::constants::*;
+use crate::errors::IdentityScoreError;
+use crate::events;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// Records that the owner was present today; at most once per day. Check-ins
+/// only weigh in under a score model with a non-zero `weight_activity`.
+pub fn check_in(ctx: Context<CheckIn>) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    let identity = &ctx.accounts.identity;
+    require!(
+        identity.is_active(),
+        IdentityScoreError::IdentityDeactivated
+    );
+
+    let activity = &mut ctx.accounts.activity;
+    activity.identity = identity.key();
+    activity.check_in(timestamp)?;
+
+    emit!(events::CheckedIn {
+        owner: ctx.accounts.owner.key(),
+        identity: identity.key(),
+        streak: activity.current_streak,
+        total_days: activity.total_days,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CheckIn<'info> {
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = Activity::SPACE,
+        seeds = [SEED_ACTIVITY, owner.key().as_ref()],
+        bump
+    )]
+    pub activity: Account<'info, Activity>,
+    #[account(
+        seeds = [SEED_IDENTITY, owner.key().as_ref()],
+        bump,
+        has_one = owner
+    )]
+    pub identity: Account<'info, IdentityAccount>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,65 @@
+use crate::constants::*;
+use crate::events;
+use crate::introspection::ensure_owner_not_credited;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// Records the owner's current balance; anyone may call it, so keepers can
+/// checkpoint on the owner's behalf. Calls less than
+/// BALANCE_CHECKPOINT_INTERVAL_SECONDS after the latest checkpoint only lower
+/// it, so the interval's minimum is what gets averaged. Like scoring, it
+/// refuses to share a transaction that may credit the owner.
+pub fn checkpoint_balance(ctx: Context<CheckpointBalance>) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    ensure_owner_not_credited(
+        &ctx.accounts.instructions,
+        &ctx.accounts.owner.key(),
+        ctx.program_id,
+    )?;
+
+    let lamports = ctx.accounts.owner.lamports();
+    let balance_history = &mut ctx.accounts.balance_history;
+    balance_history.identity = ctx.accounts.identity.key();
+    balance_history.record(
+        BalanceCheckpoint {
+            lamports,
+            timestamp,
+        },
+        BALANCE_CHECKPOINT_INTERVAL_SECONDS,
+    );
+
+    emit!(events::BalanceCheckpointed {
+        owner: ctx.accounts.owner.key(),
+        identity: ctx.accounts.identity.key(),
+        lamports,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CheckpointBalance<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = BalanceHistory::SPACE,
+        seeds = [SEED_BALANCE_HISTORY, owner.key().as_ref()],
+        bump
+    )]
+    pub balance_history: Account<'info, BalanceHistory>,
+    #[account(
+        seeds = [SEED_IDENTITY, owner.key().as_ref()],
+        bump,
+        has_one = owner
+    )]
+    pub identity: Account<'info, IdentityAccount>,
+    /// CHECK: Only its balance is read; bound to the identity via `has_one`
+    pub owner: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: Instructions sysvar, fixed by address
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
@@ -0,0 +1,328 @@
+use crate::constants::*;
+use crate::errors::IdentityScoreError;
+use crate::events;
+use crate::holdings::{parse_mint_decimals, MAX_MINT_DECIMALS};
+use crate::scoring::GENESIS_MODEL_VERSION;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// Creates the protocol config with the program's upgrade authority as admin
+pub fn initialize_config(ctx: Context<InitializeConfig>, params: ConfigParams) -> Result<()> {
+    params.validate()?;
+    let config = &mut ctx.accounts.config;
+
+    config.admin = ctx.accounts.admin.key();
+    config.apply(&params);
+    config.active_model_version = GENESIS_MODEL_VERSION;
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    let score_model = &mut ctx.accounts.score_model;
+    score_model.version = GENESIS_MODEL_VERSION;
+    score_model.params = ModelParams::default();
+    score_model.active = true;
+    score_model.created_at = timestamp;
+    score_model.retired_at = None;
+
+    emit!(events::ConfigUpdated {
+        admin: config.admin,
+        config: config.key(),
+        timestamp,
+    });
+
+    Ok(())
+}
+
+/// Stored score levels keep the cutoffs they were calculated under until the
+/// score is recalculated or `migrate_score_level` is called on it
+pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+    params.validate()?;
+    let config = &mut ctx.accounts.config;
+
+    config.apply(&params);
+
+    emit!(events::ConfigUpdated {
+        admin: config.admin,
+        config: config.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn create_score_model(
+    ctx: Context<CreateScoreModel>,
+    version: u32,
+    params: ModelParams,
+) -> Result<()> {
+    require!(params.is_valid(), IdentityScoreError::InvalidModelParams);
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    let score_model = &mut ctx.accounts.score_model;
+    score_model.version = version;
+    score_model.params = params;
+    score_model.active = true;
+    score_model.created_at = timestamp;
+    score_model.retired_at = None;
+
+    emit!(events::ScoreModelCreated {
+        version,
+        params,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+/// Switches new score calculations over to `score_model`
+pub fn activate_score_model(ctx: Context<ActivateScoreModel>) -> Result<()> {
+    let score_model = &ctx.accounts.score_model;
+    require!(score_model.active, IdentityScoreError::ScoreModelRetired);
+
+    let config = &mut ctx.accounts.config;
+    let previous_version = config.active_model_version;
+    config.active_model_version = score_model.version;
+
+    emit!(events::ScoreModelActivated {
+        version: score_model.version,
+        previous_version,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Marks a model retired; every score it produced reads as stale from now on
+pub fn retire_score_model(ctx: Context<RetireScoreModel>) -> Result<()> {
+    let score_model = &mut ctx.accounts.score_model;
+    require!(
+        score_model.version != ctx.accounts.config.active_model_version,
+        IdentityScoreError::ScoreModelInUse
+    );
+    require!(score_model.active, IdentityScoreError::ScoreModelRetired);
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    score_model.active = false;
+    score_model.retired_at = Some(timestamp);
+
+    emit!(events::ScoreModelRetired {
+        version: score_model.version,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+/// Resizes a program account, topping its rent up from `payer`; new bytes are zeroed
+pub(crate) fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    new_len: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let shortfall = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+    if shortfall > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    account.resize(new_len)?;
+    Ok(())
+}
+
+pub fn set_allowed_mint(
+    ctx: Context<SetAllowedMint>,
+    decimals: u8,
+    weight: u64,
+    usd_weight: u64,
+) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    require!(
+        parse_mint_decimals(mint)? == decimals && decimals <= MAX_MINT_DECIMALS,
+        IdentityScoreError::MintNotAllowed
+    );
+
+    let allowed_mint = &mut ctx.accounts.allowed_mint;
+    allowed_mint.mint = mint.key();
+    allowed_mint.decimals = decimals;
+    allowed_mint.weight = weight;
+    allowed_mint.usd_weight = usd_weight;
+
+    emit!(events::AllowedMintUpdated {
+        mint: mint.key(),
+        decimals,
+        weight,
+        usd_weight,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn remove_allowed_mint(ctx: Context<RemoveAllowedMint>) -> Result<()> {
+    emit!(events::AllowedMintRemoved {
+        mint: ctx.accounts.allowed_mint.mint,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = ProtocolConfig::SPACE,
+        seeds = [SEED_CONFIG],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(
+        init,
+        payer = admin,
+        space = ProtocolStats::SPACE,
+        seeds = [SEED_STATS],
+        bump
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+    #[account(
+        init,
+        payer = admin,
+        space = ScoreModel::SPACE,
+        seeds = [SEED_SCORE_MODEL, GENESIS_MODEL_VERSION.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub score_model: Account<'info, ScoreModel>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key())
+            @ IdentityScoreError::Unauthorized
+    )]
+    pub program: Program<'info, crate::program::IdentityScore>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ IdentityScoreError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_CONFIG],
+        bump,
+        has_one = admin @ IdentityScoreError::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAllowedMint<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = AllowedMint::SPACE,
+        seeds = [SEED_ALLOWED_MINT, mint.key().as_ref()],
+        bump
+    )]
+    pub allowed_mint: Account<'info, AllowedMint>,
+    /// CHECK: Owner program and decimals are verified in the instruction
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        seeds = [SEED_CONFIG],
+        bump,
+        has_one = admin @ IdentityScoreError::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveAllowedMint<'info> {
+    #[account(
+        mut,
+        close = admin,
+        seeds = [SEED_ALLOWED_MINT, allowed_mint.mint.as_ref()],
+        bump
+    )]
+    pub allowed_mint: Account<'info, AllowedMint>,
+    #[account(
+        seeds = [SEED_CONFIG],
+        bump,
+        has_one = admin @ IdentityScoreError::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(version: u32)]
+pub struct CreateScoreModel<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = ScoreModel::SPACE,
+        seeds = [SEED_SCORE_MODEL, version.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub score_model: Account<'info, ScoreModel>,
+    #[account(
+        seeds = [SEED_CONFIG],
+        bump,
+        has_one = admin @ IdentityScoreError::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ActivateScoreModel<'info> {
+    #[account(
+        seeds = [SEED_SCORE_MODEL, score_model.version.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub score_model: Account<'info, ScoreModel>,
+    #[account(
+        mut,
+        seeds = [SEED_CONFIG],
+        bump,
+        has_one = admin @ IdentityScoreError::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RetireScoreModel<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_SCORE_MODEL, score_model.version.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub score_model: Account<'info, ScoreModel>,
+    #[account(
+        seeds = [SEED_CONFIG],
+        bump,
+        has_one = admin @ IdentityScoreError::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub admin: Signer<'info>,
+}
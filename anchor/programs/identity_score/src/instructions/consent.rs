@@ -0,0 +1,81 @@
+use crate::constants::*;
+use crate::errors::IdentityScoreError;
+use crate::events;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// Lets `grantee` rely on the owner's score within `scope`; granting again
+/// replaces the scope and expiry
+pub fn grant_consent(
+    ctx: Context<GrantConsent>,
+    grantee: Pubkey,
+    scope: u8,
+    expires_at: Option<i64>,
+) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    require!(
+        scope != 0 && scope & !CONSENT_SCOPE_ALL == 0,
+        IdentityScoreError::InvalidConsentScope
+    );
+    require!(
+        expires_at.is_none_or(|expires_at| expires_at > timestamp),
+        IdentityScoreError::InvalidConsentExpiry
+    );
+
+    let consent = &mut ctx.accounts.consent;
+    consent.owner = ctx.accounts.owner.key();
+    consent.grantee = grantee;
+    consent.scope = scope;
+    consent.granted_at = timestamp;
+    consent.expires_at = expires_at;
+
+    emit!(events::ConsentGranted {
+        owner: consent.owner,
+        grantee,
+        scope,
+        expires_at,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn revoke_consent(ctx: Context<RevokeConsent>) -> Result<()> {
+    emit!(events::ConsentRevoked {
+        owner: ctx.accounts.owner.key(),
+        grantee: ctx.accounts.consent.grantee,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(grantee: Pubkey)]
+pub struct GrantConsent<'info> {
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = Consent::SPACE,
+        seeds = [SEED_CONSENT, owner.key().as_ref(), grantee.as_ref()],
+        bump
+    )]
+    pub consent: Account<'info, Consent>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeConsent<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_CONSENT, owner.key().as_ref(), consent.grantee.as_ref()],
+        bump,
+        has_one = owner,
+        close = owner
+    )]
+    pub consent: Account<'info, Consent>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
@@ -0,0 +1,149 @@
+use crate::constants::*;
+use crate::errors::IdentityScoreError;
+use crate::events;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+pub fn add_reporter(ctx: Context<AddReporter>, program: Pubkey) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    let (authority, _) = Pubkey::find_program_address(&[SEED_REPORTER_AUTHORITY], &program);
+
+    let reporter = &mut ctx.accounts.reporter;
+    reporter.program = program;
+    reporter.authority = authority;
+    reporter.added_at = timestamp;
+    reporter.events_reported = 0;
+
+    emit!(events::ReporterAdded {
+        program,
+        authority,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn remove_reporter(ctx: Context<RemoveReporter>) -> Result<()> {
+    emit!(events::ReporterRemoved {
+        program: ctx.accounts.reporter.program,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Called by a registered lending program via CPI, signing with its reporter
+/// authority PDA. A loan can only be resolved by the reporter that opened it.
+pub fn report_credit_event(ctx: Context<ReportCreditEvent>, kind: CreditEventKind) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    let identity = &ctx.accounts.identity;
+
+    let reporter_loans = &mut ctx.accounts.reporter_loans;
+    reporter_loans.owner = identity.owner;
+    reporter_loans.reporter = ctx.accounts.reporter.program;
+    if kind == CreditEventKind::LoanOpened {
+        reporter_loans.open_loans = reporter_loans.open_loans.saturating_add(1);
+    } else {
+        require!(
+            reporter_loans.open_loans > 0,
+            IdentityScoreError::NoOutstandingLoan
+        );
+        reporter_loans.open_loans -= 1;
+    }
+
+    let credit_history = &mut ctx.accounts.credit_history;
+
+    credit_history.owner = identity.owner;
+    credit_history.record(kind);
+    credit_history.last_event_at = timestamp;
+
+    let reporter = &mut ctx.accounts.reporter;
+    reporter.events_reported += 1;
+
+    emit!(events::CreditEventReported {
+        owner: identity.owner,
+        identity: identity.key(),
+        reporter: reporter.program,
+        kind,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(program: Pubkey)]
+pub struct AddReporter<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = Reporter::SPACE,
+        seeds = [SEED_REPORTER, program.as_ref()],
+        bump
+    )]
+    pub reporter: Account<'info, Reporter>,
+    #[account(
+        seeds = [SEED_CONFIG],
+        bump,
+        has_one = admin @ IdentityScoreError::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveReporter<'info> {
+    #[account(
+        mut,
+        close = admin,
+        seeds = [SEED_REPORTER, reporter.program.as_ref()],
+        bump
+    )]
+    pub reporter: Account<'info, Reporter>,
+    #[account(
+        seeds = [SEED_CONFIG],
+        bump,
+        has_one = admin @ IdentityScoreError::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReportCreditEvent<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_REPORTER, reporter.program.as_ref()],
+        bump,
+        has_one = authority @ IdentityScoreError::Unauthorized
+    )]
+    pub reporter: Account<'info, Reporter>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [SEED_IDENTITY, identity.owner.as_ref()],
+        bump
+    )]
+    pub identity: Account<'info, IdentityAccount>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = CreditHistory::SPACE,
+        seeds = [SEED_CREDIT_HISTORY, identity.owner.as_ref()],
+        bump
+    )]
+    pub credit_history: Account<'info, CreditHistory>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = ReporterLoans::SPACE,
+        seeds = [SEED_REPORTER_LOANS, identity.owner.as_ref(), reporter.program.as_ref()],
+        bump
+    )]
+    pub reporter_loans: Account<'info, ReporterLoans>,
+}
@@ -1,4 +1,5 @@
 use crate::constants::*;
+use crate::errors::IdentityScoreError;
 use crate::state::*;
 use anchor_lang::prelude::*;
 
@@ -11,6 +12,14 @@ pub fn create_identity(ctx: Context<CreateIdentity>) -> Result<()> {
     identity.created_at = timestamp;
     identity.verified = false;
     identity.verified_at = None;
+    identity.referrer = None;
+    identity.referral_credited = false;
+    identity.deactivated_at = None;
+    identity.penalty_points = 0;
+    identity.eligible_at = timestamp;
+    identity.keeper_refresh = false;
+
+    ctx.accounts.stats.total_identities += 1;
 
     emit!(crate::events::IdentityCreated {
         owner: owner.key(),
@@ -18,6 +27,54 @@ pub fn create_identity(ctx: Context<CreateIdentity>) -> Result<()> {
         timestamp,
     });
 
+    // Owners who deleted an identity before inherit a penalty and waiting
+    // period from their tombstone, so deleting cannot wash out a bad record.
+    let tombstone = &ctx.accounts.tombstone;
+    if tombstone.data_len() > 0 {
+        require_keys_eq!(
+            *tombstone.owner,
+            *ctx.program_id,
+            IdentityScoreError::Unauthorized
+        );
+        let mut data_slice = &tombstone.data.borrow()[..];
+        let tombstone_state = Tombstone::try_deserialize(&mut data_slice)?;
+
+        identity.penalty_points = tombstone_state.penalty_points();
+        identity.eligible_at = tombstone_state.eligible_at().max(timestamp);
+
+        emit!(crate::events::TombstoneApplied {
+            owner: owner.key(),
+            identity: identity.key(),
+            tombstone: tombstone.key(),
+            deletion_count: tombstone_state.deletion_count,
+            negative_marks: tombstone_state.negative_marks,
+            penalty_points: identity.penalty_points,
+            eligible_at: identity.eligible_at,
+            timestamp,
+        });
+    }
+
+    match (
+        &ctx.accounts.referrer_identity,
+        &mut ctx.accounts.referral_stats,
+    ) {
+        (Some(referrer_identity), Some(referral_stats)) => {
+            referral_stats.referrer_identity = referrer_identity.key();
+            referral_stats.referred_count += 1;
+            identity.referrer = Some(referrer_identity.key());
+
+            emit!(crate::events::ReferralRecorded {
+                referrer_identity: referrer_identity.key(),
+                referee_owner: owner.key(),
+                referee_identity: identity.key(),
+                referred_count: referral_stats.referred_count,
+                timestamp,
+            });
+        }
+        (None, None) => {}
+        _ => return err!(IdentityScoreError::InvalidReferrer),
+    }
+
     Ok(())
 }
 
@@ -26,6 +83,10 @@ pub fn verify_identity(ctx: Context<VerifyIdentity>) -> Result<()> {
     let owner = &ctx.accounts.owner;
     let timestamp = Clock::get()?.unix_timestamp;
 
+    if !identity.verified {
+        ctx.accounts.stats.verified_identities += 1;
+    }
+
     identity.verified = true;
     identity.verified_at = Some(timestamp);
 
@@ -35,6 +96,34 @@ pub fn verify_identity(ctx: Context<VerifyIdentity>) -> Result<()> {
         timestamp,
     });
 
+    // A referral is credited the first time the referee is verified, so
+    // toggling verification cannot inflate the referrer's count.
+    if let Some(referrer) = identity.referrer {
+        if !identity.referral_credited {
+            let referral_stats = ctx
+                .accounts
+                .referral_stats
+                .as_mut()
+                .ok_or(IdentityScoreError::InvalidReferrer)?;
+            require_keys_eq!(
+                referral_stats.referrer_identity,
+                referrer,
+                IdentityScoreError::InvalidReferrer
+            );
+
+            referral_stats.verified_referred_count += 1;
+            identity.referral_credited = true;
+
+            emit!(crate::events::ReferralVerified {
+                referrer_identity: referrer,
+                referee_owner: owner.key(),
+                referee_identity: identity.key(),
+                verified_referred_count: referral_stats.verified_referred_count,
+                timestamp,
+            });
+        }
+    }
+
     Ok(())
 }
 
@@ -43,6 +132,11 @@ pub fn unverify_identity(ctx: Context<UnverifyIdentity>) -> Result<()> {
     let owner = &ctx.accounts.owner;
     let timestamp = Clock::get()?.unix_timestamp;
 
+    if identity.verified {
+        let stats = &mut ctx.accounts.stats;
+        stats.verified_identities = stats.verified_identities.saturating_sub(1);
+    }
+
     identity.verified = false;
     identity.verified_at = None;
 
@@ -55,18 +149,75 @@ pub fn unverify_identity(ctx: Context<UnverifyIdentity>) -> Result<()> {
     Ok(())
 }
 
+pub fn deactivate_identity(ctx: Context<DeactivateIdentity>) -> Result<()> {
+    let identity = &mut ctx.accounts.identity;
+    let owner = &ctx.accounts.owner;
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    require!(
+        identity.is_active(),
+        IdentityScoreError::IdentityDeactivated
+    );
+
+    identity.deactivated_at = Some(timestamp);
+
+    emit!(crate::events::IdentityDeactivated {
+        owner: owner.key(),
+        identity: identity.key(),
+        deletable_at: timestamp.saturating_add(ctx.accounts.config.deletion_cooldown_seconds),
+        timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn reactivate_identity(ctx: Context<ReactivateIdentity>) -> Result<()> {
+    let identity = &mut ctx.accounts.identity;
+    let owner = &ctx.accounts.owner;
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    let deactivated_at = identity
+        .deactivated_at
+        .ok_or(IdentityScoreError::IdentityNotDeactivated)?;
+    require!(
+        timestamp < deactivated_at.saturating_add(ctx.accounts.config.deletion_cooldown_seconds),
+        IdentityScoreError::ReactivationWindowElapsed
+    );
+
+    identity.deactivated_at = None;
+
+    emit!(crate::events::IdentityReactivated {
+        owner: owner.key(),
+        identity: identity.key(),
+        timestamp,
+    });
+
+    Ok(())
+}
+
 pub fn delete_identity(ctx: Context<DeleteIdentity>) -> Result<()> {
     let owner = &ctx.accounts.owner;
     let identity = &ctx.accounts.identity;
     let score_account = &ctx.accounts.score_account;
 
+    // Deletion is only possible once the identity has sat deactivated for the
+    // full cooldown, so a bad record cannot be shed instantly.
+    let deactivated_at = identity
+        .deactivated_at
+        .ok_or(IdentityScoreError::IdentityNotDeactivated)?;
+    require!(
+        Clock::get()?.unix_timestamp
+            >= deactivated_at.saturating_add(ctx.accounts.config.deletion_cooldown_seconds),
+        IdentityScoreError::DeletionCooldownActive
+    );
+
     // Verify score account address manually to allow uninitialized accounts
     let (score_pda, _) =
         Pubkey::find_program_address(&[SEED_SCORE, owner.key().as_ref()], ctx.program_id);
     require_keys_eq!(
         score_account.key(),
         score_pda,
-        crate::errors::IdentityScoreError::Unauthorized
+        IdentityScoreError::Unauthorized
     );
 
     let timestamp = Clock::get()?.unix_timestamp;
@@ -76,7 +227,7 @@ pub fn delete_identity(ctx: Context<DeleteIdentity>) -> Result<()> {
         require_keys_eq!(
             *score_account.owner,
             *ctx.program_id,
-            crate::errors::IdentityScoreError::Unauthorized
+            IdentityScoreError::Unauthorized
         );
         // Deserialize score account to verify identity match
         let mut data_slice = &score_account.data.borrow()[..];
@@ -85,15 +236,44 @@ pub fn delete_identity(ctx: Context<DeleteIdentity>) -> Result<()> {
         // Verify score account belongs to this identity
         require!(
             score_state.identity == identity.key(),
-            crate::errors::IdentityScoreError::Unauthorized
+            IdentityScoreError::Unauthorized
         );
 
+        ctx.accounts.stats.remove_level(score_state.score_level);
+
         // Transfer lamports from score account to owner
         let score_lamports = score_account.lamports();
         **score_account.lamports.borrow_mut() = 0;
         **owner.to_account_info().lamports.borrow_mut() += score_lamports;
     }
 
+    let stats = &mut ctx.accounts.stats;
+    stats.total_identities = stats.total_identities.saturating_sub(1);
+    if identity.verified {
+        stats.verified_identities = stats.verified_identities.saturating_sub(1);
+    }
+
+    crate::instructions::score::close_score_history(
+        &ctx.accounts.score_history,
+        &owner.to_account_info(),
+        identity.key(),
+        ctx.program_id,
+    )?;
+
+    // Marks come from the owner's record rather than the momentary score, so
+    // waiting for a good day before deleting does not clear them
+    let (active_penalties, defaults) = load_negative_record(
+        &ctx.accounts.penalty_index,
+        &ctx.accounts.credit_history,
+        ctx.program_id,
+        timestamp,
+    )?;
+    let tombstone = &mut ctx.accounts.tombstone;
+    tombstone.owner = owner.key();
+    tombstone.record_marks(identity.penalty_points, active_penalties, defaults);
+    tombstone.deletion_count += 1;
+    tombstone.last_deleted_at = timestamp;
+
     emit!(crate::events::IdentityDeleted {
         owner: owner.key(),
         identity: identity.key(),
@@ -103,19 +283,122 @@ pub fn delete_identity(ctx: Context<DeleteIdentity>) -> Result<()> {
     Ok(())
 }
 
+/// Grows an identity created before referrals to the current size and counts
+/// it in the protocol stats, which did not exist yet when it was created. A
+/// no-op for an identity already at the current size; anyone may pay for it.
+pub fn migrate_identity(ctx: Context<MigrateIdentity>) -> Result<()> {
+    let identity_info = ctx.accounts.identity.to_account_info();
+    require_keys_eq!(
+        *identity_info.owner,
+        *ctx.program_id,
+        IdentityScoreError::Unauthorized
+    );
+    if identity_info.data_len() != IdentityAccount::LEGACY_SPACE {
+        return Ok(());
+    }
+
+    crate::instructions::config::grow_account(
+        &identity_info,
+        IdentityAccount::SPACE,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+    )?;
+    let mut data_slice = &identity_info.data.borrow()[..];
+    let identity = IdentityAccount::try_deserialize(&mut data_slice)?;
+
+    let stats = &mut ctx.accounts.stats;
+    stats.total_identities += 1;
+    if identity.verified {
+        stats.verified_identities += 1;
+    }
+
+    emit!(crate::events::IdentityMigrated {
+        owner: ctx.accounts.owner.key(),
+        identity: identity_info.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Counts the owner's active reporter penalties and defaulted loans; the
+/// PDAs are checked by the accounts struct and may not exist yet
+fn load_negative_record(
+    penalty_index: &AccountInfo,
+    credit_history: &AccountInfo,
+    program_id: &Pubkey,
+    now: i64,
+) -> Result<(u32, u32)> {
+    let active_penalties = if penalty_index.data_len() > 0 {
+        require_keys_eq!(
+            *penalty_index.owner,
+            *program_id,
+            IdentityScoreError::Unauthorized
+        );
+        let mut data_slice = &penalty_index.data.borrow()[..];
+        PenaltyIndex::try_deserialize(&mut data_slice)?.active_count(now)
+    } else {
+        0
+    };
+
+    let defaults = if credit_history.data_len() > 0 {
+        require_keys_eq!(
+            *credit_history.owner,
+            *program_id,
+            IdentityScoreError::Unauthorized
+        );
+        let mut data_slice = &credit_history.data.borrow()[..];
+        CreditHistory::try_deserialize(&mut data_slice)?.defaulted
+    } else {
+        0
+    };
+
+    Ok((active_penalties, defaults))
+}
+
 #[derive(Accounts)]
 pub struct CreateIdentity<'info> {
     #[account(
         init,
         payer = owner,
-        space = 8 + 32 + 8 + 1 + 9, // discriminator + pubkey + i64 + bool + Option<i64>
+        space = IdentityAccount::SPACE,
         seeds = [SEED_IDENTITY, owner.key().as_ref()],
         bump
     )]
     pub identity: Account<'info, IdentityAccount>,
+
+    /// Tombstone left by earlier deletions - may be uninitialized
+    /// CHECK: Address verified via PDA seeds, deserialized only when initialized
+    #[account(seeds = [SEED_TOMBSTONE, owner.key().as_ref()], bump)]
+    pub tombstone: UncheckedAccount<'info>,
+
     #[account(mut)]
     pub owner: Signer<'info>,
     pub system_program: Program<'info, System>,
+    #[account(mut, seeds = [SEED_STATS], bump)]
+    pub stats: Account<'info, ProtocolStats>,
+
+    /// Identity of the user who referred this owner, if any
+    pub referrer_identity: Option<Account<'info, IdentityAccount>>,
+
+    /// Referral counters of the referrer, created on the first referral. Its
+    /// seeds fall back to the default key without a referrer, so passing it
+    /// alone fails the seeds check instead of panicking.
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = ReferralStats::SPACE,
+        seeds = [
+            SEED_REFERRAL_STATS,
+            referrer_identity
+                .as_ref()
+                .map(|identity| identity.key())
+                .unwrap_or_default()
+                .as_ref()
+        ],
+        bump
+    )]
+    pub referral_stats: Option<Account<'info, ReferralStats>>,
 }
 
 #[derive(Accounts)]
@@ -128,6 +411,13 @@ pub struct VerifyIdentity<'info> {
     )]
     pub identity: Account<'info, IdentityAccount>,
     pub owner: Signer<'info>,
+    #[account(mut, seeds = [SEED_STATS], bump)]
+    pub stats: Account<'info, ProtocolStats>,
+
+    /// Referral counters of the identity's referrer, required on the first
+    /// verification of a referred identity
+    #[account(mut)]
+    pub referral_stats: Option<Account<'info, ReferralStats>>,
 }
 
 #[derive(Accounts)]
@@ -140,6 +430,36 @@ pub struct UnverifyIdentity<'info> {
     )]
     pub identity: Account<'info, IdentityAccount>,
     pub owner: Signer<'info>,
+    #[account(mut, seeds = [SEED_STATS], bump)]
+    pub stats: Account<'info, ProtocolStats>,
+}
+
+#[derive(Accounts)]
+pub struct DeactivateIdentity<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_IDENTITY, owner.key().as_ref()],
+        bump,
+        has_one = owner
+    )]
+    pub identity: Account<'info, IdentityAccount>,
+    pub owner: Signer<'info>,
+    #[account(seeds = [SEED_CONFIG], bump)]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ReactivateIdentity<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_IDENTITY, owner.key().as_ref()],
+        bump,
+        has_one = owner
+    )]
+    pub identity: Account<'info, IdentityAccount>,
+    pub owner: Signer<'info>,
+    #[account(seeds = [SEED_CONFIG], bump)]
+    pub config: Account<'info, ProtocolConfig>,
 }
 
 #[derive(Accounts)]
@@ -158,7 +478,52 @@ pub struct DeleteIdentity<'info> {
     #[account(mut)]
     pub score_account: AccountInfo<'info>,
 
+    /// Score history info - verified manually to allow uninitialized account
+    /// CHECK: Verified in instruction
+    #[account(mut)]
+    pub score_history: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = Tombstone::SPACE,
+        seeds = [SEED_TOMBSTONE, owner.key().as_ref()],
+        bump
+    )]
+    pub tombstone: Account<'info, Tombstone>,
+
     #[account(mut)]
     pub owner: Signer<'info>,
     pub system_program: Program<'info, System>,
+
+    #[account(seeds = [SEED_CONFIG], bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut, seeds = [SEED_STATS], bump)]
+    pub stats: Account<'info, ProtocolStats>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_PENALTY_INDEX, owner.key().as_ref()], bump)]
+    pub penalty_index: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_CREDIT_HISTORY, owner.key().as_ref()], bump)]
+    pub credit_history: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateIdentity<'info> {
+    /// CHECK: Sized by the instruction before it is deserialized
+    #[account(mut, seeds = [SEED_IDENTITY, owner.key().as_ref()], bump)]
+    pub identity: UncheckedAccount<'info>,
+
+    /// CHECK: Only used to derive the identity address
+    pub owner: UncheckedAccount<'info>,
+
+    /// Tops up the rent of the grown account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    #[account(mut, seeds = [SEED_STATS], bump)]
+    pub stats: Account<'info, ProtocolStats>,
 }
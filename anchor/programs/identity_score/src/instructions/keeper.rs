@@ -0,0 +1,172 @@
+use crate::constants::*;
+use crate::errors::IdentityScoreError;
+use crate::events;
+use crate::holdings::MAX_HOLDING_ACCOUNTS;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// Opts the owner in or out of permissionless `refresh_score` calls and
+/// registers the holding accounts keepers must pass
+pub fn set_keeper_refresh(
+    ctx: Context<SetKeeperRefresh>,
+    enabled: bool,
+    holdings: Vec<Pubkey>,
+) -> Result<()> {
+    require!(
+        holdings.len() <= MAX_HOLDING_ACCOUNTS,
+        IdentityScoreError::TooManyHoldingAccounts
+    );
+    for (i, holding) in holdings.iter().enumerate() {
+        require!(
+            !holdings[..i].contains(holding),
+            IdentityScoreError::DuplicateHoldingAccount
+        );
+    }
+
+    let identity = &mut ctx.accounts.identity;
+    identity.keeper_refresh = enabled;
+
+    let holding_registry = &mut ctx.accounts.holding_registry;
+    holding_registry.owner = ctx.accounts.owner.key();
+    holding_registry.holdings = holdings.clone();
+
+    emit!(events::KeeperRefreshUpdated {
+        owner: ctx.accounts.owner.key(),
+        identity: identity.key(),
+        enabled,
+        holdings,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Deposits `amount` into the owner's tip vault, creating it if needed, and
+/// sets the tip paid per refresh
+pub fn fund_tip_vault(ctx: Context<FundTipVault>, amount: u64, tip_lamports: u64) -> Result<()> {
+    let owner = &ctx.accounts.owner;
+    let tip_vault = &mut ctx.accounts.tip_vault;
+    tip_vault.owner = owner.key();
+    tip_vault.tip_lamports = tip_lamports;
+
+    if amount > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: owner.to_account_info(),
+                    to: tip_vault.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+    }
+
+    emit!(events::TipVaultFunded {
+        owner: owner.key(),
+        tip_vault: tip_vault.key(),
+        amount,
+        tip_lamports,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Returns everything in the tip vault, rent included, to the owner
+pub fn close_tip_vault(ctx: Context<CloseTipVault>) -> Result<()> {
+    emit!(events::TipVaultClosed {
+        owner: ctx.accounts.owner.key(),
+        tip_vault: ctx.accounts.tip_vault.key(),
+        lamports: ctx.accounts.tip_vault.to_account_info().lamports(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Reads the tip the owner offers keepers; the PDA is checked by the caller
+/// and may not exist yet, offering none
+pub(crate) fn load_tip(tip_vault: &AccountInfo, program_id: &Pubkey) -> Result<u64> {
+    if tip_vault.data_len() == 0 {
+        return Ok(0);
+    }
+    require_keys_eq!(
+        *tip_vault.owner,
+        *program_id,
+        IdentityScoreError::Unauthorized
+    );
+
+    let mut data_slice = &tip_vault.data.borrow()[..];
+    Ok(TipVault::try_deserialize(&mut data_slice)?.tip_lamports)
+}
+
+/// Moves `tip` from the vault to the keeper; pays nothing rather than failing
+/// when the vault cannot cover it above its rent-exempt minimum
+pub(crate) fn pay_tip<'info>(
+    vault_info: &AccountInfo<'info>,
+    keeper: &AccountInfo<'info>,
+    tip: u64,
+) -> Result<u64> {
+    let available = vault_info
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(TipVault::SPACE));
+    if tip == 0 || tip > available {
+        return Ok(0);
+    }
+
+    **vault_info.lamports.borrow_mut() -= tip;
+    **keeper.lamports.borrow_mut() += tip;
+    Ok(tip)
+}
+
+#[derive(Accounts)]
+pub struct SetKeeperRefresh<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_IDENTITY, owner.key().as_ref()],
+        bump,
+        has_one = owner
+    )]
+    pub identity: Account<'info, IdentityAccount>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = HoldingRegistry::SPACE,
+        seeds = [SEED_HOLDING_REGISTRY, owner.key().as_ref()],
+        bump
+    )]
+    pub holding_registry: Account<'info, HoldingRegistry>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundTipVault<'info> {
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = TipVault::SPACE,
+        seeds = [SEED_TIP_VAULT, owner.key().as_ref()],
+        bump
+    )]
+    pub tip_vault: Account<'info, TipVault>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseTipVault<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_TIP_VAULT, owner.key().as_ref()],
+        bump,
+        has_one = owner,
+        close = owner
+    )]
+    pub tip_vault: Account<'info, TipVault>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
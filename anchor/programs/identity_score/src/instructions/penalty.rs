@@ -0,0 +1,156 @@
+use crate::constants::*;
+use crate::errors::IdentityScoreError;
+use crate::events;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// Called by a registered reporter via CPI, signing with its reporter
+/// authority PDA
+pub fn add_penalty(
+    ctx: Context<AddPenalty>,
+    severity: PenaltySeverity,
+    reason: String,
+    expires_at: i64,
+) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    require!(
+        reason.len() <= PENALTY_REASON_MAX_LEN,
+        IdentityScoreError::PenaltyReasonTooLong
+    );
+    require!(
+        expires_at > timestamp,
+        IdentityScoreError::InvalidPenaltyExpiry
+    );
+
+    let owner = ctx.accounts.identity.owner;
+    let penalty_index = &mut ctx.accounts.penalty_index;
+    penalty_index.prune_expired(timestamp);
+    require!(
+        penalty_index.open.len() < MAX_OPEN_PENALTIES,
+        IdentityScoreError::PenaltyLimitReached
+    );
+
+    let index = penalty_index.next_index;
+    penalty_index.owner = owner;
+    penalty_index.next_index += 1;
+    penalty_index.open.push(OpenPenalty {
+        index,
+        severity,
+        expires_at,
+    });
+
+    let penalty = &mut ctx.accounts.penalty;
+    penalty.owner = owner;
+    penalty.index = index;
+    penalty.reporter = ctx.accounts.reporter.program;
+    penalty.severity = severity;
+    penalty.reason = reason.clone();
+    penalty.created_at = timestamp;
+    penalty.expires_at = expires_at;
+    penalty.payer = ctx.accounts.payer.key();
+
+    ctx.accounts.reporter.events_reported += 1;
+
+    emit!(events::PenaltyAdded {
+        owner,
+        penalty: penalty.key(),
+        index,
+        reporter: penalty.reporter,
+        severity,
+        reason,
+        expires_at,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+/// Closes a penalty once it has expired, or at any time by its issuer, and
+/// refunds the rent to whoever paid for it
+pub fn close_penalty(ctx: Context<ClosePenalty>) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    let penalty = &ctx.accounts.penalty;
+
+    if penalty.is_active(timestamp) {
+        let (issuer_authority, _) =
+            Pubkey::find_program_address(&[SEED_REPORTER_AUTHORITY], &penalty.reporter);
+        require_keys_eq!(
+            ctx.accounts.closer.key(),
+            issuer_authority,
+            IdentityScoreError::PenaltyStillActive
+        );
+    }
+
+    ctx.accounts.penalty_index.remove(penalty.index);
+
+    emit!(events::PenaltyClosed {
+        owner: penalty.owner,
+        penalty: penalty.key(),
+        index: penalty.index,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AddPenalty<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_REPORTER, reporter.program.as_ref()],
+        bump,
+        has_one = authority @ IdentityScoreError::Unauthorized
+    )]
+    pub reporter: Account<'info, Reporter>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [SEED_IDENTITY, identity.owner.as_ref()],
+        bump
+    )]
+    pub identity: Account<'info, IdentityAccount>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = PenaltyIndex::SPACE,
+        seeds = [SEED_PENALTY_INDEX, identity.owner.as_ref()],
+        bump
+    )]
+    pub penalty_index: Account<'info, PenaltyIndex>,
+    #[account(
+        init,
+        payer = payer,
+        space = Penalty::SPACE,
+        seeds = [
+            SEED_PENALTY,
+            identity.owner.as_ref(),
+            penalty_index.next_index.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub penalty: Account<'info, Penalty>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePenalty<'info> {
+    #[account(
+        mut,
+        close = payer,
+        seeds = [SEED_PENALTY, penalty.owner.as_ref(), penalty.index.to_le_bytes().as_ref()],
+        bump,
+        has_one = payer
+    )]
+    pub penalty: Account<'info, Penalty>,
+    #[account(
+        mut,
+        seeds = [SEED_PENALTY_INDEX, penalty.owner.as_ref()],
+        bump
+    )]
+    pub penalty_index: Account<'info, PenaltyIndex>,
+    /// CHECK: Rent receiver, bound to the penalty via `has_one`
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+    pub closer: Signer<'info>,
+}
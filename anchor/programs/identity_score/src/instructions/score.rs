@@ -1,117 +1,908 @@
 use crate::constants::*;
 use crate::errors::IdentityScoreError;
 use crate::events;
+use crate::holdings::*;
+use crate::instructions::keeper::{load_tip, pay_tip};
+use crate::introspection::ensure_owner_not_credited;
+use crate::oracle::{read_sol_price, SolPrice};
+use crate::scoring::*;
 use crate::state::*;
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT};
 
-const LN_MAX_LAMPORTS: f64 = 23.025850929940457;
-const MAX_AGE_SECONDS: i64 = 365 * 24 * 60 * 60;
-const RENT_EXEMPT_PER_BYTE: u64 = 2_000_000;
+pub fn calculate_score_from_lamports(lamports: u64) -> (u8, ScoreLevel) {
+    calculate_comprehensive_score(
+        &ScoreInputs {
+            lamports,
+            is_verified: true,
+            footprint_rent: Rent::get()
+                .unwrap()
+                .minimum_balance(CreditScoreAccount::SPACE),
+            current_timestamp: Clock::get().unwrap().unix_timestamp,
+            ..Default::default()
+        },
+        &LevelCutoffs::default(),
+    )
+}
 
-const WEIGHT_ASSET: f64 = 0.4;
-const WEIGHT_STABILITY: f64 = 0.3;
-const WEIGHT_RENT_EFFICIENCY: f64 = 0.2;
-const WEIGHT_VERIFICATION: f64 = 0.1;
+pub fn calculate_score(ctx: Context<CalculateScore>) -> Result<()> {
+    let clock = Clock::get()?;
+    let timestamp = clock.unix_timestamp;
 
-const SCORE_MIN: f64 = 30.0;
-const SCORE_RANGE: f64 = 60.0;
+    ensure_owner_not_credited(
+        &ctx.accounts.instructions,
+        &ctx.accounts.owner.key(),
+        ctx.program_id,
+    )?;
 
-fn normalize_score(normalized: f64) -> u8 {
-    (SCORE_MIN + SCORE_RANGE * normalized.clamp(0.0, 1.0)) as u8
+    let score_account = &ctx.accounts.score_account;
+    if score_account.is_initialized() {
+        require!(
+            score_account
+                .cooldown_elapsed(timestamp, ctx.accounts.config.min_score_interval_seconds),
+            IdentityScoreError::ScoreCooldownActive
+        );
+    }
+
+    let computed = score_owner(
+        &ScoredOwner {
+            owner: ctx.accounts.owner.key(),
+            lamports: ctx.accounts.owner.lamports(),
+            identity: &ctx.accounts.identity,
+            credit_history: &ctx.accounts.credit_history,
+            penalty_index: &ctx.accounts.penalty_index,
+            balance_history: &ctx.accounts.balance_history,
+            activity: &ctx.accounts.activity,
+            holding_registry: &ctx.accounts.holding_registry,
+            tip_vault: &ctx.accounts.tip_vault,
+            holdings: ctx.remaining_accounts,
+            registered: None,
+        },
+        &ctx.accounts.config,
+        &ctx.accounts.score_model,
+        load_sol_price(&ctx.accounts.config, &ctx.accounts.price_feed, timestamp)?,
+        ctx.program_id,
+        &clock,
+    )?;
+
+    store_score(
+        &mut ctx.accounts.score_account,
+        &mut ctx.accounts.score_history,
+        &mut ctx.accounts.stats,
+        ctx.accounts.config.score_validity_seconds,
+        &ctx.accounts.identity,
+        computed,
+        timestamp,
+    )
 }
 
-pub fn calculate_comprehensive_score(
-    lamports: u64,
-    identity_created_at: i64,
-    is_verified: bool,
-    account_data_len: u64,
-    current_timestamp: i64,
-) -> (u8, ScoreLevel) {
-    let total_score = (calculate_asset_score(lamports) as f64 * WEIGHT_ASSET
-        + calculate_stability_score(identity_created_at, is_verified, current_timestamp) as f64
-            * WEIGHT_STABILITY
-        + calculate_rent_efficiency_score(lamports, account_data_len) as f64
-            * WEIGHT_RENT_EFFICIENCY
-        + calculate_verification_score(is_verified) as f64 * WEIGHT_VERIFICATION)
-        as u8;
-
-    let level = if total_score >= 70 {
-        ScoreLevel::High
-    } else if total_score >= 50 {
-        ScoreLevel::Medium
-    } else {
-        ScoreLevel::Low
-    };
+/// Admin override that recalculates an identity's score regardless of the cooldown
+pub fn force_calculate_score(ctx: Context<ForceCalculateScore>) -> Result<()> {
+    let clock = Clock::get()?;
+    let timestamp = clock.unix_timestamp;
+
+    let computed = score_owner(
+        &ScoredOwner {
+            owner: ctx.accounts.owner.key(),
+            lamports: ctx.accounts.owner.lamports(),
+            identity: &ctx.accounts.identity,
+            credit_history: &ctx.accounts.credit_history,
+            penalty_index: &ctx.accounts.penalty_index,
+            balance_history: &ctx.accounts.balance_history,
+            activity: &ctx.accounts.activity,
+            holding_registry: &ctx.accounts.holding_registry,
+            tip_vault: &ctx.accounts.tip_vault,
+            holdings: ctx.remaining_accounts,
+            registered: None,
+        },
+        &ctx.accounts.config,
+        &ctx.accounts.score_model,
+        load_sol_price(&ctx.accounts.config, &ctx.accounts.price_feed, timestamp)?,
+        ctx.program_id,
+        &clock,
+    )?;
+
+    store_score(
+        &mut ctx.accounts.score_account,
+        &mut ctx.accounts.score_history,
+        &mut ctx.accounts.stats,
+        ctx.accounts.config.score_validity_seconds,
+        &ctx.accounts.identity,
+        computed,
+        timestamp,
+    )
+}
+
+/// Recalculates the score of an owner who opted in to keeper refreshes. Anyone
+/// may call it once the cooldown has passed, and is paid the owner's tip when
+/// the tip vault exists and can cover it. Holding accounts go in
+/// remaining_accounts as for `calculate_score`, and must be exactly the ones
+/// the owner registered with `set_keeper_refresh`.
+pub fn refresh_score(ctx: Context<RefreshScore>) -> Result<()> {
+    let clock = Clock::get()?;
+    let timestamp = clock.unix_timestamp;
+
+    require!(
+        ctx.accounts.identity.keeper_refresh,
+        IdentityScoreError::KeeperRefreshDisabled
+    );
+    require!(
+        ctx.accounts
+            .score_account
+            .cooldown_elapsed(timestamp, ctx.accounts.config.min_score_interval_seconds),
+        IdentityScoreError::ScoreCooldownActive
+    );
+    ensure_owner_not_credited(
+        &ctx.accounts.instructions,
+        &ctx.accounts.owner.key(),
+        ctx.program_id,
+    )?;
+    let registered = load_registered_holdings(&ctx.accounts.holding_registry, ctx.program_id)?;
+    let tip = load_tip(&ctx.accounts.tip_vault, ctx.program_id)?;
+
+    let computed = score_owner(
+        &ScoredOwner {
+            owner: ctx.accounts.owner.key(),
+            lamports: ctx.accounts.owner.lamports(),
+            identity: &ctx.accounts.identity,
+            credit_history: &ctx.accounts.credit_history,
+            penalty_index: &ctx.accounts.penalty_index,
+            balance_history: &ctx.accounts.balance_history,
+            activity: &ctx.accounts.activity,
+            holding_registry: &ctx.accounts.holding_registry,
+            tip_vault: &ctx.accounts.tip_vault,
+            holdings: ctx.remaining_accounts,
+            registered: Some(&registered),
+        },
+        &ctx.accounts.config,
+        &ctx.accounts.score_model,
+        load_sol_price(&ctx.accounts.config, &ctx.accounts.price_feed, timestamp)?,
+        ctx.program_id,
+        &clock,
+    )?;
+
+    store_score(
+        &mut ctx.accounts.score_account,
+        &mut ctx.accounts.score_history,
+        &mut ctx.accounts.stats,
+        ctx.accounts.config.score_validity_seconds,
+        &ctx.accounts.identity,
+        computed,
+        timestamp,
+    )?;
 
-    (total_score, level)
+    let tip = pay_tip(
+        &ctx.accounts.tip_vault,
+        &ctx.accounts.keeper.to_account_info(),
+        tip,
+    )?;
+
+    emit!(events::ScoreRefreshed {
+        owner: ctx.accounts.owner.key(),
+        identity: ctx.accounts.identity.key(),
+        keeper: ctx.accounts.keeper.key(),
+        tip,
+        timestamp,
+    });
+
+    Ok(())
 }
 
-fn calculate_asset_score(lamports: u64) -> u8 {
-    if lamports == 0 {
-        return SCORE_MIN as u8;
+/// Accounts of one owner in `batch_refresh_scores`, ahead of its holdings
+const BATCH_ENTRY_ACCOUNTS: usize = 10;
+
+/// `refresh_score` for many owners at once. Each entry in remaining_accounts
+/// is the owner, identity and score account, then the owner's score history,
+/// credit history, penalty index, balance history, activity, holding
+/// registry and tip vault PDAs, then `holding_counts[i]` holding accounts,
+/// which must be the owner's registered holdings. Owners that cannot be
+/// refreshed are skipped and reported in the return data instead of failing
+/// the batch; an owner listed twice fails it.
+pub fn batch_refresh_scores<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchRefreshScores<'info>>,
+    holding_counts: Vec<u8>,
+) -> Result<BatchRefreshReport> {
+    let clock = Clock::get()?;
+    let timestamp = clock.unix_timestamp;
+    let expected_accounts: usize = holding_counts
+        .iter()
+        .map(|count| BATCH_ENTRY_ACCOUNTS + *count as usize)
+        .sum();
+    require!(
+        holding_counts.len() <= u16::MAX as usize
+            && ctx.remaining_accounts.len() == expected_accounts,
+        IdentityScoreError::InvalidBatchAccounts
+    );
+    // A repeated owner would be paid for, and tip the keeper, more than once
+    let mut owners: Vec<Pubkey> = Vec::with_capacity(holding_counts.len());
+    let mut offset = 0;
+    for count in holding_counts.iter() {
+        let owner = ctx.remaining_accounts[offset].key();
+        require!(
+            !owners.contains(&owner),
+            IdentityScoreError::DuplicateBatchOwner
+        );
+        owners.push(owner);
+        offset += BATCH_ENTRY_ACCOUNTS + *count as usize;
+    }
+
+    let accounts = &mut *ctx.accounts;
+    let batch = BatchContext {
+        keeper: accounts.keeper.to_account_info(),
+        config: &accounts.config,
+        score_model: &accounts.score_model,
+        instructions: &accounts.instructions,
+        sol_price: load_sol_price(&accounts.config, &accounts.price_feed, timestamp)?,
+        program_id: ctx.program_id,
+        clock: &clock,
+    };
+    let stats = &mut accounts.stats;
+    let mut report = BatchRefreshReport::default();
+    let mut tips = 0u64;
+    let mut remaining = ctx.remaining_accounts;
+    for (index, count) in holding_counts.iter().enumerate() {
+        let (entry, rest) = remaining.split_at(BATCH_ENTRY_ACCOUNTS + *count as usize);
+        remaining = rest;
+        match refresh_batch_entry(&batch, stats, entry) {
+            Ok(tip) => {
+                report.refreshed += 1;
+                tips = tips.saturating_add(tip);
+            }
+            Err(error) => report.skipped.push(SkippedRefresh {
+                index: index as u16,
+                error_code: error_code(&error),
+            }),
+        }
     }
-    normalize_score((lamports as f64).ln() / LN_MAX_LAMPORTS)
+
+    emit!(events::ScoresBatchRefreshed {
+        keeper: ctx.accounts.keeper.key(),
+        refreshed: report.refreshed,
+        skipped: report.skipped.len() as u16,
+        tips,
+        timestamp,
+    });
+
+    Ok(report)
 }
 
-fn calculate_stability_score(
-    identity_created_at: i64,
-    is_verified: bool,
-    current_timestamp: i64,
-) -> u8 {
-    let age_seconds = (current_timestamp - identity_created_at).max(0);
-    let age_score = ((age_seconds as f64 / MAX_AGE_SECONDS as f64).clamp(0.0, 1.0) * 60.0) as u8;
-    (age_score + if is_verified { 30 } else { 0 }).min(90)
+/// What every entry of a batch refresh shares
+struct BatchContext<'a, 'info> {
+    keeper: AccountInfo<'info>,
+    config: &'a ProtocolConfig,
+    score_model: &'a ScoreModel,
+    instructions: &'a AccountInfo<'info>,
+    sol_price: Option<SolPrice>,
+    program_id: &'a Pubkey,
+    clock: &'a Clock,
 }
 
-fn calculate_rent_efficiency_score(lamports: u64, account_data_len: u64) -> u8 {
-    let rent_exempt = (account_data_len as u64 * RENT_EXEMPT_PER_BYTE).max(RENT_EXEMPT_PER_BYTE);
-    let ratio = (lamports as f64 / rent_exempt as f64).clamp(1.0, 10.0);
-    normalize_score((ratio - 1.0) / 9.0)
+/// Refreshes one owner of a batch, returning the tip paid. Every check runs
+/// before anything is written, so a failed entry leaves its accounts as
+/// they were.
+fn refresh_batch_entry<'info>(
+    batch: &BatchContext<'_, 'info>,
+    stats: &mut ProtocolStats,
+    entry: &'info [AccountInfo<'info>],
+) -> Result<u64> {
+    let (fixed, holdings) = entry.split_at(BATCH_ENTRY_ACCOUNTS);
+    let owner = &fixed[0];
+    let identity = &fixed[1];
+    let score_account = &fixed[2];
+    let score_history = &fixed[3];
+    let credit_history = &fixed[4];
+    let penalty_index = &fixed[5];
+    let balance_history = &fixed[6];
+    let activity = &fixed[7];
+    let holding_registry = &fixed[8];
+    let tip_vault = &fixed[9];
+    let owner_key = owner.key();
+    let program_id = batch.program_id;
+    let timestamp = batch.clock.unix_timestamp;
+
+    for (account, seed) in [
+        (identity, SEED_IDENTITY),
+        (score_account, SEED_SCORE),
+        (score_history, SEED_SCORE_HISTORY),
+        (credit_history, SEED_CREDIT_HISTORY),
+        (penalty_index, SEED_PENALTY_INDEX),
+        (balance_history, SEED_BALANCE_HISTORY),
+        (activity, SEED_ACTIVITY),
+        (holding_registry, SEED_HOLDING_REGISTRY),
+        (tip_vault, SEED_TIP_VAULT),
+    ] {
+        let (address, _) = Pubkey::find_program_address(&[seed, owner_key.as_ref()], program_id);
+        require_keys_eq!(account.key(), address, IdentityScoreError::Unauthorized);
+    }
+    // A read-only account would fail the whole transaction once written
+    require!(
+        score_account.is_writable
+            && score_history.is_writable
+            && (tip_vault.data_len() == 0 || tip_vault.is_writable),
+        IdentityScoreError::InvalidBatchAccounts
+    );
+
+    let identity = Account::<IdentityAccount>::try_from(identity)?;
+    let mut score_account = Account::<CreditScoreAccount>::try_from(score_account)?;
+    let mut score_history = Account::<ScoreHistory>::try_from(score_history)?;
+    require_keys_eq!(identity.owner, owner_key, IdentityScoreError::Unauthorized);
+    require_keys_eq!(
+        score_account.identity,
+        identity.key(),
+        IdentityScoreError::Unauthorized
+    );
+    require!(
+        identity.keeper_refresh,
+        IdentityScoreError::KeeperRefreshDisabled
+    );
+    require!(
+        score_account.cooldown_elapsed(timestamp, batch.config.min_score_interval_seconds),
+        IdentityScoreError::ScoreCooldownActive
+    );
+    ensure_owner_not_credited(batch.instructions, &owner_key, program_id)?;
+    let registered = load_registered_holdings(holding_registry, program_id)?;
+
+    let computed = score_owner(
+        &ScoredOwner {
+            owner: owner_key,
+            lamports: owner.lamports(),
+            identity: &identity,
+            credit_history,
+            penalty_index,
+            balance_history,
+            activity,
+            holding_registry,
+            tip_vault,
+            holdings,
+            registered: Some(&registered),
+        },
+        batch.config,
+        batch.score_model,
+        batch.sol_price,
+        program_id,
+        batch.clock,
+    )?;
+    let tip = load_tip(tip_vault, program_id)?;
+
+    // The stats are shared by the whole batch, so they only take this entry's
+    // counts once its accounts are written back
+    let mut entry_stats = stats.clone();
+    store_score(
+        &mut score_account,
+        &mut score_history,
+        &mut entry_stats,
+        batch.config.score_validity_seconds,
+        &identity,
+        computed,
+        timestamp,
+    )?;
+    score_account.exit(program_id)?;
+    score_history.exit(program_id)?;
+    *stats = entry_stats;
+
+    pay_tip(tip_vault, &batch.keeper, tip)
 }
 
-fn calculate_verification_score(is_verified: bool) -> u8 {
-    if is_verified {
-        90
-    } else {
-        50
+/// Numeric code of an error, as reported for skipped batch entries. Builtin
+/// program errors keep their full `u64` encoding.
+fn error_code(error: &Error) -> u64 {
+    match error {
+        Error::AnchorError(error) => error.error_code_number as u64,
+        Error::ProgramError(error) => u64::from(error.program_error.clone()),
     }
 }
 
-pub fn calculate_score_from_lamports(lamports: u64) -> (u8, ScoreLevel) {
-    calculate_comprehensive_score(lamports, 0, true, 100, Clock::get().unwrap().unix_timestamp)
+/// Runs the same computation as `calculate_score` without writing any account;
+/// the result is returned through return data, for simulation and CPI callers
+pub fn preview_score(ctx: Context<PreviewScore>) -> Result<ScorePreview> {
+    let clock = Clock::get()?;
+    let timestamp = clock.unix_timestamp;
+
+    let computed = score_owner(
+        &ScoredOwner {
+            owner: ctx.accounts.owner.key(),
+            lamports: ctx.accounts.owner.lamports(),
+            identity: &ctx.accounts.identity,
+            credit_history: &ctx.accounts.credit_history,
+            penalty_index: &ctx.accounts.penalty_index,
+            balance_history: &ctx.accounts.balance_history,
+            activity: &ctx.accounts.activity,
+            holding_registry: &ctx.accounts.holding_registry,
+            tip_vault: &ctx.accounts.tip_vault,
+            holdings: ctx.remaining_accounts,
+            registered: None,
+        },
+        &ctx.accounts.config,
+        &ctx.accounts.score_model,
+        load_sol_price(&ctx.accounts.config, &ctx.accounts.price_feed, timestamp)?,
+        ctx.program_id,
+        &clock,
+    )?;
+
+    Ok(ScorePreview {
+        score: computed.score,
+        score_level: computed.level,
+        breakdown: computed.breakdown,
+        model_version: computed.model_version,
+    })
 }
 
-pub fn calculate_score(ctx: Context<CalculateScore>) -> Result<()> {
-    let owner = &ctx.accounts.owner;
+/// Gate for consumer programs to CPI into: fails unless the owner's identity
+/// is verified and active and its score, as read now under the penalties
+/// active now, meets the requirement.
+/// Unless the owner signs a top-level call, the owner's unexpired consent
+/// must be passed along with the grantee program's `[SEED_SCORE_GATE]` PDA as
+/// a signer, which only that program can produce. The owner's signature
+/// carries through CPI, so it cannot stand in for consent there.
+pub fn assert_min_score(
+    ctx: Context<AssertMinScore>,
+    min_score: u8,
+    max_age_seconds: i64,
+    min_model_version: u32,
+) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
     let identity = &ctx.accounts.identity;
-    let data_len = ctx.accounts.score_account.to_account_info().data_len() as u64;
+    let score_account = &ctx.accounts.score_account;
 
     require!(identity.verified, IdentityScoreError::IdentityNotVerified);
+    require!(
+        identity.is_active(),
+        IdentityScoreError::IdentityDeactivated
+    );
 
-    let lamports = owner.lamports();
-    let timestamp = Clock::get()?.unix_timestamp;
+    let top_level = get_stack_height() == TRANSACTION_LEVEL_STACK_HEIGHT;
+    if !(ctx.accounts.owner.is_signer && top_level) {
+        let consent = ctx
+            .accounts
+            .consent
+            .as_ref()
+            .ok_or(IdentityScoreError::ConsentRequired)?;
+        let gate_authority = ctx
+            .accounts
+            .gate_authority
+            .as_ref()
+            .ok_or(IdentityScoreError::ConsentRequired)?;
+        let (grantee_authority, _) =
+            Pubkey::find_program_address(&[SEED_SCORE_GATE], &consent.grantee);
+        require!(
+            consent.owner == identity.owner
+                && gate_authority.key() == grantee_authority
+                && consent.allows(CONSENT_SCOPE_MIN_SCORE, timestamp),
+            IdentityScoreError::ConsentRequired
+        );
+    }
 
-    let (score, level) = calculate_comprehensive_score(
-        lamports,
-        identity.created_at,
-        identity.verified,
-        data_len,
+    require!(
+        timestamp.saturating_sub(score_account.calculated_at) <= max_age_seconds,
+        IdentityScoreError::ScoreTooOld
+    );
+    score_account.check_model(&ctx.accounts.score_model, min_model_version)?;
+    let penalties = load_penalty_effect(&ctx.accounts.penalty_index, ctx.program_id, timestamp)?;
+    require!(
+        score_account.penalized_score(
+            timestamp,
+            identity.penalty_points,
+            penalties,
+            &ctx.accounts.config.level_cutoffs,
+        ) >= min_score,
+        IdentityScoreError::ScoreBelowThreshold
+    );
+
+    Ok(())
+}
+
+/// Reclassifies a stored score and its history under the configured cutoffs.
+/// A score calculated before `expires_at` is first grown to the current size:
+/// it expires one validity period after it was calculated, names no model
+/// and is counted in the stats for the first time. A no-op for a score that
+/// already matches the cutoffs; anyone may pay for it.
+pub fn migrate_score_level(ctx: Context<MigrateScoreLevel>) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let cutoffs = config.level_cutoffs;
+    let score_info = ctx.accounts.score_account.to_account_info();
+    require_keys_eq!(
+        *score_info.owner,
+        *ctx.program_id,
+        IdentityScoreError::Unauthorized
+    );
+
+    let legacy = score_info.data_len() == CreditScoreAccount::LEGACY_SPACE;
+    if legacy {
+        crate::instructions::config::grow_account(
+            &score_info,
+            CreditScoreAccount::SPACE,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+        )?;
+    }
+    let mut score_account =
+        CreditScoreAccount::try_deserialize(&mut &score_info.data.borrow()[..])?;
+    if legacy {
+        score_account.expires_at = score_account.calculated_at + config.score_validity_seconds;
+        // Scored before versioned models, so no model account vouches for it
+        score_account.model_version = 0;
+        score_account.breakdown.base_score = score_account.score;
+    }
+    let previous_level = score_account.score_level;
+    let level = cutoffs.level(score_account.score);
+
+    let stats = &mut ctx.accounts.stats;
+    if !legacy {
+        stats.remove_level(previous_level);
+    }
+    stats.add_level(level);
+
+    score_account.score_level = level;
+    score_account.try_serialize(&mut &mut score_info.data.borrow_mut()[..])?;
+
+    let history_info = ctx.accounts.score_history.to_account_info();
+    if history_info.data_len() > 0 {
+        require_keys_eq!(
+            *history_info.owner,
+            *ctx.program_id,
+            IdentityScoreError::Unauthorized
+        );
+        let mut score_history =
+            ScoreHistory::try_deserialize(&mut &history_info.data.borrow()[..])?;
+        score_history.reclassify(&cutoffs);
+        score_history.try_serialize(&mut &mut history_info.data.borrow_mut()[..])?;
+    }
+
+    emit!(events::ScoreLevelMigrated {
+        owner: ctx.accounts.owner.key(),
+        score_account: score_info.key(),
+        previous_level,
+        score_level: level,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Reads the pinned SOL/USD price, if the protocol values assets in USD
+fn load_sol_price(
+    config: &ProtocolConfig,
+    price_feed: &Option<UncheckedAccount>,
+    now: i64,
+) -> Result<Option<SolPrice>> {
+    if !config.uses_price_oracle() {
+        return Ok(None);
+    }
+
+    let feed = price_feed
+        .as_ref()
+        .ok_or(IdentityScoreError::InvalidPriceFeed)?;
+    require_keys_eq!(
+        feed.key(),
+        config.price_oracle,
+        IdentityScoreError::InvalidPriceFeed
+    );
+
+    read_sol_price(
+        feed,
+        now,
+        config.max_price_age_seconds,
+        config.max_price_confidence_bps,
+    )
+    .map(Some)
+}
+
+/// The balance to score: the live one, or when the protocol requires
+/// checkpoints, the time-weighted average of the recorded ones. The PDA is
+/// checked by the accounts struct and may not exist yet.
+fn load_scored_balance(
+    config: &ProtocolConfig,
+    balance_history: &AccountInfo,
+    program_id: &Pubkey,
+    live_lamports: u64,
+    now: i64,
+) -> Result<u64> {
+    if !config.uses_balance_checkpoints() {
+        return Ok(live_lamports);
+    }
+    require!(
+        balance_history.data_len() > 0,
+        IdentityScoreError::InsufficientBalanceCheckpoints
+    );
+    require_keys_eq!(
+        *balance_history.owner,
+        *program_id,
+        IdentityScoreError::Unauthorized
+    );
+
+    let mut data_slice = &balance_history.data.borrow()[..];
+    let history = BalanceHistory::try_deserialize(&mut data_slice)?;
+    history
+        .time_weighted_average(
+            now,
+            config.balance_window_seconds,
+            config.min_balance_checkpoints,
+        )
+        .ok_or_else(|| IdentityScoreError::InsufficientBalanceCheckpoints.into())
+}
+
+/// Reads the owner's repayment record; the PDA is checked by the accounts
+/// struct and may not exist yet
+fn load_repayment_record(
+    credit_history: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<Option<RepaymentRecord>> {
+    if credit_history.data_len() == 0 {
+        return Ok(None);
+    }
+    require_keys_eq!(
+        *credit_history.owner,
+        *program_id,
+        IdentityScoreError::Unauthorized
+    );
+
+    let mut data_slice = &credit_history.data.borrow()[..];
+    let history = CreditHistory::try_deserialize(&mut data_slice)?;
+    Ok(history.repayment_record())
+}
+
+/// Sums the owner's active reporter penalties; the PDA is checked by the
+/// accounts struct and may not exist yet
+fn load_penalty_effect(
+    penalty_index: &AccountInfo,
+    program_id: &Pubkey,
+    now: i64,
+) -> Result<PenaltyEffect> {
+    if penalty_index.data_len() == 0 {
+        return Ok(PenaltyEffect::default());
+    }
+    require_keys_eq!(
+        *penalty_index.owner,
+        *program_id,
+        IdentityScoreError::Unauthorized
+    );
+
+    let mut data_slice = &penalty_index.data.borrow()[..];
+    let index = PenaltyIndex::try_deserialize(&mut data_slice)?;
+    Ok(index.effect(now))
+}
+
+/// Reads the holding accounts the owner registered for keeper refreshes; the
+/// PDA is checked by the accounts struct and may not exist yet, leaving none
+/// registered
+fn load_registered_holdings(
+    holding_registry: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<Vec<Pubkey>> {
+    if holding_registry.data_len() == 0 {
+        return Ok(Vec::new());
+    }
+    require_keys_eq!(
+        *holding_registry.owner,
+        *program_id,
+        IdentityScoreError::Unauthorized
+    );
+
+    let mut data_slice = &holding_registry.data.borrow()[..];
+    let registry = HoldingRegistry::try_deserialize(&mut data_slice)?;
+    Ok(registry.holdings)
+}
+
+/// Reads the owner's check-ins; the PDA is checked by the accounts struct and
+/// may not exist yet
+fn load_activity_record(
+    activity: &AccountInfo,
+    program_id: &Pubkey,
+    now: i64,
+) -> Result<Option<ActivityRecord>> {
+    if activity.data_len() == 0 {
+        return Ok(None);
+    }
+    require_keys_eq!(
+        *activity.owner,
+        *program_id,
+        IdentityScoreError::Unauthorized
+    );
+
+    let mut data_slice = &activity.data.borrow()[..];
+    let activity = Activity::try_deserialize(&mut data_slice)?;
+    Ok(activity.record(now))
+}
+
+/// Rent-exempt minimum of the accounts the owner keeps: the identity, score
+/// and score history at the full size scoring gives them, the owner's other
+/// PDAs that exist, and the verified holding accounts. The penalty index is
+/// left out, as active penalties already weigh in on their own.
+fn owner_footprint_rent(scored: &ScoredOwner, holdings: &Holdings) -> Result<u64> {
+    let rent = Rent::get()?;
+    let sidecars = [
+        scored.credit_history,
+        scored.balance_history,
+        scored.activity,
+        scored.holding_registry,
+        scored.tip_vault,
+    ]
+    .map(|account| account.data_len());
+    Ok([
+        IdentityAccount::SPACE,
+        CreditScoreAccount::SPACE,
+        ScoreHistory::SPACE,
+    ]
+    .into_iter()
+    .chain(sidecars.into_iter().filter(|len| *len > 0))
+    .map(|len| rent.minimum_balance(len))
+    .fold(holdings.account_rent, u64::saturating_add))
+}
+
+/// The owner's accounts a score is read from, as passed to a scoring
+/// instruction. The sidecar PDAs are checked by the caller and may not exist
+/// yet.
+struct ScoredOwner<'a, 'info, 'holding> {
+    owner: Pubkey,
+    lamports: u64,
+    identity: &'a IdentityAccount,
+    credit_history: &'a AccountInfo<'info>,
+    penalty_index: &'a AccountInfo<'info>,
+    balance_history: &'a AccountInfo<'info>,
+    activity: &'a AccountInfo<'info>,
+    holding_registry: &'a AccountInfo<'info>,
+    tip_vault: &'a AccountInfo<'info>,
+    holdings: &'a [AccountInfo<'holding>],
+    /// The holdings the owner registered, which a keeper must pass exactly
+    registered: Option<&'a [Pubkey]>,
+}
+
+/// Reads every signal of `scored` and computes its score under `model`; the
+/// one path shared by calculating, refreshing and previewing a score
+fn score_owner(
+    scored: &ScoredOwner,
+    config: &ProtocolConfig,
+    model: &ScoreModel,
+    sol_price: Option<SolPrice>,
+    program_id: &Pubkey,
+    clock: &Clock,
+) -> Result<ComputedScore> {
+    let timestamp = clock.unix_timestamp;
+    let holdings = collect_holdings(
+        &scored.owner,
+        scored.holdings,
+        program_id,
+        clock.epoch,
+        scored.registered,
+    )?;
+    let signals = ScoreSignals {
+        footprint_rent: owner_footprint_rent(scored, &holdings)?,
+        holdings,
+        sol_price,
+        repayment: load_repayment_record(scored.credit_history, program_id)?,
+        penalties: load_penalty_effect(scored.penalty_index, program_id, timestamp)?,
+        activity: load_activity_record(scored.activity, program_id, timestamp)?,
+    };
+    compute_score(
+        scored.identity,
+        model,
+        load_scored_balance(
+            config,
+            scored.balance_history,
+            program_id,
+            scored.lamports,
+            timestamp,
+        )?,
+        signals,
+        &config.level_cutoffs,
         timestamp,
+    )
+}
+
+/// Everything besides the wallet balance that feeds a score, read from the
+/// optional and sidecar accounts of the scoring instructions
+struct ScoreSignals {
+    holdings: Holdings,
+    footprint_rent: u64,
+    sol_price: Option<SolPrice>,
+    repayment: Option<RepaymentRecord>,
+    penalties: PenaltyEffect,
+    activity: Option<ActivityRecord>,
+}
+
+/// A freshly computed score, how it was reached, and the model that produced it
+struct ComputedScore {
+    score: u8,
+    level: ScoreLevel,
+    breakdown: ScoreBreakdown,
+    model_version: u32,
+}
+
+/// Scores a verified, active identity from its owner's balance and the
+/// verified signals at `timestamp`
+fn compute_score(
+    identity: &IdentityAccount,
+    model: &ScoreModel,
+    lamports: u64,
+    signals: ScoreSignals,
+    cutoffs: &LevelCutoffs,
+    timestamp: i64,
+) -> Result<ComputedScore> {
+    require!(identity.verified, IdentityScoreError::IdentityNotVerified);
+    require!(
+        identity.is_active(),
+        IdentityScoreError::IdentityDeactivated
+    );
+    require!(
+        timestamp >= identity.eligible_at,
+        IdentityScoreError::IdentityInWaitingPeriod
     );
 
-    let score_account = &mut ctx.accounts.score_account;
+    let mut breakdown = calculate_score_breakdown(&ScoreInputs {
+        lamports,
+        token_value: signals.holdings.token_value,
+        token_usd_value: signals.holdings.token_usd_value,
+        staked_lamports: signals.holdings.staked_lamports,
+        stake_age_epochs: signals.holdings.stake_age_epochs,
+        identity_created_at: identity.created_at,
+        is_verified: identity.verified,
+        footprint_rent: signals.footprint_rent,
+        current_timestamp: timestamp,
+        sol_price: signals.sol_price,
+        repayment: signals.repayment,
+        activity: signals.activity,
+        model: model.params,
+    });
+    let score = apply_penalty(breakdown.base_score, identity.penalty_points);
+    let (score, level) = apply_penalty_effect(score, signals.penalties, cutoffs);
+    breakdown.penalty_deduction = breakdown.base_score - score;
+    Ok(ComputedScore {
+        score,
+        level,
+        breakdown,
+        model_version: model.version,
+    })
+}
+
+/// Writes a computed score to the score account and its history, keeping the
+/// protocol stats in step
+fn store_score<'info>(
+    score_account: &mut Account<'info, CreditScoreAccount>,
+    score_history: &mut Account<'info, ScoreHistory>,
+    stats: &mut ProtocolStats,
+    validity_seconds: i64,
+    identity: &Account<'info, IdentityAccount>,
+    computed: ComputedScore,
+    timestamp: i64,
+) -> Result<()> {
+    let ComputedScore {
+        score,
+        level,
+        breakdown,
+        model_version,
+    } = computed;
+
+    if score_account.is_initialized() {
+        stats.remove_level(score_account.score_level);
+    }
+    stats.add_level(level);
+    stats.scores_computed += 1;
 
     score_account.identity = identity.key();
     score_account.score = score;
     score_account.score_level = level;
     score_account.calculated_at = timestamp;
+    score_account.expires_at = timestamp + validity_seconds;
+    score_account.model_version = model_version;
+    score_account.breakdown = breakdown;
+
+    score_history.identity = identity.key();
+    score_history.push(ScoreHistoryEntry {
+        score,
+        score_level: level,
+        calculated_at: timestamp,
+        model_version,
+    });
 
     emit!(events::ScoreCalculated {
-        owner: owner.key(),
+        owner: identity.owner,
         identity: identity.key(),
         score_account: score_account.key(),
         score,
         score_level: level,
+        model_version,
+        breakdown,
+        expires_at: score_account.expires_at,
         timestamp,
     });
 
@@ -149,6 +940,13 @@ pub fn delete_score(ctx: Context<DeleteScore>) -> Result<()> {
             score_state.identity == identity.key(),
             crate::errors::IdentityScoreError::Unauthorized
         );
+        // Deleting resets the cooldown of `calculate_score`, so it waits for it
+        require!(
+            score_state.cooldown_elapsed(timestamp, ctx.accounts.config.min_score_interval_seconds),
+            IdentityScoreError::ScoreCooldownActive
+        );
+
+        ctx.accounts.stats.remove_level(score_state.score_level);
 
         // Transfer lamports from score account to owner
         let score_lamports = score_account.lamports();
@@ -156,6 +954,13 @@ pub fn delete_score(ctx: Context<DeleteScore>) -> Result<()> {
         **owner.to_account_info().lamports.borrow_mut() += score_lamports;
     }
 
+    close_score_history(
+        &ctx.accounts.score_history,
+        &owner.to_account_info(),
+        identity.key(),
+        ctx.program_id,
+    )?;
+
     emit!(crate::events::ScoreDeleted {
         owner: owner.key(),
         identity: identity.key(),
@@ -166,17 +971,62 @@ pub fn delete_score(ctx: Context<DeleteScore>) -> Result<()> {
     Ok(())
 }
 
+/// Closes an owner's score history, if it exists, refunding its rent to the owner
+pub(crate) fn close_score_history<'info>(
+    score_history: &AccountInfo<'info>,
+    owner: &AccountInfo<'info>,
+    identity: Pubkey,
+    program_id: &Pubkey,
+) -> Result<()> {
+    let (history_pda, _) =
+        Pubkey::find_program_address(&[SEED_SCORE_HISTORY, owner.key().as_ref()], program_id);
+    require_keys_eq!(
+        score_history.key(),
+        history_pda,
+        IdentityScoreError::Unauthorized
+    );
+
+    if score_history.data_len() > 0 {
+        require_keys_eq!(
+            *score_history.owner,
+            *program_id,
+            IdentityScoreError::Unauthorized
+        );
+        let mut data_slice = &score_history.data.borrow()[..];
+        let history_state = ScoreHistory::try_deserialize(&mut data_slice)?;
+        require!(
+            history_state.identity == identity,
+            IdentityScoreError::Unauthorized
+        );
+
+        let history_lamports = score_history.lamports();
+        **score_history.lamports.borrow_mut() = 0;
+        **owner.lamports.borrow_mut() += history_lamports;
+    }
+
+    Ok(())
+}
+
 #[derive(Accounts)]
 pub struct CalculateScore<'info> {
     #[account(
         init_if_needed,
         payer = owner,
-        space = 8 + 32 + 1 + 1 + 8, // discriminator + pubkey + u8 + enum(1) + i64
+        space = CreditScoreAccount::SPACE,
         seeds = [SEED_SCORE, owner.key().as_ref()],
         bump,
     )]
     pub score_account: Account<'info, CreditScoreAccount>,
 
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = ScoreHistory::SPACE,
+        seeds = [SEED_SCORE_HISTORY, owner.key().as_ref()],
+        bump,
+    )]
+    pub score_history: Account<'info, ScoreHistory>,
+
     #[account(
         seeds = [SEED_IDENTITY, owner.key().as_ref()],
         bump,
@@ -188,6 +1038,340 @@ pub struct CalculateScore<'info> {
     pub owner: Signer<'info>,
 
     pub system_program: Program<'info, System>,
+
+    #[account(mut, seeds = [SEED_STATS], bump)]
+    pub stats: Account<'info, ProtocolStats>,
+
+    #[account(seeds = [SEED_CONFIG], bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_CREDIT_HISTORY, owner.key().as_ref()], bump)]
+    pub credit_history: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_PENALTY_INDEX, owner.key().as_ref()], bump)]
+    pub penalty_index: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_BALANCE_HISTORY, owner.key().as_ref()], bump)]
+    pub balance_history: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_ACTIVITY, owner.key().as_ref()], bump)]
+    pub activity: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only its size is read
+    #[account(seeds = [SEED_HOLDING_REGISTRY, owner.key().as_ref()], bump)]
+    pub holding_registry: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only its size is read
+    #[account(seeds = [SEED_TIP_VAULT, owner.key().as_ref()], bump)]
+    pub tip_vault: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [SEED_SCORE_MODEL, config.active_model_version.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub score_model: Account<'info, ScoreModel>,
+
+    /// CHECK: Instructions sysvar, fixed by address
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    /// CHECK: Must match `config.price_oracle`; parsed in the instruction
+    pub price_feed: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct ForceCalculateScore<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = CreditScoreAccount::SPACE,
+        seeds = [SEED_SCORE, owner.key().as_ref()],
+        bump,
+    )]
+    pub score_account: Account<'info, CreditScoreAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = ScoreHistory::SPACE,
+        seeds = [SEED_SCORE_HISTORY, owner.key().as_ref()],
+        bump,
+    )]
+    pub score_history: Account<'info, ScoreHistory>,
+
+    #[account(
+        seeds = [SEED_IDENTITY, owner.key().as_ref()],
+        bump,
+        has_one = owner,
+    )]
+    pub identity: Account<'info, IdentityAccount>,
+
+    /// CHECK: Only its balance is read; bound to the identity via `has_one`
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(mut, seeds = [SEED_STATS], bump)]
+    pub stats: Account<'info, ProtocolStats>,
+
+    #[account(
+        seeds = [SEED_CONFIG],
+        bump,
+        has_one = admin @ IdentityScoreError::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_CREDIT_HISTORY, owner.key().as_ref()], bump)]
+    pub credit_history: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_PENALTY_INDEX, owner.key().as_ref()], bump)]
+    pub penalty_index: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_BALANCE_HISTORY, owner.key().as_ref()], bump)]
+    pub balance_history: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_ACTIVITY, owner.key().as_ref()], bump)]
+    pub activity: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only its size is read
+    #[account(seeds = [SEED_HOLDING_REGISTRY, owner.key().as_ref()], bump)]
+    pub holding_registry: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only its size is read
+    #[account(seeds = [SEED_TIP_VAULT, owner.key().as_ref()], bump)]
+    pub tip_vault: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [SEED_SCORE_MODEL, config.active_model_version.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub score_model: Account<'info, ScoreModel>,
+
+    /// CHECK: Must match `config.price_oracle`; parsed in the instruction
+    pub price_feed: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshScore<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_SCORE, owner.key().as_ref()],
+        bump,
+        constraint = score_account.identity == identity.key() @ IdentityScoreError::Unauthorized,
+    )]
+    pub score_account: Account<'info, CreditScoreAccount>,
+
+    #[account(mut, seeds = [SEED_SCORE_HISTORY, owner.key().as_ref()], bump)]
+    pub score_history: Account<'info, ScoreHistory>,
+
+    #[account(
+        seeds = [SEED_IDENTITY, owner.key().as_ref()],
+        bump,
+        has_one = owner,
+    )]
+    pub identity: Account<'info, IdentityAccount>,
+
+    /// CHECK: Only its balance is read; bound to the identity via `has_one`
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(mut, seeds = [SEED_STATS], bump)]
+    pub stats: Account<'info, ProtocolStats>,
+
+    #[account(seeds = [SEED_CONFIG], bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_CREDIT_HISTORY, owner.key().as_ref()], bump)]
+    pub credit_history: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_PENALTY_INDEX, owner.key().as_ref()], bump)]
+    pub penalty_index: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_BALANCE_HISTORY, owner.key().as_ref()], bump)]
+    pub balance_history: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_ACTIVITY, owner.key().as_ref()], bump)]
+    pub activity: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_HOLDING_REGISTRY, owner.key().as_ref()], bump)]
+    pub holding_registry: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [SEED_SCORE_MODEL, config.active_model_version.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub score_model: Account<'info, ScoreModel>,
+
+    /// CHECK: Instructions sysvar, fixed by address
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    /// CHECK: The owner's tip vault; may be uninitialized, in which case no
+    /// tip is paid
+    #[account(mut, seeds = [SEED_TIP_VAULT, owner.key().as_ref()], bump)]
+    pub tip_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Must match `config.price_oracle`; parsed in the instruction
+    pub price_feed: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct BatchRefreshScores<'info> {
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(mut, seeds = [SEED_STATS], bump)]
+    pub stats: Account<'info, ProtocolStats>,
+
+    #[account(seeds = [SEED_CONFIG], bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [SEED_SCORE_MODEL, config.active_model_version.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub score_model: Account<'info, ScoreModel>,
+
+    /// CHECK: Instructions sysvar, fixed by address
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    /// CHECK: Must match `config.price_oracle`; parsed in the instruction
+    pub price_feed: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct AssertMinScore<'info> {
+    #[account(
+        seeds = [SEED_IDENTITY, owner.key().as_ref()],
+        bump,
+        has_one = owner,
+    )]
+    pub identity: Account<'info, IdentityAccount>,
+
+    /// CHECK: Derives the identity and score addresses; signs when the owner
+    /// gates on their own score in a top-level call
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [SEED_SCORE, owner.key().as_ref()],
+        bump,
+        constraint = score_account.identity == identity.key() @ IdentityScoreError::Unauthorized,
+    )]
+    pub score_account: Account<'info, CreditScoreAccount>,
+
+    #[account(
+        seeds = [SEED_SCORE_MODEL, score_account.model_version.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub score_model: Account<'info, ScoreModel>,
+
+    #[account(seeds = [SEED_CONFIG], bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_PENALTY_INDEX, owner.key().as_ref()], bump)]
+    pub penalty_index: UncheckedAccount<'info>,
+
+    /// The `[SEED_SCORE_GATE]` PDA of the consent's grantee, signed for by
+    /// that program; unused when the owner signs a top-level call
+    pub gate_authority: Option<Signer<'info>>,
+
+    /// The owner's consent for the grantee program; unused when the owner
+    /// signs a top-level call
+    pub consent: Option<Account<'info, Consent>>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateScoreLevel<'info> {
+    /// CHECK: Sized by the instruction before it is deserialized
+    #[account(mut, seeds = [SEED_SCORE, owner.key().as_ref()], bump)]
+    pub score_account: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(mut, seeds = [SEED_SCORE_HISTORY, owner.key().as_ref()], bump)]
+    pub score_history: UncheckedAccount<'info>,
+
+    /// CHECK: Only used to derive the score addresses
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [SEED_STATS], bump)]
+    pub stats: Account<'info, ProtocolStats>,
+
+    #[account(seeds = [SEED_CONFIG], bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Tops up the rent of a grown score account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PreviewScore<'info> {
+    #[account(
+        seeds = [SEED_IDENTITY, owner.key().as_ref()],
+        bump,
+        has_one = owner,
+    )]
+    pub identity: Account<'info, IdentityAccount>,
+
+    /// CHECK: Only its balance is read; bound to the identity via `has_one`
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(seeds = [SEED_CONFIG], bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_CREDIT_HISTORY, owner.key().as_ref()], bump)]
+    pub credit_history: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_PENALTY_INDEX, owner.key().as_ref()], bump)]
+    pub penalty_index: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_BALANCE_HISTORY, owner.key().as_ref()], bump)]
+    pub balance_history: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only deserialized when it has data
+    #[account(seeds = [SEED_ACTIVITY, owner.key().as_ref()], bump)]
+    pub activity: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only its size is read
+    #[account(seeds = [SEED_HOLDING_REGISTRY, owner.key().as_ref()], bump)]
+    pub holding_registry: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized; only its size is read
+    #[account(seeds = [SEED_TIP_VAULT, owner.key().as_ref()], bump)]
+    pub tip_vault: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [SEED_SCORE_MODEL, config.active_model_version.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub score_model: Account<'info, ScoreModel>,
+
+    /// CHECK: Must match `config.price_oracle`; parsed in the instruction
+    pub price_feed: Option<UncheckedAccount<'info>>,
 }
 
 #[derive(Accounts)]
@@ -198,6 +1382,10 @@ pub struct DeleteScore<'info> {
     #[account(mut)]
     pub score_account: AccountInfo<'info>,
 
+    /// CHECK: May be uninitialized; address and identity verified in the instruction
+    #[account(mut)]
+    pub score_history: AccountInfo<'info>,
+
     #[account(
         seeds = [SEED_IDENTITY, owner.key().as_ref()],
         bump,
@@ -208,4 +1396,9 @@ pub struct DeleteScore<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
     pub system_program: Program<'info, System>,
+    #[account(mut, seeds = [SEED_STATS], bump)]
+    pub stats: Account<'info, ProtocolStats>,
+
+    #[account(seeds = [SEED_CONFIG], bump)]
+    pub config: Account<'info, ProtocolConfig>,
 }
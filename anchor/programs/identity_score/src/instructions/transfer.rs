@@ -20,6 +20,8 @@ pub fn initiate_transfer(ctx: Context<InitiateTransfer>) -> Result<()> {
     transfer_request.created_at = timestamp;
     transfer_request.expires_at = timestamp + TRANSFER_EXPIRY_SECONDS;
 
+    ctx.accounts.stats.active_transfer_requests += 1;
+
     emit!(events::TransferInitiated {
         from_owner: ctx.accounts.owner.key(),
         to_owner: ctx.accounts.recipient.key(),
@@ -38,9 +40,10 @@ pub fn initiate_transfer(ctx: Context<InitiateTransfer>) -> Result<()> {
 /// 接收者认领发起的身份转移请求
 /// 系统会自动：
 /// - 创建新的身份账户给接收者
-/// - 转移信用分（如果存在）
+/// - 转移信用分及其历史记录（如果存在）
 /// - 关闭旧的转移请求
 /// - 关闭旧的身份账户
+/// - 在旧所有者的墓碑中记录违约和扣分标记
 ///
 /// # 注意事项
 /// - 必须在转移请求过期前认领
@@ -60,14 +63,67 @@ pub fn claim_transfer(ctx: Context<ClaimTransfer>) -> Result<()> {
         IdentityScoreError::Unauthorized
     );
 
+    // 有未过期处罚的身份不能通过转移摆脱处罚
+    if ctx.accounts.old_penalty_index.data_len() > 0 {
+        let penalty_index = verify_and_extract_old_penalty_index(&ctx)?;
+        require!(
+            !penalty_index.has_active(timestamp),
+            IdentityScoreError::ActivePenaltiesExist
+        );
+    }
+    let old_credit_history = if ctx.accounts.old_credit_history.data_len() > 0 {
+        Some(verify_and_extract_old_credit_history(&ctx)?)
+    } else {
+        None
+    };
+    // 未结清的贷款记在按旧所有者派生的 ReporterLoans 上，转移后贷款方无法再
+    // 对新所有者报告违约，因此须先结清
+    require!(
+        old_credit_history
+            .as_ref()
+            .map_or(0, |history| history.outstanding_loans())
+            == 0,
+        IdentityScoreError::OutstandingLoansExist
+    );
+
+    // 旧钱包留下墓碑标记，转出身份后重新创建也无法洗白记录；
+    // 有效处罚已在上面拒绝，这里只剩违约和携带的扣分
+    let old_tombstone = &mut ctx.accounts.old_tombstone;
+    old_tombstone.owner = ctx.accounts.old_owner.key();
+    old_tombstone.record_marks(
+        ctx.accounts.old_identity.penalty_points,
+        0,
+        old_credit_history
+            .as_ref()
+            .map_or(0, |history| history.defaulted),
+    );
+
     ctx.accounts.new_identity.owner = ctx.accounts.new_owner.key();
     ctx.accounts.new_identity.created_at = ctx.accounts.old_identity.created_at;
     ctx.accounts.new_identity.verified = ctx.accounts.old_identity.verified;
     ctx.accounts.new_identity.verified_at = ctx.accounts.old_identity.verified_at;
+    ctx.accounts.new_identity.referrer = ctx.accounts.old_identity.referrer;
+    ctx.accounts.new_identity.referral_credited = ctx.accounts.old_identity.referral_credited;
+    ctx.accounts.new_identity.deactivated_at = None;
+    ctx.accounts.new_identity.penalty_points = ctx.accounts.old_identity.penalty_points;
+    ctx.accounts.new_identity.eligible_at = ctx.accounts.old_identity.eligible_at;
+    // 守护者刷新授权属于旧所有者，新所有者需自行开启
+    ctx.accounts.new_identity.keeper_refresh = false;
+
+    let stats = &mut ctx.accounts.stats;
+    stats.active_transfer_requests = stats.active_transfer_requests.saturating_sub(1);
 
     let (old_score_key, new_score_key) = if ctx.accounts.old_score.data_len() > 0 {
         let score_state = verify_and_extract_old_score(&ctx)?;
 
+        // The old score's level moves with it; only a stale score already at
+        // the new owner's address drops out of the histogram.
+        if ctx.accounts.new_score.is_initialized() {
+            ctx.accounts
+                .stats
+                .remove_level(ctx.accounts.new_score.score_level);
+        }
+
         let score_lamports = ctx.accounts.old_score.lamports();
         **ctx.accounts.old_score.lamports.borrow_mut() = 0;
         **ctx
@@ -81,6 +137,9 @@ pub fn claim_transfer(ctx: Context<ClaimTransfer>) -> Result<()> {
         ctx.accounts.new_score.score = score_state.score;
         ctx.accounts.new_score.score_level = score_state.score_level;
         ctx.accounts.new_score.calculated_at = score_state.calculated_at;
+        ctx.accounts.new_score.expires_at = score_state.expires_at;
+        ctx.accounts.new_score.model_version = score_state.model_version;
+        ctx.accounts.new_score.breakdown = score_state.breakdown;
 
         (
             Some(ctx.accounts.old_score.key()),
@@ -90,6 +149,41 @@ pub fn claim_transfer(ctx: Context<ClaimTransfer>) -> Result<()> {
         (None, None)
     };
 
+    if ctx.accounts.old_score_history.data_len() > 0 {
+        let history_state = verify_and_extract_old_score_history(&ctx)?;
+
+        let history_lamports = ctx.accounts.old_score_history.lamports();
+        **ctx.accounts.old_score_history.lamports.borrow_mut() = 0;
+        **ctx
+            .accounts
+            .new_owner
+            .to_account_info()
+            .lamports
+            .borrow_mut() += history_lamports;
+
+        let new_score_history = &mut ctx.accounts.new_score_history;
+        new_score_history.identity = ctx.accounts.new_identity.key();
+        new_score_history.head = history_state.head;
+        new_score_history.len = history_state.len;
+        new_score_history.entries = history_state.entries;
+    }
+
+    // 还款记录随身份转移，并与接收者已有的记录合并
+    if let Some(history_state) = old_credit_history {
+        let history_lamports = ctx.accounts.old_credit_history.lamports();
+        **ctx.accounts.old_credit_history.lamports.borrow_mut() = 0;
+        **ctx
+            .accounts
+            .new_owner
+            .to_account_info()
+            .lamports
+            .borrow_mut() += history_lamports;
+
+        let new_credit_history = &mut ctx.accounts.new_credit_history;
+        new_credit_history.owner = ctx.accounts.new_owner.key();
+        new_credit_history.merge(&history_state);
+    }
+
     emit!(events::TransferClaimed {
         from_owner: ctx.accounts.old_owner.key(),
         to_owner: ctx.accounts.new_owner.key(),
@@ -109,6 +203,9 @@ pub fn claim_transfer(ctx: Context<ClaimTransfer>) -> Result<()> {
 /// 转移发起者可以取消未认领的转移请求
 /// 转移请求账户会被关闭，lamports 返回给发起者
 pub fn cancel_transfer(ctx: Context<CancelTransfer>) -> Result<()> {
+    let stats = &mut ctx.accounts.stats;
+    stats.active_transfer_requests = stats.active_transfer_requests.saturating_sub(1);
+
     emit!(events::TransferCancelled {
         from_owner: ctx.accounts.owner.key(),
         to_owner: ctx.accounts.transfer_request.to_owner,
@@ -120,6 +217,34 @@ pub fn cancel_transfer(ctx: Context<CancelTransfer>) -> Result<()> {
     Ok(())
 }
 
+/// 关闭过期的转移请求
+///
+/// # 功能说明
+/// 任何人都可以关闭已过期的转移请求，使活跃转移请求数不包含失效的请求
+/// 转移请求账户会被关闭，lamports 返回给发起者
+pub fn close_expired_transfer(ctx: Context<CloseExpiredTransfer>) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    let transfer_request = &ctx.accounts.transfer_request;
+
+    require!(
+        timestamp > transfer_request.expires_at,
+        IdentityScoreError::TransferNotExpired
+    );
+
+    let stats = &mut ctx.accounts.stats;
+    stats.active_transfer_requests = stats.active_transfer_requests.saturating_sub(1);
+
+    emit!(events::TransferRequestExpired {
+        from_owner: transfer_request.from_owner,
+        to_owner: transfer_request.to_owner,
+        identity: transfer_request.identity,
+        transfer_request: transfer_request.key(),
+        timestamp,
+    });
+
+    Ok(())
+}
+
 /// 验证并提取旧的信用分数据
 ///
 /// # 功能说明
@@ -157,6 +282,92 @@ fn verify_and_extract_old_score(ctx: &Context<ClaimTransfer>) -> Result<CreditSc
     Ok(score_state)
 }
 
+/// 验证并提取旧的信用分历史数据
+///
+/// # 验证项
+/// - 账户地址是否为正确的 PDA
+/// - 账户所有者是否为当前程序
+/// - 历史记录是否关联到正确的身份账户
+fn verify_and_extract_old_score_history(ctx: &Context<ClaimTransfer>) -> Result<ScoreHistory> {
+    let (expected_history_pda, _) = Pubkey::find_program_address(
+        &[SEED_SCORE_HISTORY, ctx.accounts.old_owner.key().as_ref()],
+        ctx.program_id,
+    );
+
+    require_keys_eq!(
+        ctx.accounts.old_score_history.key(),
+        expected_history_pda,
+        IdentityScoreError::Unauthorized
+    );
+
+    require!(
+        ctx.accounts.old_score_history.owner == ctx.program_id,
+        IdentityScoreError::Unauthorized
+    );
+
+    let mut data_slice = &ctx.accounts.old_score_history.data.borrow()[..];
+    let history_state = ScoreHistory::try_deserialize(&mut data_slice)?;
+
+    require!(
+        history_state.identity == ctx.accounts.old_identity.key(),
+        IdentityScoreError::Unauthorized
+    );
+
+    Ok(history_state)
+}
+
+/// 验证并提取旧的还款记录
+///
+/// # 验证项
+/// - 账户地址是否为正确的 PDA
+/// - 账户所有者是否为当前程序
+fn verify_and_extract_old_credit_history(ctx: &Context<ClaimTransfer>) -> Result<CreditHistory> {
+    let (expected_history_pda, _) = Pubkey::find_program_address(
+        &[SEED_CREDIT_HISTORY, ctx.accounts.old_owner.key().as_ref()],
+        ctx.program_id,
+    );
+
+    require_keys_eq!(
+        ctx.accounts.old_credit_history.key(),
+        expected_history_pda,
+        IdentityScoreError::Unauthorized
+    );
+
+    require!(
+        ctx.accounts.old_credit_history.owner == ctx.program_id,
+        IdentityScoreError::Unauthorized
+    );
+
+    let mut data_slice = &ctx.accounts.old_credit_history.data.borrow()[..];
+    CreditHistory::try_deserialize(&mut data_slice)
+}
+
+/// 验证并提取旧所有者的处罚索引
+///
+/// # 验证项
+/// - 账户地址是否为正确的 PDA
+/// - 账户所有者是否为当前程序
+fn verify_and_extract_old_penalty_index(ctx: &Context<ClaimTransfer>) -> Result<PenaltyIndex> {
+    let (expected_index_pda, _) = Pubkey::find_program_address(
+        &[SEED_PENALTY_INDEX, ctx.accounts.old_owner.key().as_ref()],
+        ctx.program_id,
+    );
+
+    require_keys_eq!(
+        ctx.accounts.old_penalty_index.key(),
+        expected_index_pda,
+        IdentityScoreError::Unauthorized
+    );
+
+    require!(
+        ctx.accounts.old_penalty_index.owner == ctx.program_id,
+        IdentityScoreError::Unauthorized
+    );
+
+    let mut data_slice = &ctx.accounts.old_penalty_index.data.borrow()[..];
+    PenaltyIndex::try_deserialize(&mut data_slice)
+}
+
 /// 发起身份转移的账户结构
 ///
 /// # 所需账户
@@ -189,7 +400,8 @@ pub struct InitiateTransfer<'info> {
         seeds = [SEED_IDENTITY, owner.key().as_ref()],
         bump,
         constraint = identity.owner == owner.key() @ IdentityScoreError::Unauthorized,
-        constraint = identity.verified == true @ IdentityScoreError::IdentityNotVerified
+        constraint = identity.verified @ IdentityScoreError::IdentityNotVerified,
+        constraint = identity.is_active() @ IdentityScoreError::IdentityDeactivated
     )]
     pub identity: Account<'info, IdentityAccount>,
 
@@ -213,6 +425,10 @@ pub struct InitiateTransfer<'info> {
 
     /// 系统程序
     pub system_program: Program<'info, System>,
+
+    /// 全局统计账户
+    #[account(mut, seeds = [SEED_STATS], bump)]
+    pub stats: Account<'info, ProtocolStats>,
 }
 #[derive(Accounts)]
 pub struct ClaimTransfer<'info> {
@@ -222,7 +438,8 @@ pub struct ClaimTransfer<'info> {
         seeds = [SEED_IDENTITY, old_owner.key().as_ref()],
         bump,
         close = old_owner,
-        constraint = old_identity.owner == old_owner.key() @ IdentityScoreError::Unauthorized
+        constraint = old_identity.owner == old_owner.key() @ IdentityScoreError::Unauthorized,
+        constraint = old_identity.is_active() @ IdentityScoreError::IdentityDeactivated
     )]
     pub old_identity: Account<'info, IdentityAccount>,
 
@@ -230,18 +447,19 @@ pub struct ClaimTransfer<'info> {
     #[account(
         init,
         payer = new_owner,
-        space = 8 + 32 + 8 + 1 + 9,
+        space = IdentityAccount::SPACE,
         seeds = [SEED_IDENTITY, new_owner.key().as_ref()],
         bump
     )]
     pub new_identity: Account<'info, IdentityAccount>,
 
-    /// 转移请求账户（将被关闭）
+    /// 转移请求账户（将被关闭），必须由旧所有者发起
     #[account(
         mut,
         close = new_owner,
         seeds = [SEED_TRANSFER_REQUEST, transfer_request.from_owner.as_ref(), transfer_request.to_owner.as_ref()],
-        bump
+        bump,
+        constraint = transfer_request.from_owner == old_owner.key() @ IdentityScoreError::Unauthorized
     )]
     pub transfer_request: Account<'info, TransferRequest>,
 
@@ -254,12 +472,27 @@ pub struct ClaimTransfer<'info> {
     #[account(
         init_if_needed,
         payer = new_owner,
-        space = 8 + 32 + 1 + 1 + 8,
+        space = CreditScoreAccount::SPACE,
         seeds = [SEED_SCORE, new_owner.key().as_ref()],
         bump
     )]
     pub new_score: Account<'info, CreditScoreAccount>,
 
+    /// 旧的信用分历史账户（可选，可以是未初始化的）
+    /// CHECK: 允许未初始化的账户，地址通过 PDA 验证，仅在 data_len() > 0 时才反序列化处理
+    #[account(mut)]
+    pub old_score_history: AccountInfo<'info>,
+
+    /// 新的信用分历史账户（自动创建）
+    #[account(
+        init_if_needed,
+        payer = new_owner,
+        space = ScoreHistory::SPACE,
+        seeds = [SEED_SCORE_HISTORY, new_owner.key().as_ref()],
+        bump
+    )]
+    pub new_score_history: Account<'info, ScoreHistory>,
+
     /// 旧身份的所有者（不需要签名，已通过 initiate_transfer 授权）
     /// CHECK: 仅用于验证身份所有权和关闭账户
     #[account(mut)]
@@ -271,6 +504,39 @@ pub struct ClaimTransfer<'info> {
 
     /// 系统程序
     pub system_program: Program<'info, System>,
+
+    /// 全局统计账户
+    #[account(mut, seeds = [SEED_STATS], bump)]
+    pub stats: Account<'info, ProtocolStats>,
+
+    /// 旧的还款记录账户（可选，可以是未初始化的）
+    /// CHECK: 允许未初始化的账户，地址通过 PDA 验证，仅在 data_len() > 0 时才反序列化处理
+    #[account(mut)]
+    pub old_credit_history: AccountInfo<'info>,
+
+    /// 新的还款记录账户（自动创建）
+    #[account(
+        init_if_needed,
+        payer = new_owner,
+        space = CreditHistory::SPACE,
+        seeds = [SEED_CREDIT_HISTORY, new_owner.key().as_ref()],
+        bump
+    )]
+    pub new_credit_history: Account<'info, CreditHistory>,
+
+    /// 旧所有者的处罚索引（可以是未初始化的）
+    /// CHECK: 允许未初始化的账户，地址通过 PDA 验证，仅在 data_len() > 0 时才反序列化处理
+    pub old_penalty_index: AccountInfo<'info>,
+
+    /// 旧所有者的墓碑账户（自动创建），记录转出身份的负面标记
+    #[account(
+        init_if_needed,
+        payer = new_owner,
+        space = Tombstone::SPACE,
+        seeds = [SEED_TOMBSTONE, old_owner.key().as_ref()],
+        bump
+    )]
+    pub old_tombstone: Account<'info, Tombstone>,
 }
 
 #[derive(Accounts)]
@@ -288,4 +554,29 @@ pub struct CancelTransfer<'info> {
     /// 转移发起者
     #[account(mut)]
     pub owner: Signer<'info>,
+
+    /// 全局统计账户
+    #[account(mut, seeds = [SEED_STATS], bump)]
+    pub stats: Account<'info, ProtocolStats>,
+}
+
+#[derive(Accounts)]
+pub struct CloseExpiredTransfer<'info> {
+    /// 过期的转移请求账户（将被关闭）
+    #[account(
+        mut,
+        close = from_owner,
+        seeds = [SEED_TRANSFER_REQUEST, transfer_request.from_owner.as_ref(), transfer_request.to_owner.as_ref()],
+        bump
+    )]
+    pub transfer_request: Account<'info, TransferRequest>,
+
+    /// 转移发起者，接收返还的 lamports
+    /// CHECK: 地址必须与转移请求的发起者一致
+    #[account(mut, address = transfer_request.from_owner @ IdentityScoreError::Unauthorized)]
+    pub from_owner: UncheckedAccount<'info>,
+
+    /// 全局统计账户
+    #[account(mut, seeds = [SEED_STATS], bump)]
+    pub stats: Account<'info, ProtocolStats>,
 }
@@ -0,0 +1,62 @@
+//! Instructions-sysvar checks.
+//!
+//! A score reads the owner's balance at one instant, so a transaction that
+//! first credits the owner (a borrowed transfer, a flash loan) and then
+//! scores would inflate the asset factor. Scoring refuses to share its
+//! transaction with anything that could do that.
+
+use crate::errors::IdentityScoreError;
+use anchor_lang::prelude::*;
+use solana_instructions_sysvar::{load_current_index_checked, load_instruction_at_checked};
+
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
+    pubkey!("ComputeBudget111111111111111111111111111111");
+pub const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+pub const MEMO_V1_PROGRAM_ID: Pubkey = pubkey!("Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo");
+
+/// Programs that never move lamports and may always share the transaction
+const ALLOWED_PROGRAMS: [Pubkey; 3] = [
+    COMPUTE_BUDGET_PROGRAM_ID,
+    MEMO_PROGRAM_ID,
+    MEMO_V1_PROGRAM_ID,
+];
+
+/// Fails with `SuspiciousTransaction` unless every top-level instruction of
+/// the transaction is one of:
+/// - this instruction, invoked directly rather than through CPI
+/// - another instruction of this program
+/// - a compute budget or memo instruction
+/// - a system instruction that writes to the owner only as its first,
+///   funding account
+pub fn ensure_owner_not_credited(
+    instructions: &AccountInfo,
+    owner: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<()> {
+    let current = load_current_index_checked(instructions)? as usize;
+    let count = u16::from_le_bytes(
+        instructions.data.borrow()[..2]
+            .try_into()
+            .map_err(|_| IdentityScoreError::SuspiciousTransaction)?,
+    ) as usize;
+
+    for index in 0..count {
+        let ix = load_instruction_at_checked(index, instructions)?;
+        let allowed = if ix.program_id == *program_id {
+            true
+        } else if index == current {
+            // Called through CPI: the caller's own effects cannot be inspected
+            false
+        } else if ix.program_id == anchor_lang::system_program::ID {
+            !ix.accounts
+                .iter()
+                .skip(1)
+                .any(|meta| meta.pubkey == *owner && meta.is_writable)
+        } else {
+            ALLOWED_PROGRAMS.contains(&ix.program_id)
+        };
+        require!(allowed, IdentityScoreError::SuspiciousTransaction);
+    }
+
+    Ok(())
+}
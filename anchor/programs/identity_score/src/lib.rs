@@ -3,11 +3,20 @@ use anchor_lang::prelude::*;
 pub mod constants;
 pub mod errors;
 pub mod events;
+#[cfg(feature = "cpi")]
+pub mod gate;
+pub mod holdings;
 pub mod instructions;
+pub mod introspection;
+pub mod oracle;
+pub mod scoring;
 pub mod state;
 mod tests;
 
 use instructions::*;
+use state::{
+    BatchRefreshReport, ConfigParams, CreditEventKind, ModelParams, PenaltySeverity, ScorePreview,
+};
 
 declare_id!("7dTkLRoAkjVFnLvbPoPhxFFXLNAwg7VfDw798GjEZ4xk");
 
@@ -15,6 +24,71 @@ declare_id!("7dTkLRoAkjVFnLvbPoPhxFFXLNAwg7VfDw798GjEZ4xk");
 pub mod identity_score {
     use super::*;
 
+    pub fn initialize_config(ctx: Context<InitializeConfig>, params: ConfigParams) -> Result<()> {
+        instructions::config::initialize_config(ctx, params)
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+        instructions::config::update_config(ctx, params)
+    }
+
+    pub fn create_score_model(
+        ctx: Context<CreateScoreModel>,
+        version: u32,
+        params: ModelParams,
+    ) -> Result<()> {
+        instructions::config::create_score_model(ctx, version, params)
+    }
+
+    pub fn activate_score_model(ctx: Context<ActivateScoreModel>) -> Result<()> {
+        instructions::config::activate_score_model(ctx)
+    }
+
+    pub fn retire_score_model(ctx: Context<RetireScoreModel>) -> Result<()> {
+        instructions::config::retire_score_model(ctx)
+    }
+
+    pub fn set_allowed_mint(
+        ctx: Context<SetAllowedMint>,
+        decimals: u8,
+        weight: u64,
+        usd_weight: u64,
+    ) -> Result<()> {
+        instructions::config::set_allowed_mint(ctx, decimals, weight, usd_weight)
+    }
+
+    pub fn remove_allowed_mint(ctx: Context<RemoveAllowedMint>) -> Result<()> {
+        instructions::config::remove_allowed_mint(ctx)
+    }
+
+    pub fn add_reporter(ctx: Context<AddReporter>, program: Pubkey) -> Result<()> {
+        instructions::credit::add_reporter(ctx, program)
+    }
+
+    pub fn remove_reporter(ctx: Context<RemoveReporter>) -> Result<()> {
+        instructions::credit::remove_reporter(ctx)
+    }
+
+    pub fn report_credit_event(
+        ctx: Context<ReportCreditEvent>,
+        kind: CreditEventKind,
+    ) -> Result<()> {
+        instructions::credit::report_credit_event(ctx, kind)
+    }
+
+    pub fn add_penalty(
+        ctx: Context<AddPenalty>,
+        severity: PenaltySeverity,
+        reason: String,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::penalty::add_penalty(ctx, severity, reason, expires_at)
+    }
+
+    pub fn close_penalty(ctx: Context<ClosePenalty>) -> Result<()> {
+        instructions::penalty::close_penalty(ctx)
+    }
+
     pub fn create_identity(ctx: Context<CreateIdentity>) -> Result<()> {
         instructions::identity::create_identity(ctx)
     }
@@ -27,14 +101,99 @@ pub mod identity_score {
         instructions::identity::unverify_identity(ctx)
     }
 
+    pub fn deactivate_identity(ctx: Context<DeactivateIdentity>) -> Result<()> {
+        instructions::identity::deactivate_identity(ctx)
+    }
+
+    pub fn reactivate_identity(ctx: Context<ReactivateIdentity>) -> Result<()> {
+        instructions::identity::reactivate_identity(ctx)
+    }
+
     pub fn delete_identity(ctx: Context<DeleteIdentity>) -> Result<()> {
         instructions::identity::delete_identity(ctx)
     }
 
+    pub fn migrate_identity(ctx: Context<MigrateIdentity>) -> Result<()> {
+        instructions::identity::migrate_identity(ctx)
+    }
+
+    pub fn grant_consent(
+        ctx: Context<GrantConsent>,
+        grantee: Pubkey,
+        scope: u8,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        instructions::consent::grant_consent(ctx, grantee, scope, expires_at)
+    }
+
+    pub fn revoke_consent(ctx: Context<RevokeConsent>) -> Result<()> {
+        instructions::consent::revoke_consent(ctx)
+    }
+
+    pub fn check_in(ctx: Context<CheckIn>) -> Result<()> {
+        instructions::activity::check_in(ctx)
+    }
+
+    pub fn checkpoint_balance(ctx: Context<CheckpointBalance>) -> Result<()> {
+        instructions::balance::checkpoint_balance(ctx)
+    }
+
     pub fn calculate_score(ctx: Context<CalculateScore>) -> Result<()> {
         instructions::score::calculate_score(ctx)
     }
 
+    pub fn force_calculate_score(ctx: Context<ForceCalculateScore>) -> Result<()> {
+        instructions::score::force_calculate_score(ctx)
+    }
+
+    pub fn refresh_score(ctx: Context<RefreshScore>) -> Result<()> {
+        instructions::score::refresh_score(ctx)
+    }
+
+    pub fn batch_refresh_scores<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchRefreshScores<'info>>,
+        holding_counts: Vec<u8>,
+    ) -> Result<BatchRefreshReport> {
+        instructions::score::batch_refresh_scores(ctx, holding_counts)
+    }
+
+    pub fn set_keeper_refresh(
+        ctx: Context<SetKeeperRefresh>,
+        enabled: bool,
+        holdings: Vec<Pubkey>,
+    ) -> Result<()> {
+        instructions::keeper::set_keeper_refresh(ctx, enabled, holdings)
+    }
+
+    pub fn fund_tip_vault(
+        ctx: Context<FundTipVault>,
+        amount: u64,
+        tip_lamports: u64,
+    ) -> Result<()> {
+        instructions::keeper::fund_tip_vault(ctx, amount, tip_lamports)
+    }
+
+    pub fn close_tip_vault(ctx: Context<CloseTipVault>) -> Result<()> {
+        instructions::keeper::close_tip_vault(ctx)
+    }
+
+    pub fn preview_score(ctx: Context<PreviewScore>) -> Result<ScorePreview> {
+        instructions::score::preview_score(ctx)
+    }
+
+    pub fn assert_min_score(
+        ctx: Context<AssertMinScore>,
+        min_score: u8,
+        max_age_seconds: i64,
+        min_model_version: u32,
+    ) -> Result<()> {
+        instructions::score::assert_min_score(ctx, min_score, max_age_seconds, min_model_version)
+    }
+
+    pub fn migrate_score_level(ctx: Context<MigrateScoreLevel>) -> Result<()> {
+        instructions::score::migrate_score_level(ctx)
+    }
+
     pub fn delete_score(ctx: Context<DeleteScore>) -> Result<()> {
         instructions::score::delete_score(ctx)
     }
@@ -50,4 +209,8 @@ pub mod identity_score {
     pub fn cancel_transfer(ctx: Context<CancelTransfer>) -> Result<()> {
         instructions::transfer::cancel_transfer(ctx)
     }
+
+    pub fn close_expired_transfer(ctx: Context<CloseExpiredTransfer>) -> Result<()> {
+        instructions::transfer::close_expired_transfer(ctx)
+    }
 }
@@ -0,0 +1,152 @@
+//! SOL/USD price feed reader.
+//!
+//! Reads Pyth v2 price accounts, trusted only when owned by the Pyth oracle
+//! program and living at the address pinned in `ProtocolConfig`. Builds with
+//! the `mock-oracle` feature also accept a small mock format that local test
+//! validators can write directly.
+
+use crate::errors::IdentityScoreError;
+use anchor_lang::prelude::*;
+
+/// Owner of the Pyth v2 price accounts
+pub const PYTH_ORACLE_PROGRAM_ID: Pubkey = pubkey!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH");
+
+const PYTH_MAGIC: u32 = 0xa1b2_c3d4;
+const PYTH_VERSION: u32 = 2;
+const PYTH_ACCOUNT_TYPE_PRICE: u32 = 3;
+const PYTH_STATUS_TRADING: u32 = 1;
+const PYTH_EXPO_OFFSET: usize = 20;
+const PYTH_TIMESTAMP_OFFSET: usize = 96;
+const PYTH_AGG_PRICE_OFFSET: usize = 208;
+const PYTH_AGG_CONF_OFFSET: usize = 216;
+const PYTH_AGG_STATUS_OFFSET: usize = 224;
+const PYTH_PRICE_ACCOUNT_MIN_LEN: usize = 240;
+
+/// `magic (8) | price i64 | conf u64 | expo i32 | publish_time i64`
+#[cfg(feature = "mock-oracle")]
+pub const MOCK_ORACLE_MAGIC: [u8; 8] = *b"MOCKPRCE";
+#[cfg(feature = "mock-oracle")]
+const MOCK_ORACLE_LEN: usize = 8 + 8 + 8 + 4 + 8;
+
+const LAMPORTS_PER_SOL_EXPO: i32 = 9;
+const MICRO_USD_EXPO: i32 = 6;
+
+/// A checked SOL/USD price, `price * 10^expo` dollars per SOL
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SolPrice {
+    pub price: u64,
+    pub expo: i32,
+}
+
+impl SolPrice {
+    /// Value of `lamports` in micro-USD, saturating at `u64::MAX`
+    pub fn lamports_to_micro_usd(&self, lamports: u64) -> u64 {
+        let value = lamports as u128 * self.price as u128;
+        let shift = self.expo + MICRO_USD_EXPO - LAMPORTS_PER_SOL_EXPO;
+        let scaled = if shift >= 0 {
+            10u128
+                .checked_pow(shift as u32)
+                .and_then(|factor| value.checked_mul(factor))
+                .unwrap_or(u128::MAX)
+        } else {
+            10u128
+                .checked_pow(shift.unsigned_abs())
+                .map_or(0, |factor| value / factor)
+        };
+        scaled.min(u64::MAX as u128) as u64
+    }
+}
+
+/// Raw fields common to both formats
+struct PriceFeed {
+    price: i64,
+    conf: u64,
+    expo: i32,
+    publish_time: i64,
+}
+
+fn read_i64(data: &[u8], offset: usize) -> i64 {
+    i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn parse_pyth(data: &[u8]) -> Result<PriceFeed> {
+    require!(
+        data.len() >= PYTH_PRICE_ACCOUNT_MIN_LEN
+            && read_u32(data, 0) == PYTH_MAGIC
+            && read_u32(data, 4) == PYTH_VERSION
+            && read_u32(data, 8) == PYTH_ACCOUNT_TYPE_PRICE,
+        IdentityScoreError::InvalidPriceFeed
+    );
+    require!(
+        read_u32(data, PYTH_AGG_STATUS_OFFSET) == PYTH_STATUS_TRADING,
+        IdentityScoreError::InvalidPriceFeed
+    );
+
+    Ok(PriceFeed {
+        price: read_i64(data, PYTH_AGG_PRICE_OFFSET),
+        conf: read_i64(data, PYTH_AGG_CONF_OFFSET) as u64,
+        expo: read_u32(data, PYTH_EXPO_OFFSET) as i32,
+        publish_time: read_i64(data, PYTH_TIMESTAMP_OFFSET),
+    })
+}
+
+#[cfg(feature = "mock-oracle")]
+fn parse_mock(data: &[u8]) -> Result<PriceFeed> {
+    require!(
+        data.len() == MOCK_ORACLE_LEN,
+        IdentityScoreError::InvalidPriceFeed
+    );
+
+    Ok(PriceFeed {
+        price: read_i64(data, 8),
+        conf: read_i64(data, 16) as u64,
+        expo: read_u32(data, 24) as i32,
+        publish_time: read_i64(data, 28),
+    })
+}
+
+fn parse_feed(feed: &AccountInfo, data: &[u8]) -> Result<PriceFeed> {
+    #[cfg(feature = "mock-oracle")]
+    if data.starts_with(&MOCK_ORACLE_MAGIC) {
+        return parse_mock(data);
+    }
+
+    require_keys_eq!(
+        *feed.owner,
+        PYTH_ORACLE_PROGRAM_ID,
+        IdentityScoreError::InvalidPriceFeed
+    );
+    parse_pyth(data)
+}
+
+/// Reads the SOL/USD price, rejecting feeds older than `max_age_seconds` or
+/// whose confidence interval exceeds `max_confidence_bps` of the price
+pub fn read_sol_price(
+    feed: &AccountInfo,
+    now: i64,
+    max_age_seconds: i64,
+    max_confidence_bps: u64,
+) -> Result<SolPrice> {
+    let data = feed.try_borrow_data()?;
+    let parsed = parse_feed(feed, &data)?;
+
+    require!(parsed.price > 0, IdentityScoreError::InvalidPriceFeed);
+    require!(
+        now.saturating_sub(parsed.publish_time) <= max_age_seconds,
+        IdentityScoreError::StalePriceFeed
+    );
+    let price = parsed.price as u64;
+    require!(
+        parsed.conf as u128 * 10_000 <= price as u128 * max_confidence_bps as u128,
+        IdentityScoreError::PriceConfidenceTooWide
+    );
+
+    Ok(SolPrice {
+        price,
+        expo: parsed.expo,
+    })
+}
@@ -0,0 +1,304 @@
+//! Integer scoring engine.
+//!
+//! All fractional math uses unsigned Q32.32 fixed point, so the same inputs
+//! produce bit-identical scores on-chain and off-chain.
+
+use crate::oracle::SolPrice;
+use crate::state::{LevelCutoffs, ModelParams, ScoreBreakdown, ScoreComponent, ScoreLevel};
+
+/// Version of the model created with the protocol config, using
+/// `ModelParams::default()`
+pub const GENESIS_MODEL_VERSION: u32 = 1;
+
+/// 1.0 in Q32.32
+pub const ONE: u64 = 1 << 32;
+/// ln(2) in Q32.32
+const LN_2: u64 = 2_977_044_472;
+/// ln(10_000_000_000) in Q32.32, the asset value at which the asset factor
+/// saturates: 10 SOL in lamports, or $10,000 in micro-USD
+const LN_MAX_ASSET_VALUE: u64 = 98_895_276_707;
+
+const MAX_AGE_SECONDS: i64 = 365 * 24 * 60 * 60;
+/// Roughly a year of two-day epochs, after which stake earns the full bonus
+const MAX_STAKE_AGE_EPOCHS: u64 = 180;
+const MAX_STAKE_AGE_BONUS: u64 = 15;
+/// Balance, as a multiple of the owner's footprint rent, that earns the full
+/// rent-efficiency score
+const MAX_RENT_RATIO: u64 = 10;
+/// Streak length, in days, that earns the full streak half of the activity score
+const MAX_ACTIVITY_STREAK_DAYS: u64 = 30;
+/// Days active that earn the full tenure half of the activity score
+const MAX_ACTIVITY_TOTAL_DAYS: u64 = 180;
+/// Days without a check-in after which past activity stops counting
+const ACTIVITY_LAPSE_DAYS: u64 = 30;
+
+pub const SCORE_MIN: u8 = 30;
+const SCORE_RANGE: u64 = 60;
+
+/// log2(x) in Q32.32, for x > 0
+pub fn log2_fixed(x: u64) -> u64 {
+    debug_assert!(x > 0);
+    let msb = 63 - x.leading_zeros() as u64;
+
+    // Mantissa in [1, 2) as Q32.32
+    let mut y = if msb >= 32 {
+        x >> (msb - 32)
+    } else {
+        x << (32 - msb)
+    };
+
+    // Each squaring of the mantissa yields the next fractional bit
+    let mut fraction = 0u64;
+    for bit in (0..32).rev() {
+        y = ((y as u128 * y as u128) >> 32) as u64;
+        if y >= 2 * ONE {
+            y >>= 1;
+            fraction |= 1 << bit;
+        }
+    }
+
+    (msb << 32) | fraction
+}
+
+/// ln(x) in Q32.32, for x > 0
+pub fn ln_fixed(x: u64) -> u64 {
+    ((log2_fixed(x) as u128 * LN_2 as u128) >> 32) as u64
+}
+
+/// Maps a Q32.32 value in [0, 1] onto [SCORE_MIN, SCORE_MIN + SCORE_RANGE]
+fn normalize_score(normalized: u64) -> u8 {
+    SCORE_MIN + ((SCORE_RANGE * normalized.min(ONE)) >> 32) as u8
+}
+
+/// Resolved loan outcomes reported by lending programs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RepaymentRecord {
+    pub on_time: u32,
+    pub late: u32,
+    pub defaulted: u32,
+}
+
+/// The owner's check-ins as of the scoring time
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ActivityRecord {
+    /// Current streak, zero once a day was missed
+    pub streak_days: u32,
+    pub total_days: u32,
+    pub days_since_check_in: u64,
+}
+
+/// Everything the engine scores an identity on
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ScoreInputs {
+    /// The owner's wallet balance, time-weighted when the protocol requires checkpoints
+    pub lamports: u64,
+    /// Weighted lamport-equivalent value of the owner's allow-listed tokens,
+    /// scored without a SOL price
+    pub token_value: u64,
+    /// Micro-USD value of the same tokens, scored with a SOL price
+    pub token_usd_value: u64,
+    /// Lamports delegated from stake accounts the owner controls
+    pub staked_lamports: u64,
+    /// Stake-weighted average epochs since activation
+    pub stake_age_epochs: u64,
+    pub identity_created_at: i64,
+    pub is_verified: bool,
+    /// Rent-exempt minimum of the accounts the owner keeps: the identity,
+    /// score and score history, the owner's other PDAs that exist besides
+    /// the penalty index, and the verified holding accounts
+    pub footprint_rent: u64,
+    pub current_timestamp: i64,
+    /// When set, assets are valued in micro-USD instead of lamports: SOL
+    /// through this price, tokens through their own USD weights
+    pub sol_price: Option<SolPrice>,
+    /// Repayment history, weighted in only when at least one loan resolved
+    pub repayment: Option<RepaymentRecord>,
+    /// Check-in history; None before the owner's first check-in
+    pub activity: Option<ActivityRecord>,
+    /// Factor weights of the model doing the scoring
+    pub model: ModelParams,
+}
+
+pub fn calculate_comprehensive_score(
+    inputs: &ScoreInputs,
+    cutoffs: &LevelCutoffs,
+) -> (u8, ScoreLevel) {
+    let total_score = calculate_score_breakdown(inputs).base_score;
+    (total_score, cutoffs.level(total_score))
+}
+
+/// Every factor's sub-score and weight, and the weighted score they add up
+/// to; penalties are left for the caller to record
+pub fn calculate_score_breakdown(inputs: &ScoreInputs) -> ScoreBreakdown {
+    let sol_assets = inputs.lamports.saturating_add(inputs.staked_lamports);
+    let asset_value = match inputs.sol_price {
+        // Tokens are priced in USD directly, so only SOL moves with its price
+        Some(price) => price
+            .lamports_to_micro_usd(sol_assets)
+            .saturating_add(inputs.token_usd_value),
+        None => sol_assets.saturating_add(inputs.token_value),
+    };
+    let model = &inputs.model;
+    let component = |score: u8, weight_bps: u16| ScoreComponent { score, weight_bps };
+
+    let asset = component(calculate_asset_score(asset_value), model.weight_asset);
+    let stability = component(
+        calculate_stability_score(
+            inputs.identity_created_at,
+            inputs.is_verified,
+            inputs.stake_age_epochs,
+            inputs.current_timestamp,
+        ),
+        model.weight_stability,
+    );
+    let rent_efficiency = component(
+        calculate_rent_efficiency_score(inputs.lamports, inputs.footprint_rent),
+        model.weight_rent_efficiency,
+    );
+    let verification = component(
+        calculate_verification_score(inputs.is_verified),
+        model.weight_verification,
+    );
+    let activity = component(
+        calculate_activity_score(inputs.activity),
+        model.weight_activity,
+    );
+    // Without resolved loans the repayment factor carries no weight at all
+    let repayment = match inputs.repayment {
+        Some(record) => component(calculate_repayment_score(&record), model.weight_repayment),
+        None => component(0, 0),
+    };
+
+    let components = [
+        asset,
+        stability,
+        rent_efficiency,
+        verification,
+        activity,
+        repayment,
+    ];
+    let weighted: u64 = components
+        .iter()
+        .map(|c| c.score as u64 * c.weight_bps as u64)
+        .sum();
+    let total_weight: u64 = components.iter().map(|c| c.weight_bps as u64).sum();
+
+    ScoreBreakdown {
+        asset,
+        stability,
+        rent_efficiency,
+        verification,
+        activity,
+        repayment,
+        base_score: (weighted / total_weight) as u8,
+        penalty_deduction: 0,
+    }
+}
+
+/// Combined effect of an owner's active reporter penalties
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PenaltyEffect {
+    pub points: u8,
+    /// Highest level the score may reach
+    pub level_cap: Option<ScoreLevel>,
+}
+
+impl PenaltyEffect {
+    pub fn combine(self, other: PenaltyEffect) -> PenaltyEffect {
+        let level_cap = match (self.level_cap, other.level_cap) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        PenaltyEffect {
+            points: self.points.saturating_add(other.points),
+            level_cap,
+        }
+    }
+}
+
+/// Subtracts the penalty points, then clamps the score under the level cap
+pub fn apply_penalty_effect(
+    score: u8,
+    effect: PenaltyEffect,
+    cutoffs: &LevelCutoffs,
+) -> (u8, ScoreLevel) {
+    let penalized = apply_penalty(score, effect.points);
+    let capped = match effect.level_cap {
+        Some(level) => penalized.min(cutoffs.ceiling(level)),
+        None => penalized,
+    };
+    (capped, cutoffs.level(capped))
+}
+
+/// Deducts penalty points from a computed score, never pushing it below SCORE_MIN
+pub fn apply_penalty(score: u8, penalty_points: u8) -> u8 {
+    score
+        .saturating_sub(penalty_points)
+        .max(SCORE_MIN.min(score))
+}
+
+fn calculate_asset_score(asset_value: u64) -> u8 {
+    if asset_value == 0 {
+        return SCORE_MIN;
+    }
+    let normalized = (ln_fixed(asset_value) as u128 * ONE as u128) / LN_MAX_ASSET_VALUE as u128;
+    normalize_score(normalized as u64)
+}
+
+fn calculate_stability_score(
+    identity_created_at: i64,
+    is_verified: bool,
+    stake_age_epochs: u64,
+    current_timestamp: i64,
+) -> u8 {
+    let age_seconds = (current_timestamp - identity_created_at).clamp(0, MAX_AGE_SECONDS);
+    let age_score = (age_seconds * 60 / MAX_AGE_SECONDS) as u8;
+    let stake_bonus = (stake_age_epochs.min(MAX_STAKE_AGE_EPOCHS) * MAX_STAKE_AGE_BONUS
+        / MAX_STAKE_AGE_EPOCHS) as u8;
+    (age_score + stake_bonus + if is_verified { 30 } else { 0 }).min(90)
+}
+
+/// How many times over the wallet balance covers the rent locked in the
+/// owner's accounts, scaled from 1x (SCORE_MIN) to MAX_RENT_RATIO
+fn calculate_rent_efficiency_score(lamports: u64, footprint_rent: u64) -> u8 {
+    let rent_exempt = footprint_rent.max(1) as u128;
+    let clamped = (lamports as u128).clamp(rent_exempt, rent_exempt * MAX_RENT_RATIO as u128);
+    // (ratio - 1) / (MAX_RENT_RATIO - 1), kept exact by scaling before dividing
+    let normalized = ((clamped - rent_exempt) << 32) / (rent_exempt * (MAX_RENT_RATIO as u128 - 1));
+    normalize_score(normalized as u64)
+}
+
+/// Half for the current streak, half for the days active overall; the latter
+/// only while the owner still checks in
+fn calculate_activity_score(activity: Option<ActivityRecord>) -> u8 {
+    let Some(record) = activity else {
+        return SCORE_MIN;
+    };
+    let streak = (record.streak_days as u64).min(MAX_ACTIVITY_STREAK_DAYS);
+    let total = if record.days_since_check_in <= ACTIVITY_LAPSE_DAYS {
+        (record.total_days as u64).min(MAX_ACTIVITY_TOTAL_DAYS)
+    } else {
+        0
+    };
+    let normalized =
+        (streak << 31) / MAX_ACTIVITY_STREAK_DAYS + (total << 31) / MAX_ACTIVITY_TOTAL_DAYS;
+    normalize_score(normalized)
+}
+
+/// On-time repayments count fully, late ones half, defaults not at all
+fn calculate_repayment_score(record: &RepaymentRecord) -> u8 {
+    let resolved = record.on_time as u128 + record.late as u128 + record.defaulted as u128;
+    if resolved == 0 {
+        return SCORE_MIN;
+    }
+    let credit = 2 * record.on_time as u128 + record.late as u128;
+    normalize_score(((credit << 32) / (2 * resolved)) as u64)
+}
+
+fn calculate_verification_score(is_verified: bool) -> u8 {
+    if is_verified {
+        90
+    } else {
+        50
+    }
+}
@@ -0,0 +1,62 @@
+use crate::constants::ACTIVITY_DAY_SECONDS;
+use crate::errors::IdentityScoreError;
+use crate::scoring::ActivityRecord;
+use anchor_lang::prelude::*;
+
+/// The owner's check-ins, counted in whole days
+#[account]
+pub struct Activity {
+    pub identity: Pubkey,
+    pub last_check_in: i64,
+    /// Consecutive days checked in, ending on the day of `last_check_in`
+    pub current_streak: u32,
+    /// Distinct days checked in
+    pub total_days: u32,
+}
+
+impl Activity {
+    pub const SPACE: usize = 8 + 32 + 8 + 4 + 4;
+
+    pub fn day(timestamp: i64) -> i64 {
+        timestamp.div_euclid(ACTIVITY_DAY_SECONDS)
+    }
+
+    /// Counts `now` as a day of activity: extends the streak when the last
+    /// check-in was yesterday and restarts it otherwise
+    pub fn check_in(&mut self, now: i64) -> Result<()> {
+        let today = Self::day(now);
+        self.current_streak = if self.total_days == 0 {
+            1
+        } else {
+            let last_day = Self::day(self.last_check_in);
+            require!(today > last_day, IdentityScoreError::AlreadyCheckedIn);
+            if today == last_day + 1 {
+                self.current_streak.saturating_add(1)
+            } else {
+                1
+            }
+        };
+        self.total_days = self.total_days.saturating_add(1);
+        self.last_check_in = now;
+        Ok(())
+    }
+
+    /// The streak as it stands at `now`: broken once a whole day passes
+    /// without a check-in
+    pub fn streak_at(&self, now: i64) -> u32 {
+        if self.total_days == 0 || Self::day(now) > Self::day(self.last_check_in) + 1 {
+            0
+        } else {
+            self.current_streak
+        }
+    }
+
+    /// What the scoring engine sees at `now`; None before the first check-in
+    pub fn record(&self, now: i64) -> Option<ActivityRecord> {
+        (self.total_days > 0).then(|| ActivityRecord {
+            streak_days: self.streak_at(now),
+            total_days: self.total_days,
+            days_since_check_in: (Self::day(now) - Self::day(self.last_check_in)).max(0) as u64,
+        })
+    }
+}
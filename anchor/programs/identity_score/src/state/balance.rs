@@ -0,0 +1,92 @@
+use crate::constants::BALANCE_HISTORY_LEN;
+use anchor_lang::prelude::*;
+
+/// Ring buffer of observed wallet balances, so scoring can use a
+/// time-weighted average instead of a balance that may be borrowed for a
+/// single transaction
+#[account]
+pub struct BalanceHistory {
+    pub identity: Pubkey,
+    /// Slot the next checkpoint is written to
+    pub head: u8,
+    /// Number of valid checkpoints, at most BALANCE_HISTORY_LEN
+    pub len: u8,
+    pub checkpoints: [BalanceCheckpoint; BALANCE_HISTORY_LEN],
+}
+
+impl BalanceHistory {
+    pub const SPACE: usize = 8 + 32 + 1 + 1 + BalanceCheckpoint::SPACE * BALANCE_HISTORY_LEN;
+
+    pub fn push(&mut self, checkpoint: BalanceCheckpoint) {
+        self.checkpoints[self.head as usize] = checkpoint;
+        self.head = ((self.head as usize + 1) % BALANCE_HISTORY_LEN) as u8;
+        self.len = (self.len + 1).min(BALANCE_HISTORY_LEN as u8);
+    }
+
+    /// Pushes `checkpoint`, or, when the latest checkpoint is less than
+    /// `min_spacing` seconds older, keeps the lower of the two balances in it
+    pub fn record(&mut self, checkpoint: BalanceCheckpoint, min_spacing: i64) {
+        let latest = (self.head as usize + BALANCE_HISTORY_LEN - 1) % BALANCE_HISTORY_LEN;
+        let latest = &mut self.checkpoints[latest];
+        if self.len > 0 && checkpoint.timestamp < latest.timestamp + min_spacing {
+            latest.lamports = latest.lamports.min(checkpoint.lamports);
+        } else {
+            self.push(checkpoint);
+        }
+    }
+
+    pub fn latest(&self) -> Option<&BalanceCheckpoint> {
+        self.iter_newest_first().next()
+    }
+
+    /// Valid checkpoints from newest to oldest
+    pub fn iter_newest_first(&self) -> impl Iterator<Item = &BalanceCheckpoint> {
+        (1..=self.len as usize).map(move |i| {
+            &self.checkpoints[(self.head as usize + BALANCE_HISTORY_LEN - i) % BALANCE_HISTORY_LEN]
+        })
+    }
+
+    /// Average balance over `[now - window, now]`, each checkpoint holding
+    /// until the next one. The last checkpoint before the window carries into
+    /// it but does not count toward `min_checkpoints`; None if fewer than
+    /// that many were taken inside the window.
+    pub fn time_weighted_average(&self, now: i64, window: i64, min_checkpoints: u8) -> Option<u64> {
+        let start = now - window;
+        let mut end = now;
+        let mut weighted = 0u128;
+        let mut covered = 0u128;
+        let mut in_window = 0u8;
+        let mut newest = None;
+
+        for checkpoint in self.iter_newest_first() {
+            newest = newest.or(Some(checkpoint.lamports));
+            let span = (end - checkpoint.timestamp.max(start)).max(0) as u128;
+            weighted += checkpoint.lamports as u128 * span;
+            covered += span;
+            if checkpoint.timestamp <= start {
+                break;
+            }
+            in_window = in_window.saturating_add(1);
+            end = checkpoint.timestamp;
+        }
+
+        if in_window < min_checkpoints {
+            return None;
+        }
+        if covered == 0 {
+            // Every checkpoint in the window was taken at `now`
+            return newest;
+        }
+        Some((weighted / covered) as u64)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct BalanceCheckpoint {
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+impl BalanceCheckpoint {
+    pub const SPACE: usize = 8 + 8;
+}
@@ -0,0 +1,190 @@
+use crate::constants::{
+    BALANCE_HISTORY_LEN, MAX_DELETION_COOLDOWN_SECONDS, MAX_SCORE_INTERVAL_SECONDS,
+};
+use crate::errors::IdentityScoreError;
+use crate::state::ScoreLevel;
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct ProtocolConfig {
+    pub admin: Pubkey,
+    pub deletion_cooldown_seconds: i64,
+    pub score_validity_seconds: i64,
+    pub min_score_interval_seconds: i64,
+    pub price_oracle: Pubkey,
+    pub max_price_age_seconds: i64,
+    pub max_price_confidence_bps: u64,
+    /// Model used for every new score calculation
+    pub active_model_version: u32,
+    pub level_cutoffs: LevelCutoffs,
+    pub balance_window_seconds: i64,
+    pub min_balance_checkpoints: u8,
+}
+
+impl ProtocolConfig {
+    pub const SPACE: usize = 8 + 32 + 8 + 8 + 8 + 32 + 8 + 8 + 4 + LevelCutoffs::SPACE + 8 + 1;
+
+    pub fn apply(&mut self, params: &ConfigParams) {
+        self.deletion_cooldown_seconds = params.deletion_cooldown_seconds;
+        self.score_validity_seconds = params.score_validity_seconds;
+        self.min_score_interval_seconds = params.min_score_interval_seconds;
+        self.price_oracle = params.price_oracle;
+        self.max_price_age_seconds = params.max_price_age_seconds;
+        self.max_price_confidence_bps = params.max_price_confidence_bps;
+        self.level_cutoffs = params.level_cutoffs;
+        self.balance_window_seconds = params.balance_window_seconds;
+        self.min_balance_checkpoints = params.min_balance_checkpoints;
+    }
+
+    pub fn uses_price_oracle(&self) -> bool {
+        self.price_oracle != Pubkey::default()
+    }
+
+    /// Whether scoring uses the time-weighted balance instead of the live one
+    pub fn uses_balance_checkpoints(&self) -> bool {
+        self.min_balance_checkpoints > 0
+    }
+}
+
+/// Tunable protocol parameters, set by the admin
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ConfigParams {
+    /// Seconds an identity must stay deactivated before it can be deleted, at
+    /// most MAX_DELETION_COOLDOWN_SECONDS
+    pub deletion_cooldown_seconds: i64,
+    /// Seconds a calculated score stays fresh before it starts to decay
+    pub score_validity_seconds: i64,
+    /// Minimum seconds between two score calculations for the same identity,
+    /// at most MAX_SCORE_INTERVAL_SECONDS
+    pub min_score_interval_seconds: i64,
+    /// SOL/USD price account; the default pubkey keeps assets valued in lamports
+    pub price_oracle: Pubkey,
+    /// Oldest price, in seconds, that scoring accepts
+    pub max_price_age_seconds: i64,
+    /// Widest confidence interval accepted, in basis points of the price
+    pub max_price_confidence_bps: u64,
+    /// Scores at which each level above Poor begins
+    pub level_cutoffs: LevelCutoffs,
+    /// Seconds of balance checkpoints averaged into the asset factor
+    pub balance_window_seconds: i64,
+    /// Checkpoints required inside the window, at most BALANCE_HISTORY_LEN;
+    /// zero scores the live balance
+    pub min_balance_checkpoints: u8,
+}
+
+impl ConfigParams {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            (0..=MAX_DELETION_COOLDOWN_SECONDS).contains(&self.deletion_cooldown_seconds),
+            IdentityScoreError::InvalidDeletionCooldown
+        );
+        require!(
+            (0..=MAX_SCORE_INTERVAL_SECONDS).contains(&self.min_score_interval_seconds),
+            IdentityScoreError::InvalidScoreInterval
+        );
+        require!(
+            self.level_cutoffs.is_valid(),
+            IdentityScoreError::InvalidLevelCutoffs
+        );
+        require!(
+            (self.min_balance_checkpoints == 0 || self.balance_window_seconds > 0)
+                && self.min_balance_checkpoints as usize <= BALANCE_HISTORY_LEN,
+            IdentityScoreError::InvalidBalanceWindow
+        );
+        Ok(())
+    }
+}
+
+/// Lowest score of each level above Poor; must be strictly ascending
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LevelCutoffs {
+    pub fair: u8,
+    pub good: u8,
+    pub very_good: u8,
+    pub excellent: u8,
+}
+
+impl LevelCutoffs {
+    pub const SPACE: usize = 4;
+
+    pub fn is_valid(&self) -> bool {
+        0 < self.fair
+            && self.fair < self.good
+            && self.good < self.very_good
+            && self.very_good < self.excellent
+    }
+
+    pub fn level(&self, score: u8) -> ScoreLevel {
+        if score >= self.excellent {
+            ScoreLevel::Excellent
+        } else if score >= self.very_good {
+            ScoreLevel::VeryGood
+        } else if score >= self.good {
+            ScoreLevel::Good
+        } else if score >= self.fair {
+            ScoreLevel::Fair
+        } else {
+            ScoreLevel::Poor
+        }
+    }
+
+    /// Highest score that still maps to `level`
+    pub fn ceiling(&self, level: ScoreLevel) -> u8 {
+        match level {
+            ScoreLevel::Poor => self.fair - 1,
+            ScoreLevel::Fair => self.good - 1,
+            ScoreLevel::Good => self.very_good - 1,
+            ScoreLevel::VeryGood => self.excellent - 1,
+            ScoreLevel::Excellent => u8::MAX,
+        }
+    }
+}
+
+/// Splits the old Low (below 50) and Medium (50-69) levels in two and keeps
+/// 70 as the start of the top level
+impl Default for LevelCutoffs {
+    fn default() -> Self {
+        LevelCutoffs {
+            fair: 40,
+            good: 50,
+            very_good: 60,
+            excellent: 70,
+        }
+    }
+}
+
+/// Token mint whose holdings count toward the asset factor
+#[account]
+pub struct AllowedMint {
+    pub mint: Pubkey,
+    pub decimals: u8,
+    /// Lamport-equivalent value credited per whole token
+    pub weight: u64,
+    /// Micro-USD value credited per whole token, used instead of `weight`
+    /// when the protocol values assets in USD
+    pub usd_weight: u64,
+}
+
+impl AllowedMint {
+    pub const SPACE: usize = 8 + 32 + 1 + 8 + 8;
+
+    /// Lamport-equivalent value of `amount` base units of this mint; zero for
+    /// decimals too large to scale by, where no amount is worth a lamport
+    pub fn value_of(&self, amount: u64) -> u64 {
+        self.scale(amount, self.weight)
+    }
+
+    /// Micro-USD value of `amount` base units of this mint, likewise zero for
+    /// decimals too large to scale by
+    pub fn usd_value_of(&self, amount: u64) -> u64 {
+        self.scale(amount, self.usd_weight)
+    }
+
+    fn scale(&self, amount: u64, per_token: u64) -> u64 {
+        let Some(scale) = 10u128.checked_pow(self.decimals as u32) else {
+            return 0;
+        };
+        let value = amount as u128 * per_token as u128 / scale;
+        value.min(u64::MAX as u128) as u64
+    }
+}
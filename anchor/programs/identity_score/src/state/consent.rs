@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+/// An owner's permission for one program to rely on their score
+#[account]
+pub struct Consent {
+    pub owner: Pubkey,
+    pub grantee: Pubkey,
+    /// CONSENT_SCOPE_* bits
+    pub scope: u8,
+    pub granted_at: i64,
+    /// None for a consent that lasts until revoked
+    pub expires_at: Option<i64>,
+}
+
+impl Consent {
+    pub const SPACE: usize = 8 + 32 + 32 + 1 + 8 + 9;
+
+    /// True while unexpired and covering every bit of `scope`
+    pub fn allows(&self, scope: u8, now: i64) -> bool {
+        self.scope & scope == scope && self.expires_at.is_none_or(|expires_at| now < expires_at)
+    }
+}
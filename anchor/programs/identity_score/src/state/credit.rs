@@ -0,0 +1,93 @@
+use crate::scoring::RepaymentRecord;
+use anchor_lang::prelude::*;
+
+/// Lending program allowed to report credit events
+#[account]
+pub struct Reporter {
+    /// The lending program's id
+    pub program: Pubkey,
+    /// PDA of `[SEED_REPORTER_AUTHORITY]` under `program`, which only that
+    /// program can sign for
+    pub authority: Pubkey,
+    pub added_at: i64,
+    pub events_reported: u64,
+}
+
+impl Reporter {
+    pub const SPACE: usize = 8 + 32 + 32 + 8 + 8;
+}
+
+/// Loans one reporter opened for one owner and has not resolved yet, so a
+/// lender can only resolve the loans it opened itself
+#[account]
+pub struct ReporterLoans {
+    pub owner: Pubkey,
+    /// Program id of the reporter
+    pub reporter: Pubkey,
+    pub open_loans: u32,
+}
+
+impl ReporterLoans {
+    pub const SPACE: usize = 8 + 32 + 32 + 4;
+}
+
+/// Aggregate repayment behaviour of one owner
+#[account]
+pub struct CreditHistory {
+    pub owner: Pubkey,
+    pub loans_opened: u32,
+    pub repaid_on_time: u32,
+    pub repaid_late: u32,
+    pub defaulted: u32,
+    pub last_event_at: i64,
+}
+
+impl CreditHistory {
+    pub const SPACE: usize = 8 + 32 + 4 + 4 + 4 + 4 + 8;
+
+    pub fn resolved_loans(&self) -> u32 {
+        self.repaid_on_time
+            .saturating_add(self.repaid_late)
+            .saturating_add(self.defaulted)
+    }
+
+    pub fn outstanding_loans(&self) -> u32 {
+        self.loans_opened.saturating_sub(self.resolved_loans())
+    }
+
+    pub fn record(&mut self, kind: CreditEventKind) {
+        let counter = match kind {
+            CreditEventKind::LoanOpened => &mut self.loans_opened,
+            CreditEventKind::RepaidOnTime => &mut self.repaid_on_time,
+            CreditEventKind::RepaidLate => &mut self.repaid_late,
+            CreditEventKind::Defaulted => &mut self.defaulted,
+        };
+        *counter = counter.saturating_add(1);
+    }
+
+    /// Folds another history into this one, used when an identity changes hands
+    pub fn merge(&mut self, other: &CreditHistory) {
+        self.loans_opened = self.loans_opened.saturating_add(other.loans_opened);
+        self.repaid_on_time = self.repaid_on_time.saturating_add(other.repaid_on_time);
+        self.repaid_late = self.repaid_late.saturating_add(other.repaid_late);
+        self.defaulted = self.defaulted.saturating_add(other.defaulted);
+        self.last_event_at = self.last_event_at.max(other.last_event_at);
+    }
+
+    /// Repayment outcomes for scoring; `None` until at least one loan resolved
+    pub fn repayment_record(&self) -> Option<RepaymentRecord> {
+        (self.resolved_loans() > 0).then_some(RepaymentRecord {
+            on_time: self.repaid_on_time,
+            late: self.repaid_late,
+            defaulted: self.defaulted,
+        })
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CreditEventKind {
+    LoanOpened,
+    RepaidOnTime,
+    RepaidLate,
+    Defaulted,
+}
@@ -1,3 +1,4 @@
+use crate::constants::*;
 use anchor_lang::prelude::*;
 
 #[account]
@@ -6,4 +7,74 @@ pub struct IdentityAccount {
     pub created_at: i64,
     pub verified: bool,
     pub verified_at: Option<i64>,
+    pub referrer: Option<Pubkey>,
+    pub referral_credited: bool,
+    pub deactivated_at: Option<i64>,
+    pub penalty_points: u8,
+    pub eligible_at: i64,
+    /// Lets any keeper recalculate the score through `refresh_score`
+    pub keeper_refresh: bool,
+}
+
+impl IdentityAccount {
+    // discriminator + pubkey + i64 + bool + Option<i64> + Option<Pubkey> + bool + Option<i64>
+    // + u8 + i64 + bool
+    pub const SPACE: usize = 8 + 32 + 8 + 1 + 9 + 33 + 1 + 9 + 1 + 8 + 1;
+    /// Size of identities created before referrals, when the account ended at
+    /// `verified_at`; zeroed bytes are valid defaults for every later field
+    pub const LEGACY_SPACE: usize = 8 + 32 + 8 + 1 + 9;
+
+    pub fn is_active(&self) -> bool {
+        self.deactivated_at.is_none()
+    }
+}
+
+#[account]
+pub struct ReferralStats {
+    pub referrer_identity: Pubkey,
+    pub referred_count: u64,
+    pub verified_referred_count: u64,
+}
+
+impl ReferralStats {
+    pub const SPACE: usize = 8 + 32 + 8 + 8;
+}
+
+/// Survives `delete_identity` so a recreated identity inherits the owner's history
+#[account]
+pub struct Tombstone {
+    pub owner: Pubkey,
+    pub deletion_count: u32,
+    pub last_deleted_at: i64,
+    pub negative_marks: u32,
+}
+
+impl Tombstone {
+    pub const SPACE: usize = 8 + 32 + 4 + 8 + 4;
+
+    /// Score points deducted from an identity recreated by this owner
+    pub fn penalty_points(&self) -> u8 {
+        let points = self.deletion_count as u64 * TOMBSTONE_PENALTY_PER_DELETION
+            + self.negative_marks as u64 * TOMBSTONE_PENALTY_PER_MARK;
+        points.min(TOMBSTONE_MAX_PENALTY) as u8
+    }
+
+    /// Adds the negative marks an identity leaves behind when it is deleted
+    /// or transferred away: one per active reporter penalty, one per
+    /// defaulted loan, and one per `TOMBSTONE_PENALTY_PER_MARK` of penalty
+    /// points it carried beyond what this tombstone already imposes
+    pub fn record_marks(&mut self, penalty_points: u8, active_penalties: u32, defaults: u32) {
+        let carried = penalty_points.saturating_sub(self.penalty_points()) as u64;
+        let carried_marks = carried.div_ceil(TOMBSTONE_PENALTY_PER_MARK) as u32;
+        self.negative_marks = self
+            .negative_marks
+            .saturating_add(active_penalties)
+            .saturating_add(defaults)
+            .saturating_add(carried_marks);
+    }
+
+    /// Earliest time an identity recreated by this owner can be scored
+    pub fn eligible_at(&self) -> i64 {
+        self.last_deleted_at + self.deletion_count as i64 * TOMBSTONE_WAITING_PERIOD_SECONDS
+    }
 }
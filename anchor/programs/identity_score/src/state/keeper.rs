@@ -0,0 +1,16 @@
+use crate::holdings::MAX_HOLDING_ACCOUNTS;
+use anchor_lang::prelude::*;
+
+/// Holding accounts an owner registered when opting in to keeper refreshes.
+/// Keepers must pass exactly these, so a refresh cannot leave holdings out.
+#[account]
+pub struct HoldingRegistry {
+    pub owner: Pubkey,
+    /// Token and stake accounts, without the allow-list accounts that follow
+    /// token accounts in `remaining_accounts`
+    pub holdings: Vec<Pubkey>,
+}
+
+impl HoldingRegistry {
+    pub const SPACE: usize = 8 + 32 + 4 + 32 * MAX_HOLDING_ACCOUNTS;
+}
@@ -1,7 +1,27 @@
+pub mod activity;
+pub mod balance;
+pub mod config;
+pub mod consent;
+pub mod credit;
 pub mod identity;
+pub mod keeper;
+pub mod model;
+pub mod penalty;
 pub mod score;
+pub mod stats;
+pub mod tip;
 pub mod transfer;
 
+pub use activity::*;
+pub use balance::*;
+pub use config::*;
+pub use consent::*;
+pub use credit::*;
 pub use identity::*;
+pub use keeper::*;
+pub use model::*;
+pub use penalty::*;
 pub use score::*;
+pub use stats::*;
+pub use tip::*;
 pub use transfer::*;
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+
+/// One version of the scoring formula
+#[account]
+pub struct ScoreModel {
+    pub version: u32,
+    pub params: ModelParams,
+    /// Cleared when the model is retired; scores it produced then read as stale
+    pub active: bool,
+    pub created_at: i64,
+    pub retired_at: Option<i64>,
+}
+
+impl ScoreModel {
+    pub const SPACE: usize = 8 + 4 + ModelParams::SPACE + 1 + 8 + 9;
+}
+
+/// Factor weights in basis points
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ModelParams {
+    pub weight_asset: u16,
+    pub weight_stability: u16,
+    pub weight_rent_efficiency: u16,
+    pub weight_verification: u16,
+    /// Zero in model version 1, so check-ins only count under a model the
+    /// admin creates and activates with a non-zero weight here
+    pub weight_activity: u16,
+    /// Added on top of the other weights once an owner has repayment history
+    pub weight_repayment: u16,
+}
+
+impl ModelParams {
+    pub const SPACE: usize = 2 * 6;
+    /// The base weights must sum to this
+    pub const WEIGHT_TOTAL: u16 = 10_000;
+
+    pub fn is_valid(&self) -> bool {
+        self.weight_asset as u32
+            + self.weight_stability as u32
+            + self.weight_rent_efficiency as u32
+            + self.weight_verification as u32
+            + self.weight_activity as u32
+            == Self::WEIGHT_TOTAL as u32
+    }
+}
+
+/// The weights of model version 1, created with the protocol config. It
+/// predates check-ins, so activity carries no weight: for check-ins to move a
+/// score, the admin must `create_score_model` with a non-zero
+/// `weight_activity`, taking it from the other base weights, and then
+/// `activate_score_model` it.
+impl Default for ModelParams {
+    fn default() -> Self {
+        ModelParams {
+            weight_asset: 4_000,
+            weight_stability: 3_000,
+            weight_rent_efficiency: 2_000,
+            weight_verification: 1_000,
+            weight_activity: 0,
+            weight_repayment: 2_500,
+        }
+    }
+}
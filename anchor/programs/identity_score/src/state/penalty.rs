@@ -0,0 +1,111 @@
+use crate::constants::*;
+use crate::scoring::PenaltyEffect;
+use crate::state::ScoreLevel;
+use anchor_lang::prelude::*;
+
+/// Negative mark attached to an owner by a registered reporter
+#[account]
+pub struct Penalty {
+    pub owner: Pubkey,
+    /// Position in the owner's `PenaltyIndex`, part of the PDA seeds
+    pub index: u32,
+    /// Program id of the reporter that issued the penalty
+    pub reporter: Pubkey,
+    pub severity: PenaltySeverity,
+    pub reason: String,
+    pub created_at: i64,
+    pub expires_at: i64,
+    /// Receives the rent back when the penalty is closed
+    pub payer: Pubkey,
+}
+
+impl Penalty {
+    pub const SPACE: usize = 8 + 32 + 4 + 32 + 1 + (4 + PENALTY_REASON_MAX_LEN) + 8 + 8 + 32;
+
+    pub fn is_active(&self, now: i64) -> bool {
+        now < self.expires_at
+    }
+}
+
+/// Per-owner penalty counter plus the penalties that are still open, so
+/// scoring never depends on the caller passing every `Penalty` account
+#[account]
+pub struct PenaltyIndex {
+    pub owner: Pubkey,
+    /// Penalties `0..next_index` have been issued; closed ones no longer exist
+    pub next_index: u32,
+    pub open: Vec<OpenPenalty>,
+}
+
+impl PenaltyIndex {
+    pub const SPACE: usize = 8 + 32 + 4 + 4 + MAX_OPEN_PENALTIES * OpenPenalty::SPACE;
+
+    pub fn prune_expired(&mut self, now: i64) {
+        self.open.retain(|penalty| now < penalty.expires_at);
+    }
+
+    pub fn remove(&mut self, index: u32) {
+        self.open.retain(|penalty| penalty.index != index);
+    }
+
+    pub fn has_active(&self, now: i64) -> bool {
+        self.open.iter().any(|penalty| now < penalty.expires_at)
+    }
+
+    pub fn active_count(&self, now: i64) -> u32 {
+        self.open
+            .iter()
+            .filter(|penalty| now < penalty.expires_at)
+            .count() as u32
+    }
+
+    /// Combined effect of every penalty still active at `now`
+    pub fn effect(&self, now: i64) -> PenaltyEffect {
+        self.open
+            .iter()
+            .filter(|penalty| now < penalty.expires_at)
+            .fold(PenaltyEffect::default(), |effect, penalty| {
+                effect.combine(penalty.severity.effect())
+            })
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpenPenalty {
+    pub index: u32,
+    pub severity: PenaltySeverity,
+    pub expires_at: i64,
+}
+
+impl OpenPenalty {
+    pub const SPACE: usize = 4 + 1 + 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PenaltySeverity {
+    /// Subtracts a few points
+    Minor,
+    /// Subtracts more and keeps the score out of the top level
+    Major,
+    /// Subtracts the most and keeps the score in the bottom two levels
+    Severe,
+}
+
+impl PenaltySeverity {
+    pub fn effect(self) -> PenaltyEffect {
+        match self {
+            PenaltySeverity::Minor => PenaltyEffect {
+                points: 5,
+                level_cap: None,
+            },
+            PenaltySeverity::Major => PenaltyEffect {
+                points: 15,
+                level_cap: Some(ScoreLevel::VeryGood),
+            },
+            PenaltySeverity::Severe => PenaltyEffect {
+                points: 30,
+                level_cap: Some(ScoreLevel::Fair),
+            },
+        }
+    }
+}
@@ -1,3 +1,7 @@
+use crate::constants::{SCORE_DECAY_SECONDS, SCORE_HISTORY_LEN};
+use crate::errors::IdentityScoreError;
+use crate::scoring::{apply_penalty, apply_penalty_effect, PenaltyEffect, SCORE_MIN};
+use crate::state::{LevelCutoffs, ScoreModel};
 use anchor_lang::prelude::*;
 
 #[account]
@@ -6,21 +10,235 @@ pub struct CreditScoreAccount {
     pub score: u8,
     pub score_level: ScoreLevel,
     pub calculated_at: i64,
+    pub expires_at: i64,
+    pub model_version: u32,
+    pub breakdown: ScoreBreakdown,
 }
 
+impl CreditScoreAccount {
+    // discriminator + pubkey + u8 + enum(1) + i64 + i64 + u32 + breakdown
+    pub const SPACE: usize = 8 + 32 + 1 + 1 + 8 + 8 + 4 + ScoreBreakdown::SPACE;
+    /// Size of scores calculated before `expires_at`, when the account ended
+    /// at `calculated_at`
+    pub const LEGACY_SPACE: usize = 8 + 32 + 1 + 1 + 8;
+
+    /// False for a score account that was just created and never written
+    pub fn is_initialized(&self) -> bool {
+        self.identity != Pubkey::default()
+    }
+
+    /// Whether at least `interval_seconds` have passed since the score was
+    /// calculated, so it may be recalculated or deleted
+    pub fn cooldown_elapsed(&self, now: i64, interval_seconds: i64) -> bool {
+        now >= self.calculated_at.saturating_add(interval_seconds)
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now > self.expires_at
+    }
+
+    /// Score as it should be read at `now`: the stored score until it expires,
+    /// then decaying linearly to SCORE_MIN over SCORE_DECAY_SECONDS
+    pub fn effective_score(&self, now: i64) -> u8 {
+        self.decay(self.score, now)
+    }
+
+    pub fn effective_level(&self, now: i64, cutoffs: &LevelCutoffs) -> ScoreLevel {
+        cutoffs.level(self.effective_score(now))
+    }
+
+    /// Like `effective_score`, but re-applies the penalties from the base
+    /// score instead of trusting the ones baked in at calculation time, so
+    /// penalties added or lifted since then count immediately. `penalty_points`
+    /// are the identity's tombstone points and `penalties` the owner's
+    /// `PenaltyIndex::effect` at `now`.
+    pub fn penalized_score(
+        &self,
+        now: i64,
+        penalty_points: u8,
+        penalties: PenaltyEffect,
+        cutoffs: &LevelCutoffs,
+    ) -> u8 {
+        let base = apply_penalty(self.breakdown.base_score, penalty_points);
+        let (score, _) = apply_penalty_effect(base, penalties, cutoffs);
+        self.decay(score, now)
+    }
+
+    pub fn penalized_level(
+        &self,
+        now: i64,
+        penalty_points: u8,
+        penalties: PenaltyEffect,
+        cutoffs: &LevelCutoffs,
+    ) -> ScoreLevel {
+        cutoffs.level(self.penalized_score(now, penalty_points, penalties, cutoffs))
+    }
+
+    /// `score` until the account expires, then decaying linearly to SCORE_MIN
+    /// over SCORE_DECAY_SECONDS
+    fn decay(&self, score: u8, now: i64) -> u8 {
+        if !self.is_expired(now) || score <= SCORE_MIN {
+            return score;
+        }
+        let elapsed = (now - self.expires_at).min(SCORE_DECAY_SECONDS);
+        let excess = (score - SCORE_MIN) as i64;
+        score - (excess * elapsed / SCORE_DECAY_SECONDS) as u8
+    }
+
+    /// For consumers: fails if `model` (the model this score was produced by)
+    /// has been retired, or is older than `min_model_version`
+    pub fn check_model(&self, model: &ScoreModel, min_model_version: u32) -> Result<()> {
+        require_eq!(
+            model.version,
+            self.model_version,
+            IdentityScoreError::ScoreModelMismatch
+        );
+        require!(model.active, IdentityScoreError::ScoreModelRetired);
+        require_gte!(
+            self.model_version,
+            min_model_version,
+            IdentityScoreError::ScoreModelTooOld
+        );
+        Ok(())
+    }
+}
+
+/// Fixed-size ring buffer of the most recent score calculations
+#[account]
+pub struct ScoreHistory {
+    pub identity: Pubkey,
+    /// Slot the next entry is written to
+    pub head: u8,
+    /// Number of valid entries, at most SCORE_HISTORY_LEN
+    pub len: u8,
+    pub entries: [ScoreHistoryEntry; SCORE_HISTORY_LEN],
+}
+
+impl ScoreHistory {
+    pub const SPACE: usize = 8 + 32 + 1 + 1 + ScoreHistoryEntry::SPACE * SCORE_HISTORY_LEN;
+
+    pub fn push(&mut self, entry: ScoreHistoryEntry) {
+        self.entries[self.head as usize] = entry;
+        self.head = ((self.head as usize + 1) % SCORE_HISTORY_LEN) as u8;
+        self.len = (self.len + 1).min(SCORE_HISTORY_LEN as u8);
+    }
+
+    /// Valid entries from newest to oldest
+    pub fn iter_newest_first(&self) -> impl Iterator<Item = &ScoreHistoryEntry> {
+        (1..=self.len as usize).map(move |i| {
+            &self.entries[(self.head as usize + SCORE_HISTORY_LEN - i) % SCORE_HISTORY_LEN]
+        })
+    }
+
+    /// Recomputes the level of every valid entry from its score
+    pub fn reclassify(&mut self, cutoffs: &LevelCutoffs) {
+        for entry in self.entries.iter_mut().take(self.len as usize) {
+            entry.score_level = cutoffs.level(entry.score);
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ScoreHistoryEntry {
+    pub score: u8,
+    pub score_level: ScoreLevel,
+    pub calculated_at: i64,
+    pub model_version: u32,
+}
+
+impl ScoreHistoryEntry {
+    pub const SPACE: usize = 1 + 1 + 8 + 4;
+}
+
+/// Return data of `preview_score`
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ScorePreview {
+    pub score: u8,
+    pub score_level: ScoreLevel,
+    pub breakdown: ScoreBreakdown,
+    pub model_version: u32,
+}
+
+/// Return data of `batch_refresh_scores`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, Default)]
+pub struct BatchRefreshReport {
+    pub refreshed: u16,
+    pub skipped: Vec<SkippedRefresh>,
+}
+
+/// An owner the batch left alone, and why
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SkippedRefresh {
+    /// Position of the owner's entry in the batch
+    pub index: u16,
+    /// Custom program error code, or the full encoding of a builtin error
+    pub error_code: u64,
+}
+
+/// Why a score came out the way it did
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ScoreBreakdown {
+    pub asset: ScoreComponent,
+    pub stability: ScoreComponent,
+    /// Wallet balance over the rent-exempt minimum of the owner's identity,
+    /// score and score history, from 1x up to 10x. Only these accounts are
+    /// paid for by the owner in order to be scored; sidecars anyone can open
+    /// and holding accounts do not count, so the footprint never grows
+    /// behind the owner's back.
+    pub rent_efficiency: ScoreComponent,
+    pub verification: ScoreComponent,
+    /// Check-in streak and days active, lapsing after a month away
+    pub activity: ScoreComponent,
+    /// Zero weight when the owner has no resolved loans
+    pub repayment: ScoreComponent,
+    /// Weighted average of the components, before penalties
+    pub base_score: u8,
+    /// Points taken off by tombstone and reporter penalties, level caps included
+    pub penalty_deduction: u8,
+}
+
+impl ScoreBreakdown {
+    pub const SPACE: usize = ScoreComponent::SPACE * 6 + 1 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ScoreComponent {
+    /// Sub-score on the same 30-90 scale as the final score
+    pub score: u8,
+    /// Weight in basis points
+    pub weight_bps: u16,
+}
+
+impl ScoreComponent {
+    pub const SPACE: usize = 1 + 2;
+}
+
+/// Ordered from worst to best. Accounts written before the five tiers hold
+/// the old Low/Medium/High bytes, which decode as Poor/Fair/Good until
+/// `migrate_score_level` reclassifies them.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug,
+)]
 pub enum ScoreLevel {
-    Low,
-    Medium,
-    High,
+    Poor,
+    Fair,
+    Good,
+    VeryGood,
+    Excellent,
+}
+
+impl ScoreLevel {
+    pub const COUNT: usize = 5;
 }
 
 impl std::fmt::Display for ScoreLevel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ScoreLevel::Low => write!(f, "Low"),
-            ScoreLevel::Medium => write!(f, "Medium"),
-            ScoreLevel::High => write!(f, "High"),
+            ScoreLevel::Poor => write!(f, "Poor"),
+            ScoreLevel::Fair => write!(f, "Fair"),
+            ScoreLevel::Good => write!(f, "Good"),
+            ScoreLevel::VeryGood => write!(f, "Very Good"),
+            ScoreLevel::Excellent => write!(f, "Excellent"),
         }
     }
 }
@@ -0,0 +1,25 @@
+use crate::state::ScoreLevel;
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct ProtocolStats {
+    pub total_identities: u64,
+    pub verified_identities: u64,
+    pub scores_computed: u64,
+    pub active_transfer_requests: u64,
+    /// Number of live score accounts per level, indexed by `ScoreLevel as usize`
+    pub level_counts: [u64; ScoreLevel::COUNT],
+}
+
+impl ProtocolStats {
+    pub const SPACE: usize = 8 + 8 + 8 + 8 + 8 + 8 * ScoreLevel::COUNT;
+
+    pub fn add_level(&mut self, level: ScoreLevel) {
+        self.level_counts[level as usize] += 1;
+    }
+
+    pub fn remove_level(&mut self, level: ScoreLevel) {
+        let count = &mut self.level_counts[level as usize];
+        *count = count.saturating_sub(1);
+    }
+}
@@ -0,0 +1,13 @@
+use anchor_lang::prelude::*;
+
+/// Lamports an owner sets aside to pay keepers for refreshing their score
+#[account]
+pub struct TipVault {
+    pub owner: Pubkey,
+    /// Paid per refresh while the vault holds it above its rent-exempt minimum
+    pub tip_lamports: u64,
+}
+
+impl TipVault {
+    pub const SPACE: usize = 8 + 32 + 8;
+}
@@ -1,12 +1,15 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
-    use crate::constants::{SEED_IDENTITY, SEED_SCORE, SEED_TRANSFER_REQUEST};
-    use crate::state::{CreditScoreAccount, IdentityAccount, ScoreLevel};
+    use crate::constants::*;
+    use crate::errors::IdentityScoreError;
+    use crate::state::*;
     use crate::ID as PROGRAM_ID;
-    use anchor_lang::AccountDeserialize;
+    use anchor_lang::{AccountDeserialize, AccountSerialize, AnchorDeserialize, AnchorSerialize};
     use litesvm::LiteSVM;
     use solana_sdk::{
         account::Account,
+        clock::Clock,
         hash::hash,
         instruction::{AccountMeta, Instruction},
         pubkey::Pubkey,
@@ -16,6 +19,7 @@ mod tests {
     };
     use solana_system_interface::program;
 
+    /// 1 SOL 对应的 lamports 数量
     const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
 
     /// 获取指令的 discriminator
@@ -32,6 +36,31 @@ mod tests {
         sighash
     }
 
+    /// 获取还款记录账户的 PDA 地址
+    fn get_credit_history_pda(owner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SEED_CREDIT_HISTORY, owner.as_ref()], &PROGRAM_ID)
+    }
+
+    /// 获取处罚索引账户的 PDA 地址
+    fn get_penalty_index_pda(owner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SEED_PENALTY_INDEX, owner.as_ref()], &PROGRAM_ID)
+    }
+
+    /// 获取余额历史账户的 PDA 地址
+    fn get_balance_history_pda(owner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SEED_BALANCE_HISTORY, owner.as_ref()], &PROGRAM_ID)
+    }
+
+    /// 获取签到记录账户的 PDA 地址
+    fn get_activity_pda(owner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SEED_ACTIVITY, owner.as_ref()], &PROGRAM_ID)
+    }
+
+    /// 获取评分模型账户的 PDA 地址
+    fn get_score_model_pda(version: u32) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SEED_SCORE_MODEL, &version.to_le_bytes()], &PROGRAM_ID)
+    }
+
     /// 获取身份账户的 PDA 地址
     ///
     /// # 参数
@@ -43,6 +72,44 @@ mod tests {
         Pubkey::find_program_address(&[SEED_IDENTITY, owner.as_ref()], &PROGRAM_ID)
     }
 
+    /// 获取协议配置账户的 PDA 地址
+    ///
+    /// # 返回
+    /// - PDA 地址和 bump seed
+    fn get_config_pda() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SEED_CONFIG], &PROGRAM_ID)
+    }
+
+    /// 获取信用分历史账户的 PDA 地址
+    ///
+    /// # 参数
+    /// - `owner`: 身份所有者的公钥
+    ///
+    /// # 返回
+    /// - PDA 地址和 bump seed
+    fn get_score_history_pda(owner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SEED_SCORE_HISTORY, owner.as_ref()], &PROGRAM_ID)
+    }
+
+    /// 获取墓碑账户的 PDA 地址
+    ///
+    /// # 参数
+    /// - `owner`: 身份所有者的公钥
+    ///
+    /// # 返回
+    /// - PDA 地址和 bump seed
+    fn get_tombstone_pda(owner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SEED_TOMBSTONE, owner.as_ref()], &PROGRAM_ID)
+    }
+
+    /// 获取全局统计账户的 PDA 地址
+    ///
+    /// # 返回
+    /// - PDA 地址和 bump seed
+    fn get_stats_pda() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SEED_STATS], &PROGRAM_ID)
+    }
+
     /// 获取信用分账户的 PDA 地址
     ///
     /// # 参数
@@ -73,6 +140,20 @@ mod tests {
         )
     }
 
+    /// 获取推荐统计账户的 PDA 地址
+    ///
+    /// # 参数
+    /// - `referrer_identity`: 推荐人的身份账户
+    ///
+    /// # 返回
+    /// - PDA 地址和 bump seed
+    fn get_referral_stats_pda(referrer_identity: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[SEED_REFERRAL_STATS, referrer_identity.as_ref()],
+            &PROGRAM_ID,
+        )
+    }
+
     /// 构建创建身份指令
     ///
     /// # 参数
@@ -92,13 +173,50 @@ mod tests {
             program_id: PROGRAM_ID,
             accounts: vec![
                 AccountMeta::new(*identity, false), // new(pubkey, is_signer) creates writable account
+                AccountMeta::new_readonly(get_tombstone_pda(owner).0, false),
                 AccountMeta::new(*owner, true),
                 AccountMeta::new_readonly(Pubkey::from(program::id().to_bytes()), false),
+                AccountMeta::new(get_stats_pda().0, false),
+                // 可选账户传入程序 ID 表示 None
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
             ],
             data,
         }
     }
 
+    /// 构建带推荐人的创建身份指令
+    ///
+    /// # 参数
+    /// - `owner`: 身份所有者
+    /// - `identity`: 身份账户 PDA
+    /// - `referrer_identity`: 推荐人的身份账户
+    ///
+    /// # 返回
+    /// - 创建身份的指令
+    fn create_identity_with_referrer_ix(
+        owner: &Pubkey,
+        identity: &Pubkey,
+        referrer_identity: &Pubkey,
+    ) -> Instruction {
+        let discriminator = get_discriminator("create_identity");
+        let (referral_stats, _) = get_referral_stats_pda(referrer_identity);
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*identity, false),
+                AccountMeta::new_readonly(get_tombstone_pda(owner).0, false),
+                AccountMeta::new(*owner, true),
+                AccountMeta::new_readonly(Pubkey::from(program::id().to_bytes()), false),
+                AccountMeta::new(get_stats_pda().0, false),
+                AccountMeta::new_readonly(*referrer_identity, false),
+                AccountMeta::new(referral_stats, false),
+            ],
+            data: discriminator.to_vec(),
+        }
+    }
+
     /// 构建验证身份指令
     ///
     /// # 参数
@@ -119,11 +237,42 @@ mod tests {
             accounts: vec![
                 AccountMeta::new(*identity, false), // new(pubkey, is_signer) creates writable account
                 AccountMeta::new_readonly(*owner, true),
+                AccountMeta::new(get_stats_pda().0, false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
             ],
             data,
         }
     }
 
+    /// 构建带推荐统计账户的验证身份指令
+    ///
+    /// # 参数
+    /// - `owner`: 身份所有者
+    /// - `identity`: 身份账户 PDA
+    /// - `referrer_identity`: 推荐人的身份账户
+    ///
+    /// # 返回
+    /// - 验证身份的指令
+    fn verify_identity_with_referral_ix(
+        owner: &Pubkey,
+        identity: &Pubkey,
+        referrer_identity: &Pubkey,
+    ) -> Instruction {
+        let discriminator = get_discriminator("verify_identity");
+        let (referral_stats, _) = get_referral_stats_pda(referrer_identity);
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*identity, false),
+                AccountMeta::new_readonly(*owner, true),
+                AccountMeta::new(get_stats_pda().0, false),
+                AccountMeta::new(referral_stats, false),
+            ],
+            data: discriminator.to_vec(),
+        }
+    }
+
     /// 构建计算信用分指令
     ///
     /// # 参数
@@ -148,14 +297,62 @@ mod tests {
             program_id: PROGRAM_ID,
             accounts: vec![
                 AccountMeta::new(*score_account, false),
+                AccountMeta::new(get_score_history_pda(owner).0, false),
                 AccountMeta::new(*identity, false),
                 AccountMeta::new(*owner, true),
                 AccountMeta::new_readonly(Pubkey::from(program::id().to_bytes()), false),
+                AccountMeta::new(get_stats_pda().0, false),
+                AccountMeta::new_readonly(get_config_pda().0, false),
+                AccountMeta::new_readonly(get_credit_history_pda(owner).0, false),
+                AccountMeta::new_readonly(get_penalty_index_pda(owner).0, false),
+                AccountMeta::new_readonly(get_balance_history_pda(owner).0, false),
+                AccountMeta::new_readonly(get_activity_pda(owner).0, false),
+                AccountMeta::new_readonly(get_holding_registry_pda(owner).0, false),
+                AccountMeta::new_readonly(get_tip_vault_pda(owner).0, false),
+                AccountMeta::new_readonly(get_score_model_pda(1).0, false),
+                AccountMeta::new_readonly(solana_instructions_sysvar::ID, false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
             ],
             data,
         }
     }
 
+    /// 构建管理员强制计算信用分指令
+    ///
+    /// # 参数
+    /// - `admin`: 协议管理员
+    /// - `owner`: 身份所有者
+    /// - `identity`: 身份账户 PDA
+    ///
+    /// # 返回
+    /// - 强制计算信用分的指令
+    fn force_calculate_score_ix(admin: &Pubkey, owner: &Pubkey, identity: &Pubkey) -> Instruction {
+        let discriminator = get_discriminator("force_calculate_score");
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(get_score_pda(owner).0, false),
+                AccountMeta::new(get_score_history_pda(owner).0, false),
+                AccountMeta::new_readonly(*identity, false),
+                AccountMeta::new_readonly(*owner, false),
+                AccountMeta::new(*admin, true),
+                AccountMeta::new_readonly(Pubkey::from(program::id().to_bytes()), false),
+                AccountMeta::new(get_stats_pda().0, false),
+                AccountMeta::new_readonly(get_config_pda().0, false),
+                AccountMeta::new_readonly(get_credit_history_pda(owner).0, false),
+                AccountMeta::new_readonly(get_penalty_index_pda(owner).0, false),
+                AccountMeta::new_readonly(get_balance_history_pda(owner).0, false),
+                AccountMeta::new_readonly(get_activity_pda(owner).0, false),
+                AccountMeta::new_readonly(get_holding_registry_pda(owner).0, false),
+                AccountMeta::new_readonly(get_tip_vault_pda(owner).0, false),
+                AccountMeta::new_readonly(get_score_model_pda(1).0, false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+            ],
+            data: discriminator.to_vec(),
+        }
+    }
+
     /// 构建取消验证身份指令
     ///
     /// # 参数
@@ -176,6 +373,7 @@ mod tests {
             accounts: vec![
                 AccountMeta::new(*identity, false), // new(pubkey, is_signer) creates writable account
                 AccountMeta::new_readonly(*owner, true),
+                AccountMeta::new(get_stats_pda().0, false),
             ],
             data,
         }
@@ -208,13 +406,63 @@ mod tests {
             accounts: vec![
                 AccountMeta::new(*identity, false),
                 AccountMeta::new(*score_account, false),
+                AccountMeta::new(get_score_history_pda(owner).0, false),
+                AccountMeta::new(get_tombstone_pda(owner).0, false),
                 AccountMeta::new(*owner, true),
                 AccountMeta::new_readonly(Pubkey::from(program::id().to_bytes()), false),
+                AccountMeta::new_readonly(get_config_pda().0, false),
+                AccountMeta::new(get_stats_pda().0, false),
+                AccountMeta::new_readonly(get_penalty_index_pda(owner).0, false),
+                AccountMeta::new_readonly(get_credit_history_pda(owner).0, false),
             ],
             data,
         }
     }
 
+    /// 构建停用身份指令
+    ///
+    /// # 参数
+    /// - `owner`: 身份所有者
+    /// - `identity`: 身份账户 PDA
+    ///
+    /// # 返回
+    /// - 停用身份的指令
+    fn deactivate_identity_ix(owner: &Pubkey, identity: &Pubkey) -> Instruction {
+        let discriminator = get_discriminator("deactivate_identity");
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*identity, false),
+                AccountMeta::new_readonly(*owner, true),
+                AccountMeta::new_readonly(get_config_pda().0, false),
+            ],
+            data: discriminator.to_vec(),
+        }
+    }
+
+    /// 构建重新激活身份指令
+    ///
+    /// # 参数
+    /// - `owner`: 身份所有者
+    /// - `identity`: 身份账户 PDA
+    ///
+    /// # 返回
+    /// - 重新激活身份的指令
+    fn reactivate_identity_ix(owner: &Pubkey, identity: &Pubkey) -> Instruction {
+        let discriminator = get_discriminator("reactivate_identity");
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*identity, false),
+                AccountMeta::new_readonly(*owner, true),
+                AccountMeta::new_readonly(get_config_pda().0, false),
+            ],
+            data: discriminator.to_vec(),
+        }
+    }
+
     /// 构建删除信用分指令
     ///
     /// # 参数
@@ -235,9 +483,12 @@ mod tests {
             program_id: PROGRAM_ID,
             accounts: vec![
                 AccountMeta::new(*score_account, false),
+                AccountMeta::new(get_score_history_pda(owner).0, false),
                 AccountMeta::new(*identity, false),
                 AccountMeta::new(*owner, true),
                 AccountMeta::new_readonly(Pubkey::from(program::id().to_bytes()), false),
+                AccountMeta::new(get_stats_pda().0, false),
+                AccountMeta::new_readonly(get_config_pda().0, false),
             ],
             data,
         }
@@ -269,6 +520,7 @@ mod tests {
                 AccountMeta::new(*owner, true),
                 AccountMeta::new_readonly(*recipient, false),
                 AccountMeta::new_readonly(Pubkey::from(program::id().to_bytes()), false),
+                AccountMeta::new(get_stats_pda().0, false),
             ],
             data: discriminator.to_vec(),
         }
@@ -306,9 +558,16 @@ mod tests {
                 AccountMeta::new(*transfer_request, false),
                 AccountMeta::new(*old_score, false),
                 AccountMeta::new(*new_score, false),
+                AccountMeta::new(get_score_history_pda(old_owner).0, false),
+                AccountMeta::new(get_score_history_pda(new_owner).0, false),
                 AccountMeta::new(*old_owner, true),
                 AccountMeta::new(*new_owner, true),
                 AccountMeta::new_readonly(Pubkey::from(program::id().to_bytes()), false),
+                AccountMeta::new(get_stats_pda().0, false),
+                AccountMeta::new(get_credit_history_pda(old_owner).0, false),
+                AccountMeta::new(get_credit_history_pda(new_owner).0, false),
+                AccountMeta::new_readonly(get_penalty_index_pda(old_owner).0, false),
+                AccountMeta::new(get_tombstone_pda(old_owner).0, false),
             ],
             data: discriminator.to_vec(),
         }
@@ -335,24 +594,238 @@ mod tests {
             accounts: vec![
                 AccountMeta::new(*transfer_request, false),
                 AccountMeta::new(*owner, true),
+                AccountMeta::new(get_stats_pda().0, false),
             ],
             data: discriminator.to_vec(),
         }
     }
 
-    /// Helper function to initialize test environment with loaded program
+    /// 构建关闭过期转移请求指令
+    fn close_expired_transfer_ix(from_owner: &Pubkey, transfer_request: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*transfer_request, false),
+                AccountMeta::new(*from_owner, false),
+                AccountMeta::new(get_stats_pda().0, false),
+            ],
+            data: get_discriminator("close_expired_transfer").to_vec(),
+        }
+    }
+
+    /// 构建配置指令（初始化或更新）
+    ///
+    /// # 参数
+    /// - `name`: 指令名称，`initialize_config` 或 `update_config`
+    /// - `admin`: 管理员
+    /// - `params`: 协议参数
+    ///
+    /// # 返回
+    /// - 配置指令
+    fn config_ix(name: &str, admin: &Pubkey, params: &ConfigParams) -> Instruction {
+        let mut data = get_discriminator(name).to_vec();
+        params.serialize(&mut data).unwrap();
+
+        let mut accounts = vec![
+            AccountMeta::new(get_config_pda().0, false),
+            AccountMeta::new(*admin, true),
+        ];
+        if name == "initialize_config" {
+            accounts.insert(1, AccountMeta::new(get_stats_pda().0, false));
+            accounts.insert(2, AccountMeta::new(get_score_model_pda(1).0, false));
+            accounts.extend([
+                AccountMeta::new_readonly(Pubkey::from(program::id().to_bytes()), false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+                AccountMeta::new_readonly(get_program_data_address(), false),
+            ]);
+        }
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data,
+        }
+    }
+
+    /// 测试默认使用的协议参数
+    fn test_config_params() -> ConfigParams {
+        ConfigParams {
+            deletion_cooldown_seconds: 60 * 60,
+            score_validity_seconds: 30 * 24 * 60 * 60,
+            min_score_interval_seconds: 0,
+            price_oracle: Pubkey::default(),
+            max_price_age_seconds: 60,
+            max_price_confidence_bps: 100,
+            level_cutoffs: LevelCutoffs::default(),
+            balance_window_seconds: 0,
+            min_balance_checkpoints: 0,
+        }
+    }
+
+    /// 停用身份并将时钟推进到删除冷却期结束
+    fn deactivate_and_wait_cooldown(svm: &mut LiteSVM, owner: &Keypair) {
+        let identity_pda = get_identity_pda(&owner.pubkey()).0;
+        let deactivate_ix = deactivate_identity_ix(&owner.pubkey(), &identity_pda);
+        send_ix(svm, deactivate_ix, owner).unwrap();
+
+        let account = svm.get_account(&get_config_pda().0).unwrap();
+        let config = ProtocolConfig::try_deserialize(&mut &account.data[..]).unwrap();
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += config.deletion_cooldown_seconds;
+        svm.set_sysvar::<Clock>(&clock);
+    }
+
+    /// 发送单条指令交易，失败时返回错误信息
+    fn send_ix(svm: &mut LiteSVM, ix: Instruction, payer: &Keypair) -> Result<(), String> {
+        let blockhash = svm.latest_blockhash();
+        let tx =
+            Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], blockhash);
+        svm.send_transaction(tx)
+            .map(|_| ())
+            .map_err(|e| format!("{:?}", e.err))
+    }
+
+    /// 在一笔交易中创建并验证所有者的身份
+    fn create_verified_identity(svm: &mut LiteSVM, owner: &Keypair) {
+        let (identity_pda, _) = get_identity_pda(&owner.pubkey());
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                create_identity_ix(&owner.pubkey(), &identity_pda),
+                verify_identity_ix(&owner.pubkey(), &identity_pda),
+            ],
+            Some(&owner.pubkey()),
+            &[owner],
+            blockhash,
+        );
+        svm.send_transaction(tx).unwrap();
+    }
+
+    /// 在一笔交易中创建、验证所有者的身份并计算信用分
+    fn create_scored_identity(svm: &mut LiteSVM, owner: &Keypair) {
+        let (identity_pda, _) = get_identity_pda(&owner.pubkey());
+        let (score_pda, _) = get_score_pda(&owner.pubkey());
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                create_identity_ix(&owner.pubkey(), &identity_pda),
+                verify_identity_ix(&owner.pubkey(), &identity_pda),
+                calculate_score_ix(&owner.pubkey(), &identity_pda, &score_pda),
+            ],
+            Some(&owner.pubkey()),
+            &[owner],
+            blockhash,
+        );
+        svm.send_transaction(tx).unwrap();
+    }
+
+    /// 断言交易因指定的程序错误而失败
+    fn assert_program_error(result: Result<(), String>, error: IdentityScoreError) {
+        let code: u32 = error.into();
+        let err = result.expect_err("transaction should fail");
+        assert!(
+            err.contains(&format!("Custom({})", code)),
+            "expected custom error {} but got {}",
+            code,
+            err
+        );
+    }
+
+    /// 获取本程序在可升级加载器下的 ProgramData 地址
+    fn get_program_data_address() -> Pubkey {
+        program_data_address(&PROGRAM_ID)
+    }
+
+    /// 获取任一程序在可升级加载器下的 ProgramData 地址
+    fn program_data_address(program_id: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[program_id.as_ref()],
+            &Pubkey::from(anchor_lang::solana_program::bpf_loader_upgradeable::id().to_bytes()),
+        )
+        .0
+    }
+
+    /// 以可升级加载器部署本程序，`upgrade_authority` 为升级权限
+    fn deploy_upgradeable_program(svm: &mut LiteSVM, upgrade_authority: &Pubkey) {
+        deploy_program(
+            svm,
+            &PROGRAM_ID,
+            include_bytes!("../../../target/deploy/identity_score.so"),
+            upgrade_authority,
+        );
+    }
+
+    /// 以可升级加载器部署 `program_bytes` 到 `program_id`
+    ///
+    /// 按 `UpgradeableLoaderState` 的 bincode 布局直接写入 Program 和
+    /// ProgramData 账户，ProgramData 需先于 Program 写入
+    fn deploy_program(
+        svm: &mut LiteSVM,
+        program_id: &Pubkey,
+        program_bytes: &[u8],
+        upgrade_authority: &Pubkey,
+    ) {
+        let loader =
+            Pubkey::from(anchor_lang::solana_program::bpf_loader_upgradeable::id().to_bytes());
+        let program_data_address = program_data_address(program_id);
+
+        let mut program_data = 3u32.to_le_bytes().to_vec();
+        program_data.extend_from_slice(&0u64.to_le_bytes());
+        program_data.push(1);
+        program_data.extend_from_slice(upgrade_authority.as_ref());
+        program_data.extend_from_slice(program_bytes);
+        let lamports = svm.minimum_balance_for_rent_exemption(program_data.len());
+        svm.set_account(
+            program_data_address,
+            Account {
+                lamports,
+                data: program_data,
+                owner: loader,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+
+        let mut program_account = 2u32.to_le_bytes().to_vec();
+        program_account.extend_from_slice(program_data_address.as_ref());
+        let lamports = svm.minimum_balance_for_rent_exemption(program_account.len());
+        svm.set_account(
+            *program_id,
+            Account {
+                lamports,
+                data: program_account,
+                owner: loader,
+                executable: true,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+    }
+
+    /// 初始化已加载程序的测试环境
     fn setup_test_environment() -> LiteSVM {
+        setup_test_environment_with_admin().0
+    }
+
+    /// 初始化测试环境并返回协议管理员
+    fn setup_test_environment_with_admin() -> (LiteSVM, Keypair) {
         let mut svm = LiteSVM::new();
-        let program_bytes = include_bytes!("../../../target/deploy/identity_score.so");
-        match svm.add_program(PROGRAM_ID, program_bytes) {
-            Ok(_) => svm,
-            Err(e) => {
-                println!("Error adding program: {:?}", e);
-                panic!("Failed to add program to LiteSVM");
-            }
-        }
+        let admin = Keypair::new();
+        deploy_upgradeable_program(&mut svm, &admin.pubkey());
+
+        svm.airdrop(&admin.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+        let ix = config_ix("initialize_config", &admin.pubkey(), &test_config_params());
+        send_ix(&mut svm, ix, &admin).unwrap();
+
+        (svm, admin)
     }
 
+    /// 测试创建身份账户
+    ///
+    /// # 验证点
+    /// - 身份账户归属调用者
+    /// - 新建身份默认未验证
     #[test]
     fn test_create_identity() {
         let mut svm = setup_test_environment();
@@ -374,10 +847,14 @@ mod tests {
         let identity_state = IdentityAccount::try_deserialize(&mut data_slice).unwrap();
 
         assert_eq!(identity_state.owner, user.pubkey());
-        assert_eq!(identity_state.verified, false);
+        assert!(!identity_state.verified);
         assert_eq!(identity_state.verified_at, None);
     }
 
+    /// 测试所有者验证自己的身份
+    ///
+    /// # 验证点
+    /// - verified 置为 true 并记录验证时间
     #[test]
     fn test_verify_identity() {
         let mut svm = setup_test_environment();
@@ -387,36 +864,26 @@ mod tests {
 
         let (identity_pda, _) = get_identity_pda(&user.pubkey());
 
-        // Create Identity
+        // 创建身份
         let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, create_ix, &user).unwrap();
 
-        // Verify Identity
+        // 验证身份
         let verify_ix = verify_identity_ix(&user.pubkey(), &identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[verify_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, verify_ix, &user).unwrap();
 
         let account = svm.get_account(&identity_pda).unwrap();
         let mut data_slice = &account.data[..];
         let identity_state = IdentityAccount::try_deserialize(&mut data_slice).unwrap();
 
-        assert_eq!(identity_state.verified, true);
+        assert!(identity_state.verified);
         assert!(identity_state.verified_at.is_some());
     }
 
+    /// 测试非所有者无法验证他人身份
+    ///
+    /// # 验证点
+    /// - 攻击者签名的验证交易失败
     #[test]
     fn test_verify_identity_unauthorized() {
         let mut svm = setup_test_environment();
@@ -429,20 +896,13 @@ mod tests {
 
         let (identity_pda, _) = get_identity_pda(&user.pubkey());
 
-        // Create Identity (User)
+        // 用户创建身份
         let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, create_ix, &user).unwrap();
 
-        // Verify Identity (Hacker attempts to verify User's identity)
-        // Note: verify_identity instruction expects 'owner' as signer.
-        // If we pass hacker as owner, it checks identity.owner == hacker, which fails because identity.owner is user.
+        // 攻击者尝试验证用户的身份
+        // 注意：verify_identity 要求 owner 签名
+        // 以攻击者作为 owner 传入时，identity.owner == hacker 的检查会失败
         let verify_ix = verify_identity_ix(&hacker.pubkey(), &identity_pda);
         let blockhash = svm.latest_blockhash();
         let tx = Transaction::new_signed_with_payer(
@@ -456,49 +916,36 @@ mod tests {
         assert!(result.is_err());
     }
 
+    /// 测试高余额用户的评分
+    ///
+    /// # 测试场景
+    /// 1. 用户持有 15 SOL 并验证身份
+    /// 2. 计算评分
+    ///
+    /// # 验证点
+    /// - 评分为 72，等级为 Excellent
     #[test]
     fn test_calculate_score_high() {
         let mut svm = setup_test_environment();
 
         let user = Keypair::new();
-        // Give user 15 SOL (High Score >= 10 SOL)
+        // 给用户 15 SOL（高分需 >= 10 SOL）
         svm.airdrop(&user.pubkey(), 15 * LAMPORTS_PER_SOL).unwrap();
 
         let (identity_pda, _) = get_identity_pda(&user.pubkey());
         let (score_pda, _) = get_score_pda(&user.pubkey());
 
-        // Create Identity first (required for score calculation)
+        // 先创建身份（计算评分的前提）
         let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, create_ix, &user).unwrap();
 
-        // Verify Identity first
+        // 先验证身份
         let verify_ix = verify_identity_ix(&user.pubkey(), &identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[verify_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, verify_ix, &user).unwrap();
 
-        // Calculate Score
+        // 计算评分
         let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[calc_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, calc_ix, &user).unwrap();
 
         let account = svm.get_account(&score_pda).unwrap();
         let mut data_slice = &account.data[..];
@@ -506,9 +953,14 @@ mod tests {
 
         assert_eq!(score_state.identity, identity_pda);
         assert_eq!(score_state.score, 72);
-        assert_eq!(score_state.score_level, ScoreLevel::High);
+        assert_eq!(score_state.score_level, ScoreLevel::Excellent);
     }
 
+    /// 测试取消身份验证
+    ///
+    /// # 验证点
+    /// - 验证后 verified 为 true
+    /// - 取消验证后 verified 为 false 且验证时间被清空
     #[test]
     fn test_unverify_identity() {
         let mut svm = setup_test_environment();
@@ -518,55 +970,38 @@ mod tests {
 
         let (identity_pda, _) = get_identity_pda(&user.pubkey());
 
-        // Create identity first
+        // 先创建身份
         let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, create_ix, &user).unwrap();
 
-        // Verify identity
+        // 验证身份
         let verify_ix = verify_identity_ix(&user.pubkey(), &identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[verify_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, verify_ix, &user).unwrap();
 
-        // Get the identity account
+        // 读取身份账户
         let account = svm.get_account(&identity_pda).unwrap();
         let mut data_slice = &account.data[..];
         let identity_state = IdentityAccount::try_deserialize(&mut data_slice).unwrap();
 
-        assert_eq!(identity_state.verified, true);
+        assert!(identity_state.verified);
 
-        // Unverify identity
+        // 取消验证身份
         let unverify_ix = unverify_identity_ix(&user.pubkey(), &identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[unverify_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, unverify_ix, &user).unwrap();
 
-        // Get the identity account again
+        // 再次读取身份账户
         let account = svm.get_account(&identity_pda).unwrap();
         let mut data_slice = &account.data[..];
         let identity_state = IdentityAccount::try_deserialize(&mut data_slice).unwrap();
 
-        assert_eq!(identity_state.verified, false);
+        assert!(!identity_state.verified);
         assert_eq!(identity_state.verified_at, None);
     }
 
+    /// 测试验证后再取消验证的基本流程
+    ///
+    /// # 验证点
+    /// - 身份回到未验证状态
     #[test]
     fn test_unverify_identity_basic() {
         let mut svm = setup_test_environment();
@@ -576,48 +1011,31 @@ mod tests {
 
         let (identity_pda, _) = get_identity_pda(&user.pubkey());
 
-        // Create Identity
+        // 创建身份
         let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, create_ix, &user).unwrap();
 
-        // Verify Identity
+        // 验证身份
         let verify_ix = verify_identity_ix(&user.pubkey(), &identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[verify_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, verify_ix, &user).unwrap();
 
-        // Unverify Identity
+        // 取消验证身份
         let unverify_ix = unverify_identity_ix(&user.pubkey(), &identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[unverify_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, unverify_ix, &user).unwrap();
 
-        // Check if identity is unverified
+        // 检查身份已取消验证
         let account = svm.get_account(&identity_pda).unwrap();
         let mut data_slice = &account.data[..];
         let identity_state = IdentityAccount::try_deserialize(&mut data_slice).unwrap();
 
-        assert_eq!(identity_state.verified, false);
+        assert!(!identity_state.verified);
         assert_eq!(identity_state.verified_at, None);
     }
 
+    /// 测试 5 SOL 余额用户的评分
+    ///
+    /// # 验证点
+    /// - 评分为 71，等级为 Excellent
     #[test]
     fn test_calculate_score_unverified() {
         let mut svm = setup_test_environment();
@@ -628,28 +1046,11 @@ mod tests {
         let (identity_pda, _) = get_identity_pda(&user.pubkey());
         let (score_pda, _) = get_score_pda(&user.pubkey());
 
-        // Create and Verify Identity
-        let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
-        let verify_ix = verify_identity_ix(&user.pubkey(), &identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix, verify_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        create_verified_identity(&mut svm, &user);
 
-        // Calculate Score
+        // 计算评分
         let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[calc_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, calc_ix, &user).unwrap();
 
         let account = svm.get_account(&score_pda).unwrap();
         let mut data_slice = &account.data[..];
@@ -657,9 +1058,13 @@ mod tests {
 
         assert_eq!(score_state.identity, identity_pda);
         assert_eq!(score_state.score, 71);
-        assert_eq!(score_state.score_level, ScoreLevel::High);
+        assert_eq!(score_state.score_level, ScoreLevel::Excellent);
     }
 
+    /// 测试 0.5 SOL 余额用户的评分
+    ///
+    /// # 验证点
+    /// - 评分为 68，等级为 VeryGood
     #[test]
     fn test_calculate_score_unverified_fails() {
         let mut svm = setup_test_environment();
@@ -670,37 +1075,25 @@ mod tests {
         let (identity_pda, _) = get_identity_pda(&user.pubkey());
         let (score_pda, _) = get_score_pda(&user.pubkey());
 
-        // Create and Verify Identity
-        let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
-        let verify_ix = verify_identity_ix(&user.pubkey(), &identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix, verify_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        create_verified_identity(&mut svm, &user);
 
-        // Calculate Score
+        // 计算评分
         let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[calc_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, calc_ix, &user).unwrap();
 
         let account = svm.get_account(&score_pda).unwrap();
         let mut data_slice = &account.data[..];
         let score_state = CreditScoreAccount::try_deserialize(&mut data_slice).unwrap();
 
         assert_eq!(score_state.identity, identity_pda);
-        assert_eq!(score_state.score_level, ScoreLevel::Medium);
+        assert_eq!(score_state.score, 68);
+        assert_eq!(score_state.score_level, ScoreLevel::VeryGood);
     }
 
+    /// 测试未验证身份无法计算评分
+    ///
+    /// # 验证点
+    /// - 评分交易失败
     #[test]
     fn test_calculate_score_various_levels() {
         let mut svm = setup_test_environment();
@@ -711,18 +1104,11 @@ mod tests {
         let (identity_pda, _) = get_identity_pda(&user.pubkey());
         let (score_pda, _) = get_score_pda(&user.pubkey());
 
-        // Create Identity but don't verify it
+        // 创建身份但不验证
         let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, create_ix, &user).unwrap();
 
-        // Try to calculate score for unverified identity (should fail)
+        // 为未验证的身份计算评分（应失败）
         let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
         let blockhash = svm.latest_blockhash();
         let tx = Transaction::new_signed_with_payer(
@@ -736,6 +1122,10 @@ mod tests {
         assert!(result.is_err());
     }
 
+    /// 测试取消验证后无法计算评分
+    ///
+    /// # 验证点
+    /// - 同一交易内创建、验证、取消验证后，评分交易失败
     #[test]
     fn test_calculate_score_after_unverify() {
         let mut svm = setup_test_environment();
@@ -746,7 +1136,7 @@ mod tests {
         let (identity_pda, _) = get_identity_pda(&user.pubkey());
         let (score_pda, _) = get_score_pda(&user.pubkey());
 
-        // Create, Verify, then Unverify Identity
+        // 依次创建、验证、取消验证身份
         let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
         let verify_ix = verify_identity_ix(&user.pubkey(), &identity_pda);
         let unverify_ix = unverify_identity_ix(&user.pubkey(), &identity_pda);
@@ -759,7 +1149,7 @@ mod tests {
         );
         svm.send_transaction(tx).unwrap();
 
-        // Try to calculate score after unverify (should fail)
+        // 取消验证后计算评分（应失败）
         let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
         let blockhash = svm.latest_blockhash();
         let tx = Transaction::new_signed_with_payer(
@@ -773,6 +1163,11 @@ mod tests {
         assert!(result.is_err());
     }
 
+    /// 测试删除评分账户
+    ///
+    /// # 验证点
+    /// - 评分账户被关闭
+    /// - 身份账户保留
     #[test]
     fn test_delete_score() {
         let mut svm = setup_test_environment();
@@ -783,49 +1178,29 @@ mod tests {
         let (identity_pda, _) = get_identity_pda(&user.pubkey());
         let (score_pda, _) = get_score_pda(&user.pubkey());
 
-        // Create and Verify Identity
-        let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
-        let verify_ix = verify_identity_ix(&user.pubkey(), &identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix, verify_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        create_verified_identity(&mut svm, &user);
 
-        // Calculate Score
+        // 计算评分
         let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[calc_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, calc_ix, &user).unwrap();
 
-        // Verify score account exists
+        // 确认评分账户存在
         assert!(svm.get_account(&score_pda).is_some());
 
-        // Delete Score
+        // 删除评分
         let delete_score_ix = delete_score_ix(&user.pubkey(), &identity_pda, &score_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[delete_score_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, delete_score_ix, &user).unwrap();
 
-        // Verify score account is deleted
+        // 确认评分账户已删除
         assert!(svm.get_account(&score_pda).is_none());
-        // Verify identity account still exists
+        // 确认身份账户仍然存在
         assert!(svm.get_account(&identity_pda).is_some());
     }
 
+    /// 测试删除身份时一并关闭评分账户
+    ///
+    /// # 验证点
+    /// - 停用并等待冷却期后，身份与评分账户都被关闭
     #[test]
     fn test_delete_identity_with_score() {
         let mut svm = setup_test_environment();
@@ -836,49 +1211,31 @@ mod tests {
         let (identity_pda, _) = get_identity_pda(&user.pubkey());
         let (score_pda, _) = get_score_pda(&user.pubkey());
 
-        // Create and Verify Identity
-        let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
-        let verify_ix = verify_identity_ix(&user.pubkey(), &identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix, verify_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        create_verified_identity(&mut svm, &user);
 
-        // Calculate Score
+        // 计算评分
         let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[calc_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, calc_ix, &user).unwrap();
 
-        // Verify both accounts exist
+        // 确认两个账户都存在
         assert!(svm.get_account(&identity_pda).is_some());
         assert!(svm.get_account(&score_pda).is_some());
 
-        // Delete Identity (should also delete score)
+        // 删除身份（应同时删除评分）
+        deactivate_and_wait_cooldown(&mut svm, &user);
         let delete_ix = delete_identity_ix(&user.pubkey(), &identity_pda, &score_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[delete_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, delete_ix, &user).unwrap();
 
-        // Verify both accounts are deleted
+        // 确认两个账户都已删除
         assert!(svm.get_account(&identity_pda).is_none());
         assert!(svm.get_account(&score_pda).is_none());
     }
 
+    /// 测试非所有者无法删除他人身份
+    ///
+    /// # 验证点
+    /// - 攻击者的删除交易失败
+    /// - 身份与评分账户仍然存在
     #[test]
     fn test_delete_identity_unauthorized() {
         let mut svm = setup_test_environment();
@@ -892,30 +1249,16 @@ mod tests {
         let (identity_pda, _) = get_identity_pda(&user.pubkey());
         let (score_pda, _) = get_score_pda(&user.pubkey());
 
-        // Create and Verify Identity
-        let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
-        let verify_ix = verify_identity_ix(&user.pubkey(), &identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix, verify_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        create_verified_identity(&mut svm, &user);
 
-        // Calculate Score
+        // 计算评分
         let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[calc_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, calc_ix, &user).unwrap();
+
+        // 用户已停用，只剩所有权检查能阻止删除
+        deactivate_and_wait_cooldown(&mut svm, &user);
 
-        // Hacker tries to delete user's identity (should fail)
+        // 攻击者尝试删除用户身份（应失败）
         let delete_ix = delete_identity_ix(&hacker.pubkey(), &identity_pda, &score_pda);
         let blockhash = svm.latest_blockhash();
         let tx = Transaction::new_signed_with_payer(
@@ -927,11 +1270,15 @@ mod tests {
         let result = svm.send_transaction(tx);
 
         assert!(result.is_err());
-        // Verify both accounts still exist
+        // 确认两个账户仍然存在
         assert!(svm.get_account(&identity_pda).is_some());
         assert!(svm.get_account(&score_pda).is_some());
     }
 
+    /// 测试没有评分账户时也能删除身份
+    ///
+    /// # 验证点
+    /// - 身份账户被关闭
     #[test]
     fn test_delete_identity_without_score() {
         let mut svm = setup_test_environment();
@@ -942,33 +1289,20 @@ mod tests {
         let (identity_pda, _) = get_identity_pda(&user.pubkey());
         let (score_pda, _) = get_score_pda(&user.pubkey());
 
-        // Only create identity, don't create score
+        // 只创建身份，不创建评分
         let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, create_ix, &user).unwrap();
 
-        // Verify identity exists but score doesn't
+        // 确认身份存在而评分不存在
         assert!(svm.get_account(&identity_pda).is_some());
         assert!(svm.get_account(&score_pda).is_none());
 
-        // Delete identity (should succeed even without score)
+        // 删除身份（没有评分也应成功）
+        deactivate_and_wait_cooldown(&mut svm, &user);
         let delete_ix = delete_identity_ix(&user.pubkey(), &identity_pda, &score_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[delete_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, delete_ix, &user).unwrap();
 
-        // Verify identity is deleted
+        // 确认身份已删除
         assert!(svm.get_account(&identity_pda).is_none());
     }
 
@@ -1003,16 +1337,7 @@ mod tests {
         let (old_score_pda, _) = get_score_pda(&old_owner.pubkey());
         let (new_score_pda, _) = get_score_pda(&new_owner.pubkey());
 
-        let create_ix = create_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let verify_ix = verify_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix, verify_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        create_verified_identity(&mut svm, &old_owner);
 
         let initiate_ix = initiate_transfer_ix(
             &old_owner.pubkey(),
@@ -1020,14 +1345,7 @@ mod tests {
             &transfer_request_pda,
             &new_owner.pubkey(),
         );
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[initiate_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, initiate_ix, &old_owner).unwrap();
 
         assert!(svm.get_account(&transfer_request_pda).is_some());
 
@@ -1057,7 +1375,7 @@ mod tests {
         let new_identity_state = IdentityAccount::try_deserialize(&mut data_slice).unwrap();
 
         assert_eq!(new_identity_state.owner, new_owner.pubkey());
-        assert_eq!(new_identity_state.verified, true);
+        assert!(new_identity_state.verified);
         assert!(new_identity_state.verified_at.is_some());
     }
 
@@ -1088,16 +1406,7 @@ mod tests {
         let (transfer_request_pda, _) =
             get_transfer_request_pda(&old_owner.pubkey(), &new_owner.pubkey());
 
-        let create_ix = create_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let verify_ix = verify_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix, verify_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        create_verified_identity(&mut svm, &old_owner);
 
         let initiate_ix = initiate_transfer_ix(
             &old_owner.pubkey(),
@@ -1105,14 +1414,7 @@ mod tests {
             &transfer_request_pda,
             &new_owner.pubkey(),
         );
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[initiate_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, initiate_ix, &old_owner).unwrap();
 
         assert!(svm.get_account(&transfer_request_pda).is_some());
 
@@ -1121,14 +1423,7 @@ mod tests {
             &transfer_request_pda,
             &new_owner.pubkey(),
         );
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[cancel_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, cancel_ix, &old_owner).unwrap();
 
         assert!(svm.get_account(&transfer_request_pda).is_none());
         assert!(svm.get_account(&old_identity_pda).is_some());
@@ -1167,26 +1462,10 @@ mod tests {
         let (old_score_pda, _) = get_score_pda(&old_owner.pubkey());
         let (new_score_pda, _) = get_score_pda(&new_owner.pubkey());
 
-        let create_ix = create_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let verify_ix = verify_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix, verify_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        create_verified_identity(&mut svm, &old_owner);
 
         let calc_ix = calculate_score_ix(&old_owner.pubkey(), &old_identity_pda, &old_score_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[calc_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, calc_ix, &old_owner).unwrap();
 
         let old_score_account = svm.get_account(&old_score_pda).unwrap();
         let mut old_score_data = &old_score_account.data[..];
@@ -1198,14 +1477,7 @@ mod tests {
             &transfer_request_pda,
             &new_owner.pubkey(),
         );
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[initiate_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, initiate_ix, &old_owner).unwrap();
 
         let claim_ix = claim_transfer_ix(
             &old_owner.pubkey(),
@@ -1268,16 +1540,7 @@ mod tests {
         let (old_score_pda, _) = get_score_pda(&old_owner.pubkey());
         let (new_score_pda, _) = get_score_pda(&new_owner.pubkey());
 
-        let create_ix = create_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let verify_ix = verify_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix, verify_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        create_verified_identity(&mut svm, &old_owner);
 
         let initiate_ix = initiate_transfer_ix(
             &old_owner.pubkey(),
@@ -1285,14 +1548,7 @@ mod tests {
             &transfer_request_pda,
             &new_owner.pubkey(),
         );
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[initiate_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, initiate_ix, &old_owner).unwrap();
 
         let claim_ix = claim_transfer_ix(
             &old_owner.pubkey(),
@@ -1315,6 +1571,64 @@ mod tests {
         assert!(result.is_err());
     }
 
+    /// 测试用别人的转移请求认领第三方的身份
+    ///
+    /// # 测试场景
+    /// 1. 旧所有者和受害者各自创建并验证身份账户
+    /// 2. 旧所有者发起转移到新所有者
+    /// 3. 新所有者用这条转移请求认领受害者的身份
+    ///
+    /// # 验证点
+    /// - 转移请求的发起者与旧所有者不一致时返回 Unauthorized
+    /// - 受害者的身份账户保持不变
+    #[test]
+    fn test_claim_transfer_wrong_old_owner() {
+        let mut svm = setup_test_environment();
+
+        let old_owner = Keypair::new();
+        let new_owner = Keypair::new();
+        let victim = Keypair::new();
+        for user in [&old_owner, &new_owner, &victim] {
+            svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+        }
+
+        for user in [&old_owner, &victim] {
+            create_verified_identity(&mut svm, user);
+        }
+
+        let (transfer_request_pda, _) =
+            get_transfer_request_pda(&old_owner.pubkey(), &new_owner.pubkey());
+        let initiate_ix = initiate_transfer_ix(
+            &old_owner.pubkey(),
+            &get_identity_pda(&old_owner.pubkey()).0,
+            &transfer_request_pda,
+            &new_owner.pubkey(),
+        );
+        send_ix(&mut svm, initiate_ix, &old_owner).unwrap();
+
+        let victim_identity_pda = get_identity_pda(&victim.pubkey()).0;
+        let victim_identity = svm.get_account(&victim_identity_pda).unwrap();
+        let mut claim_ix = claim_transfer_ix(
+            &victim.pubkey(),
+            &new_owner.pubkey(),
+            &victim_identity_pda,
+            &get_identity_pda(&new_owner.pubkey()).0,
+            &transfer_request_pda,
+            &get_score_pda(&victim.pubkey()).0,
+            &get_score_pda(&new_owner.pubkey()).0,
+        );
+        // 受害者不签名
+        claim_ix.accounts[7].is_signer = false;
+        assert_program_error(
+            send_ix(&mut svm, claim_ix, &new_owner),
+            IdentityScoreError::Unauthorized,
+        );
+        assert_eq!(
+            svm.get_account(&victim_identity_pda).unwrap(),
+            victim_identity
+        );
+    }
+
     /// 测试未授权的取消转移
     ///
     /// # 测试场景
@@ -1343,16 +1657,7 @@ mod tests {
         let (transfer_request_pda, _) =
             get_transfer_request_pda(&old_owner.pubkey(), &new_owner.pubkey());
 
-        let create_ix = create_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let verify_ix = verify_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix, verify_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        create_verified_identity(&mut svm, &old_owner);
 
         let initiate_ix = initiate_transfer_ix(
             &old_owner.pubkey(),
@@ -1360,14 +1665,7 @@ mod tests {
             &transfer_request_pda,
             &new_owner.pubkey(),
         );
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[initiate_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, initiate_ix, &old_owner).unwrap();
 
         let cancel_ix =
             cancel_transfer_ix(&hacker.pubkey(), &transfer_request_pda, &new_owner.pubkey());
@@ -1411,16 +1709,7 @@ mod tests {
         let (old_score_pda, _) = get_score_pda(&old_owner.pubkey());
         let (new_score_pda, _) = get_score_pda(&new_owner.pubkey());
 
-        let create_ix = create_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let verify_ix = verify_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix, verify_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        create_verified_identity(&mut svm, &old_owner);
 
         let old_identity_account = svm.get_account(&old_identity_pda).unwrap();
         let mut old_identity_data = &old_identity_account.data[..];
@@ -1482,14 +1771,7 @@ mod tests {
             get_transfer_request_pda(&old_owner.pubkey(), &new_owner.pubkey());
 
         let create_ix = create_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, create_ix, &old_owner).unwrap();
 
         let initiate_ix = initiate_transfer_ix(
             &old_owner.pubkey(),
@@ -1536,16 +1818,7 @@ mod tests {
         let (transfer_request_pda, _) =
             get_transfer_request_pda(&hacker.pubkey(), &new_owner.pubkey());
 
-        let create_ix = create_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let verify_ix = verify_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix, verify_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        create_verified_identity(&mut svm, &old_owner);
 
         let initiate_ix = initiate_transfer_ix(
             &hacker.pubkey(),
@@ -1590,16 +1863,7 @@ mod tests {
         let (transfer_request_pda, _) =
             get_transfer_request_pda(&old_owner.pubkey(), &new_owner.pubkey());
 
-        let create_ix = create_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let verify_ix = verify_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix, verify_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        create_verified_identity(&mut svm, &old_owner);
 
         let initiate_ix = initiate_transfer_ix(
             &old_owner.pubkey(),
@@ -1607,14 +1871,7 @@ mod tests {
             &transfer_request_pda,
             &new_owner.pubkey(),
         );
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[initiate_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, initiate_ix, &old_owner).unwrap();
 
         let transfer_request_account = svm.get_account(&transfer_request_pda).unwrap();
         let mut data_slice = &transfer_request_account.data[..];
@@ -1652,16 +1909,7 @@ mod tests {
         let (transfer_request_pda, _) =
             get_transfer_request_pda(&old_owner.pubkey(), &new_owner.pubkey());
 
-        let create_ix = create_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let verify_ix = verify_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix, verify_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        create_verified_identity(&mut svm, &old_owner);
 
         let initiate_ix = initiate_transfer_ix(
             &old_owner.pubkey(),
@@ -1669,14 +1917,7 @@ mod tests {
             &transfer_request_pda,
             &new_owner.pubkey(),
         );
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[initiate_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, initiate_ix, &old_owner).unwrap();
 
         let initiate_ix_2 = initiate_transfer_ix(
             &old_owner.pubkey(),
@@ -1725,16 +1966,7 @@ mod tests {
         let (old_score_pda, _) = get_score_pda(&old_owner.pubkey());
         let (new_score_pda, _) = get_score_pda(&new_owner.pubkey());
 
-        let create_ix = create_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let verify_ix = verify_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix, verify_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        create_verified_identity(&mut svm, &old_owner);
 
         let initiate_ix = initiate_transfer_ix(
             &old_owner.pubkey(),
@@ -1742,28 +1974,14 @@ mod tests {
             &transfer_request_pda,
             &new_owner.pubkey(),
         );
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[initiate_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, initiate_ix, &old_owner).unwrap();
 
         let cancel_ix = cancel_transfer_ix(
             &old_owner.pubkey(),
             &transfer_request_pda,
             &new_owner.pubkey(),
         );
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[cancel_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, cancel_ix, &old_owner).unwrap();
 
         assert!(svm.get_account(&transfer_request_pda).is_none());
 
@@ -1815,16 +2033,7 @@ mod tests {
         let (transfer_request_pda, _) =
             get_transfer_request_pda(&old_owner.pubkey(), &new_owner.pubkey());
 
-        let create_ix = create_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let verify_ix = verify_identity_ix(&old_owner.pubkey(), &old_identity_pda);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix, verify_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        create_verified_identity(&mut svm, &old_owner);
 
         let initiate_ix = initiate_transfer_ix(
             &old_owner.pubkey(),
@@ -1832,14 +2041,7 @@ mod tests {
             &transfer_request_pda,
             &new_owner.pubkey(),
         );
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[initiate_ix],
-            Some(&old_owner.pubkey()),
-            &[&old_owner],
-            blockhash,
-        );
-        svm.send_transaction(tx).unwrap();
+        send_ix(&mut svm, initiate_ix, &old_owner).unwrap();
 
         let transfer_request_account = svm.get_account(&transfer_request_pda).unwrap();
         let mut data_slice = &transfer_request_account.data[..];
@@ -1855,4 +2057,4220 @@ mod tests {
             crate::constants::TRANSFER_EXPIRY_SECONDS
         );
     }
+
+    /// 测试推荐关系的记录与验证计数
+    ///
+    /// # 测试场景
+    /// 1. 推荐人创建身份
+    /// 2. 被推荐人携带推荐人身份创建身份
+    /// 3. 被推荐人验证身份，随后取消验证并再次验证
+    ///
+    /// # 验证点
+    /// - 被推荐人的身份账户记录了推荐人
+    /// - 推荐统计的推荐数量和已验证数量正确
+    /// - 重复验证不会重复计数
+    /// - 缺少推荐人时传入推荐统计账户返回错误
+    #[test]
+    fn test_referral_tracking() {
+        let mut svm = setup_test_environment();
+
+        let referrer = Keypair::new();
+        let referee = Keypair::new();
+        svm.airdrop(&referrer.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .unwrap();
+        svm.airdrop(&referee.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .unwrap();
+
+        let (referrer_identity_pda, _) = get_identity_pda(&referrer.pubkey());
+        let (referee_identity_pda, _) = get_identity_pda(&referee.pubkey());
+        let (referral_stats_pda, _) = get_referral_stats_pda(&referrer_identity_pda);
+
+        let create_ix = create_identity_ix(&referrer.pubkey(), &referrer_identity_pda);
+        send_ix(&mut svm, create_ix, &referrer).unwrap();
+
+        // 只传推荐统计账户而不传推荐人时返回种子错误，而不是让程序崩溃
+        let mut create_ix = create_identity_with_referrer_ix(
+            &referee.pubkey(),
+            &referee_identity_pda,
+            &referrer_identity_pda,
+        );
+        create_ix.accounts[5] = AccountMeta::new_readonly(PROGRAM_ID, false);
+        let code = anchor_lang::error::ErrorCode::ConstraintSeeds as u32;
+        let err = send_ix(&mut svm, create_ix, &referee).expect_err("missing referrer");
+        assert!(err.contains(&format!("Custom({})", code)), "{}", err);
+
+        let create_ix = create_identity_with_referrer_ix(
+            &referee.pubkey(),
+            &referee_identity_pda,
+            &referrer_identity_pda,
+        );
+        send_ix(&mut svm, create_ix, &referee).unwrap();
+
+        let account = svm.get_account(&referee_identity_pda).unwrap();
+        let mut data_slice = &account.data[..];
+        let identity_state = IdentityAccount::try_deserialize(&mut data_slice).unwrap();
+        assert_eq!(identity_state.referrer, Some(referrer_identity_pda));
+        assert!(!identity_state.referral_credited);
+
+        let account = svm.get_account(&referral_stats_pda).unwrap();
+        let mut data_slice = &account.data[..];
+        let stats = ReferralStats::try_deserialize(&mut data_slice).unwrap();
+        assert_eq!(stats.referrer_identity, referrer_identity_pda);
+        assert_eq!(stats.referred_count, 1);
+        assert_eq!(stats.verified_referred_count, 0);
+
+        // 首次验证必须携带推荐统计账户
+        let verify_ix = verify_identity_ix(&referee.pubkey(), &referee_identity_pda);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[verify_ix],
+            Some(&referee.pubkey()),
+            &[&referee],
+            blockhash,
+        );
+        assert!(svm.send_transaction(tx).is_err());
+
+        let verify_ix = verify_identity_with_referral_ix(
+            &referee.pubkey(),
+            &referee_identity_pda,
+            &referrer_identity_pda,
+        );
+        let unverify_ix = unverify_identity_ix(&referee.pubkey(), &referee_identity_pda);
+        let reverify_ix = verify_identity_ix(&referee.pubkey(), &referee_identity_pda);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[verify_ix, unverify_ix, reverify_ix],
+            Some(&referee.pubkey()),
+            &[&referee],
+            blockhash,
+        );
+        svm.send_transaction(tx).unwrap();
+
+        let account = svm.get_account(&referral_stats_pda).unwrap();
+        let mut data_slice = &account.data[..];
+        let stats = ReferralStats::try_deserialize(&mut data_slice).unwrap();
+        assert_eq!(stats.referred_count, 1);
+        assert_eq!(stats.verified_referred_count, 1);
+    }
+
+    /// 测试协议配置的初始化权限与参数校验
+    ///
+    /// # 测试场景
+    /// 1. 非升级权限的账户抢先初始化配置
+    /// 2. 升级权限初始化配置
+    /// 3. 管理员将删除冷却期设置为负数或超过上限
+    ///
+    /// # 验证点
+    /// - 非升级权限初始化返回 Unauthorized
+    /// - 负的或超过上限的删除冷却期返回 InvalidDeletionCooldown
+    #[test]
+    fn test_initialize_config_requires_upgrade_authority() {
+        let mut svm = LiteSVM::new();
+        let admin = Keypair::new();
+        let attacker = Keypair::new();
+        deploy_upgradeable_program(&mut svm, &admin.pubkey());
+        svm.airdrop(&admin.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&attacker.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .unwrap();
+
+        let ix = config_ix(
+            "initialize_config",
+            &attacker.pubkey(),
+            &test_config_params(),
+        );
+        assert_program_error(
+            send_ix(&mut svm, ix, &attacker),
+            IdentityScoreError::Unauthorized,
+        );
+        assert!(svm.get_account(&get_config_pda().0).is_none());
+
+        let ix = config_ix("initialize_config", &admin.pubkey(), &test_config_params());
+        send_ix(&mut svm, ix, &admin).unwrap();
+
+        for deletion_cooldown_seconds in [-1, MAX_DELETION_COOLDOWN_SECONDS + 1, i64::MAX] {
+            let params = ConfigParams {
+                deletion_cooldown_seconds,
+                ..test_config_params()
+            };
+            let ix = config_ix("update_config", &admin.pubkey(), &params);
+            assert_program_error(
+                send_ix(&mut svm, ix, &admin),
+                IdentityScoreError::InvalidDeletionCooldown,
+            );
+        }
+        let params = ConfigParams {
+            deletion_cooldown_seconds: MAX_DELETION_COOLDOWN_SECONDS,
+            ..test_config_params()
+        };
+        let ix = config_ix("update_config", &admin.pubkey(), &params);
+        send_ix(&mut svm, ix, &admin).unwrap();
+    }
+
+    /// 测试停用冷却期内的删除与重新激活
+    ///
+    /// # 测试场景
+    /// 1. 管理员将删除冷却期设置为 1 小时
+    /// 2. 用户停用身份后立即删除、计算信用分和发起转移
+    /// 3. 用户在冷却期内重新激活身份，再次停用
+    /// 4. 时间推进超过冷却期后删除身份
+    ///
+    /// # 验证点
+    /// - 未停用的身份不能删除
+    /// - 冷却期内删除失败，停用身份不能计算信用分或转移
+    /// - 冷却期内可以重新激活，冷却期后可以删除
+    #[test]
+    fn test_deactivate_cooldown_and_reactivate() {
+        let (mut svm, admin) = setup_test_environment_with_admin();
+
+        let params = ConfigParams {
+            deletion_cooldown_seconds: 3600,
+            ..test_config_params()
+        };
+        let ix = config_ix("update_config", &admin.pubkey(), &params);
+        send_ix(&mut svm, ix, &admin).unwrap();
+
+        let user = Keypair::new();
+        let recipient = Keypair::new();
+        svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        let (transfer_request_pda, _) =
+            get_transfer_request_pda(&user.pubkey(), &recipient.pubkey());
+
+        create_verified_identity(&mut svm, &user);
+
+        let delete_ix = delete_identity_ix(&user.pubkey(), &identity_pda, &score_pda);
+        assert!(send_ix(&mut svm, delete_ix, &user).is_err());
+
+        let deactivate_ix = deactivate_identity_ix(&user.pubkey(), &identity_pda);
+        send_ix(&mut svm, deactivate_ix, &user).unwrap();
+
+        let delete_ix = delete_identity_ix(&user.pubkey(), &identity_pda, &score_pda);
+        assert!(send_ix(&mut svm, delete_ix, &user).is_err());
+        let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        assert!(send_ix(&mut svm, calc_ix, &user).is_err());
+        let initiate_ix = initiate_transfer_ix(
+            &user.pubkey(),
+            &identity_pda,
+            &transfer_request_pda,
+            &recipient.pubkey(),
+        );
+        assert!(send_ix(&mut svm, initiate_ix, &user).is_err());
+
+        let reactivate_ix = reactivate_identity_ix(&user.pubkey(), &identity_pda);
+        send_ix(&mut svm, reactivate_ix, &user).unwrap();
+
+        let account = svm.get_account(&identity_pda).unwrap();
+        let mut data_slice = &account.data[..];
+        let identity_state = IdentityAccount::try_deserialize(&mut data_slice).unwrap();
+        assert!(identity_state.is_active());
+
+        let deactivate_ix = deactivate_identity_ix(&user.pubkey(), &identity_pda);
+        send_ix(&mut svm, deactivate_ix, &user).unwrap();
+
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += 3600;
+        svm.set_sysvar::<Clock>(&clock);
+
+        let reactivate_ix = reactivate_identity_ix(&user.pubkey(), &identity_pda);
+        assert!(send_ix(&mut svm, reactivate_ix, &user).is_err());
+
+        let delete_ix = delete_identity_ix(&user.pubkey(), &identity_pda, &score_pda);
+        send_ix(&mut svm, delete_ix, &user).unwrap();
+        assert!(svm.get_account(&identity_pda).is_none());
+    }
+
+    /// 测试删除后重新创建身份继承墓碑记录
+    ///
+    /// # 测试场景
+    /// 1. 用户创建并验证身份，计算信用分
+    /// 2. 用户停用并删除身份
+    /// 3. 用户重新创建并验证身份
+    /// 4. 等待期结束后重新计算信用分
+    ///
+    /// # 验证点
+    /// - 墓碑记录删除次数，没有处罚和违约时不产生负面标记
+    /// - 新身份继承惩罚分和等待期
+    /// - 等待期内无法计算信用分，等待期后分数被扣减
+    #[test]
+    fn test_tombstone_penalty_on_recreate() {
+        let mut svm = setup_test_environment();
+
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), 10_000_000).unwrap();
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        let (tombstone_pda, _) = get_tombstone_pda(&user.pubkey());
+
+        create_scored_identity(&mut svm, &user);
+
+        deactivate_and_wait_cooldown(&mut svm, &user);
+        let delete_ix = delete_identity_ix(&user.pubkey(), &identity_pda, &score_pda);
+        send_ix(&mut svm, delete_ix, &user).unwrap();
+
+        let account = svm.get_account(&tombstone_pda).unwrap();
+        let mut data_slice = &account.data[..];
+        let tombstone = Tombstone::try_deserialize(&mut data_slice).unwrap();
+        assert_eq!(tombstone.owner, user.pubkey());
+        assert_eq!(tombstone.deletion_count, 1);
+        assert_eq!(tombstone.negative_marks, 0);
+
+        svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+        create_verified_identity(&mut svm, &user);
+
+        let account = svm.get_account(&identity_pda).unwrap();
+        let mut data_slice = &account.data[..];
+        let identity_state = IdentityAccount::try_deserialize(&mut data_slice).unwrap();
+        assert_eq!(identity_state.penalty_points, tombstone.penalty_points());
+        assert_eq!(identity_state.eligible_at, tombstone.eligible_at());
+
+        let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        assert!(send_ix(&mut svm, calc_ix, &user).is_err());
+
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = identity_state.eligible_at;
+        svm.set_sysvar::<Clock>(&clock);
+
+        let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        send_ix(&mut svm, calc_ix, &user).unwrap();
+
+        let account = svm.get_account(&score_pda).unwrap();
+        let mut data_slice = &account.data[..];
+        let score_state = CreditScoreAccount::try_deserialize(&mut data_slice).unwrap();
+        let (unpenalized, _) = fixed_score(
+            svm.get_balance(&user.pubkey()).unwrap(),
+            identity_state.created_at,
+            true,
+            owner_footprint_rent(&svm, &user.pubkey(), &[]),
+            clock.unix_timestamp,
+        );
+        assert_eq!(
+            score_state.score,
+            unpenalized - identity_state.penalty_points
+        );
+    }
+
+    /// 写入指定违约次数的还款记录
+    fn set_defaulted_credit_history(svm: &mut LiteSVM, owner: &Pubkey, defaulted: u32) {
+        let history = CreditHistory {
+            owner: *owner,
+            loans_opened: defaulted,
+            repaid_on_time: 0,
+            repaid_late: 0,
+            defaulted,
+            last_event_at: 0,
+        };
+        set_program_account(
+            svm,
+            &get_credit_history_pda(owner).0,
+            &history,
+            CreditHistory::SPACE,
+        );
+    }
+
+    /// 读取墓碑账户
+    fn get_tombstone(svm: &LiteSVM, owner: &Pubkey) -> Tombstone {
+        let account = svm.get_account(&get_tombstone_pda(owner).0).unwrap();
+        let mut data_slice = &account.data[..];
+        Tombstone::try_deserialize(&mut data_slice).unwrap()
+    }
+
+    /// 测试墓碑标记来自违约记录，且转移身份同样留下标记
+    ///
+    /// # 测试场景
+    /// 1. 有两次违约的用户将身份转移到新钱包
+    /// 2. 旧钱包重新创建身份
+    /// 3. 新钱包停用并删除身份
+    ///
+    /// # 验证点
+    /// - 转移后旧钱包的墓碑记录两个负面标记，但不计删除次数
+    /// - 旧钱包重新创建的身份继承扣分
+    /// - 新钱包删除时，违约和携带的扣分都计入自己的墓碑
+    #[test]
+    fn test_tombstone_marks_survive_transfer() {
+        let mut svm = setup_test_environment();
+
+        let user = Keypair::new();
+        let throwaway = Keypair::new();
+        svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&throwaway.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .unwrap();
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        let (new_identity_pda, _) = get_identity_pda(&throwaway.pubkey());
+        let (new_score_pda, _) = get_score_pda(&throwaway.pubkey());
+        let (transfer_request_pda, _) =
+            get_transfer_request_pda(&user.pubkey(), &throwaway.pubkey());
+
+        let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
+        let verify_ix = verify_identity_ix(&user.pubkey(), &identity_pda);
+        let initiate_ix = initiate_transfer_ix(
+            &user.pubkey(),
+            &identity_pda,
+            &transfer_request_pda,
+            &throwaway.pubkey(),
+        );
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[create_ix, verify_ix, initiate_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).unwrap();
+        set_defaulted_credit_history(&mut svm, &user.pubkey(), 2);
+
+        let claim_ix = claim_transfer_ix(
+            &user.pubkey(),
+            &throwaway.pubkey(),
+            &identity_pda,
+            &new_identity_pda,
+            &transfer_request_pda,
+            &score_pda,
+            &new_score_pda,
+        );
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[claim_ix],
+            Some(&throwaway.pubkey()),
+            &[&throwaway, &user],
+            blockhash,
+        );
+        svm.send_transaction(tx).unwrap();
+
+        let tombstone = get_tombstone(&svm, &user.pubkey());
+        assert_eq!(tombstone.owner, user.pubkey());
+        assert_eq!(tombstone.deletion_count, 0);
+        assert_eq!(tombstone.negative_marks, 2);
+
+        let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
+        send_ix(&mut svm, create_ix, &user).unwrap();
+        let account = svm.get_account(&identity_pda).unwrap();
+        let mut data_slice = &account.data[..];
+        let identity_state = IdentityAccount::try_deserialize(&mut data_slice).unwrap();
+        assert_eq!(identity_state.penalty_points, tombstone.penalty_points());
+        assert!(identity_state.penalty_points > 0);
+
+        deactivate_and_wait_cooldown(&mut svm, &throwaway);
+        let delete_ix = delete_identity_ix(&throwaway.pubkey(), &new_identity_pda, &new_score_pda);
+        send_ix(&mut svm, delete_ix, &throwaway).unwrap();
+
+        let tombstone = get_tombstone(&svm, &throwaway.pubkey());
+        assert_eq!(tombstone.deletion_count, 1);
+        assert_eq!(tombstone.negative_marks, 2);
+    }
+
+    /// 测试有未结清贷款的身份不能被认领
+    ///
+    /// # 测试场景
+    /// 1. 用户开了两笔贷款，只违约结清了一笔，随后发起转移
+    /// 2. 接收者认领
+    /// 3. 剩余贷款结清后再次认领
+    ///
+    /// # 验证点
+    /// - 仍有贷款未结清时认领失败，旧身份保持不变
+    /// - 贷款全部结清后认领成功
+    #[test]
+    fn test_claim_transfer_rejects_outstanding_loans() {
+        let mut svm = setup_test_environment();
+
+        let user = Keypair::new();
+        let recipient = Keypair::new();
+        svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&recipient.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .unwrap();
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        let (new_identity_pda, _) = get_identity_pda(&recipient.pubkey());
+        let (new_score_pda, _) = get_score_pda(&recipient.pubkey());
+        let (transfer_request_pda, _) =
+            get_transfer_request_pda(&user.pubkey(), &recipient.pubkey());
+
+        let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
+        let verify_ix = verify_identity_ix(&user.pubkey(), &identity_pda);
+        let initiate_ix = initiate_transfer_ix(
+            &user.pubkey(),
+            &identity_pda,
+            &transfer_request_pda,
+            &recipient.pubkey(),
+        );
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[create_ix, verify_ix, initiate_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).unwrap();
+
+        let mut history = CreditHistory {
+            owner: user.pubkey(),
+            loans_opened: 2,
+            repaid_on_time: 0,
+            repaid_late: 0,
+            defaulted: 1,
+            last_event_at: 0,
+        };
+        set_program_account(
+            &mut svm,
+            &get_credit_history_pda(&user.pubkey()).0,
+            &history,
+            CreditHistory::SPACE,
+        );
+
+        let claim_ix = claim_transfer_ix(
+            &user.pubkey(),
+            &recipient.pubkey(),
+            &identity_pda,
+            &new_identity_pda,
+            &transfer_request_pda,
+            &score_pda,
+            &new_score_pda,
+        );
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            std::slice::from_ref(&claim_ix),
+            Some(&recipient.pubkey()),
+            &[&recipient, &user],
+            blockhash,
+        );
+        let result = svm
+            .send_transaction(tx)
+            .map(|_| ())
+            .map_err(|e| format!("{:?}", e.err));
+        assert_program_error(result, IdentityScoreError::OutstandingLoansExist);
+        assert!(svm.get_account(&identity_pda).is_some());
+        assert!(svm.get_account(&new_identity_pda).is_none());
+
+        history.repaid_late = 1;
+        set_program_account(
+            &mut svm,
+            &get_credit_history_pda(&user.pubkey()).0,
+            &history,
+            CreditHistory::SPACE,
+        );
+        svm.expire_blockhash();
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[claim_ix],
+            Some(&recipient.pubkey()),
+            &[&recipient, &user],
+            blockhash,
+        );
+        svm.send_transaction(tx).unwrap();
+        assert!(svm.get_account(&new_identity_pda).is_some());
+    }
+
+    /// 读取全局统计账户
+    fn get_stats(svm: &LiteSVM) -> ProtocolStats {
+        let account = svm.get_account(&get_stats_pda().0).unwrap();
+        let mut data_slice = &account.data[..];
+        ProtocolStats::try_deserialize(&mut data_slice).unwrap()
+    }
+
+    /// 测试全局统计在身份、信用分、转移和删除流程中保持正确
+    ///
+    /// # 测试场景
+    /// 1. 两个用户创建身份，其中一个验证并计算信用分
+    /// 2. 重复计算信用分
+    /// 3. 已验证用户发起转移，接收者认领
+    /// 4. 接收者停用并删除身份
+    ///
+    /// # 验证点
+    /// - 身份总数、已验证数量、计算次数、活跃转移请求数正确
+    /// - 等级分布在重复计算、转移和删除后保持正确
+    #[test]
+    fn test_protocol_stats() {
+        let mut svm = setup_test_environment();
+
+        let user = Keypair::new();
+        let other = Keypair::new();
+        let recipient = Keypair::new();
+        svm.airdrop(&user.pubkey(), 15 * LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&other.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&recipient.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        let (other_identity_pda, _) = get_identity_pda(&other.pubkey());
+        let (new_identity_pda, _) = get_identity_pda(&recipient.pubkey());
+        let (new_score_pda, _) = get_score_pda(&recipient.pubkey());
+        let (transfer_request_pda, _) =
+            get_transfer_request_pda(&user.pubkey(), &recipient.pubkey());
+
+        let create_ix = create_identity_ix(&other.pubkey(), &other_identity_pda);
+        send_ix(&mut svm, create_ix, &other).unwrap();
+
+        create_scored_identity(&mut svm, &user);
+
+        svm.expire_blockhash();
+        let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        send_ix(&mut svm, calc_ix, &user).unwrap();
+
+        let stats = get_stats(&svm);
+        assert_eq!(stats.total_identities, 2);
+        assert_eq!(stats.verified_identities, 1);
+        assert_eq!(stats.scores_computed, 2);
+        assert_eq!(stats.level_counts, [0, 0, 0, 0, 1]);
+
+        let initiate_ix = initiate_transfer_ix(
+            &user.pubkey(),
+            &identity_pda,
+            &transfer_request_pda,
+            &recipient.pubkey(),
+        );
+        send_ix(&mut svm, initiate_ix, &user).unwrap();
+        assert_eq!(get_stats(&svm).active_transfer_requests, 1);
+
+        let claim_ix = claim_transfer_ix(
+            &user.pubkey(),
+            &recipient.pubkey(),
+            &identity_pda,
+            &new_identity_pda,
+            &transfer_request_pda,
+            &score_pda,
+            &new_score_pda,
+        );
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[claim_ix],
+            Some(&recipient.pubkey()),
+            &[&recipient, &user],
+            blockhash,
+        );
+        svm.send_transaction(tx).unwrap();
+
+        let stats = get_stats(&svm);
+        assert_eq!(stats.total_identities, 2);
+        assert_eq!(stats.verified_identities, 1);
+        assert_eq!(stats.active_transfer_requests, 0);
+        assert_eq!(stats.level_counts, [0, 0, 0, 0, 1]);
+
+        deactivate_and_wait_cooldown(&mut svm, &recipient);
+        let delete_ix = delete_identity_ix(&recipient.pubkey(), &new_identity_pda, &new_score_pda);
+        send_ix(&mut svm, delete_ix, &recipient).unwrap();
+
+        let stats = get_stats(&svm);
+        assert_eq!(stats.total_identities, 1);
+        assert_eq!(stats.verified_identities, 0);
+        assert_eq!(stats.level_counts, [0; ScoreLevel::COUNT]);
+    }
+
+    /// 测试任何人都可以关闭过期的转移请求
+    ///
+    /// # 测试场景
+    /// 1. 用户向接收者发起转移，接收者未认领
+    /// 2. 第三方在过期前后分别关闭转移请求
+    ///
+    /// # 验证点
+    /// - 过期前关闭返回 TransferNotExpired
+    /// - 过期后关闭成功，租金返还发起者，活跃转移请求数归零
+    #[test]
+    fn test_close_expired_transfer() {
+        let mut svm = setup_test_environment();
+
+        let user = Keypair::new();
+        let recipient = Keypair::new();
+        let cranker = Keypair::new();
+        svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&cranker.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (transfer_request_pda, _) =
+            get_transfer_request_pda(&user.pubkey(), &recipient.pubkey());
+
+        let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
+        let verify_ix = verify_identity_ix(&user.pubkey(), &identity_pda);
+        let initiate_ix = initiate_transfer_ix(
+            &user.pubkey(),
+            &identity_pda,
+            &transfer_request_pda,
+            &recipient.pubkey(),
+        );
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[create_ix, verify_ix, initiate_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).unwrap();
+        assert_eq!(get_stats(&svm).active_transfer_requests, 1);
+
+        let close_ix = close_expired_transfer_ix(&user.pubkey(), &transfer_request_pda);
+        assert_program_error(
+            send_ix(&mut svm, close_ix, &cranker),
+            IdentityScoreError::TransferNotExpired,
+        );
+
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += TRANSFER_EXPIRY_SECONDS + 1;
+        svm.set_sysvar::<Clock>(&clock);
+
+        svm.expire_blockhash();
+        let request_rent = svm.get_balance(&transfer_request_pda).unwrap();
+        let user_balance = svm.get_balance(&user.pubkey()).unwrap();
+        let close_ix = close_expired_transfer_ix(&user.pubkey(), &transfer_request_pda);
+        send_ix(&mut svm, close_ix, &cranker).unwrap();
+
+        assert!(svm.get_account(&transfer_request_pda).is_none());
+        assert_eq!(
+            svm.get_balance(&user.pubkey()).unwrap(),
+            user_balance + request_rent
+        );
+        assert_eq!(get_stats(&svm).active_transfer_requests, 0);
+    }
+
+    /// 旧版浮点评分公式，用于校验定点实现
+    fn reference_float_score(
+        lamports: u64,
+        identity_created_at: i64,
+        is_verified: bool,
+        footprint_rent: u64,
+        current_timestamp: i64,
+    ) -> u8 {
+        let normalize = |n: f64| (30.0 + 60.0 * n.clamp(0.0, 1.0)) as u8;
+        let asset = if lamports == 0 {
+            30
+        } else {
+            normalize((lamports as f64).ln() / 23.025850929940457)
+        };
+        let age_seconds = (current_timestamp - identity_created_at).max(0);
+        let max_age = (365 * 24 * 60 * 60) as f64;
+        let age_score = ((age_seconds as f64 / max_age).clamp(0.0, 1.0) * 60.0) as u8;
+        let stability = (age_score + if is_verified { 30 } else { 0 }).min(90);
+        let rent_exempt = footprint_rent.max(1);
+        let ratio = (lamports as f64 / rent_exempt as f64).clamp(1.0, 10.0);
+        let rent_efficiency = normalize((ratio - 1.0) / 9.0);
+        let verification = if is_verified { 90 } else { 50 };
+        (asset as f64 * 0.4
+            + stability as f64 * 0.3
+            + rent_efficiency as f64 * 0.2
+            + verification as f64 * 0.1) as u8
+    }
+
+    /// 测试定点对数的精度
+    ///
+    /// # 验证点
+    /// - ln_fixed 与浮点 ln 的误差小于 1e-6
+    /// - 2 的整数次幂结果精确
+    #[test]
+    fn test_ln_fixed_accuracy() {
+        use crate::scoring::{ln_fixed, log2_fixed, ONE};
+
+        assert_eq!(ln_fixed(1), 0);
+        for exp in 0..64 {
+            assert_eq!(log2_fixed(1u64 << exp), exp * ONE);
+        }
+
+        let mut x = 1u64;
+        while x < u64::MAX / 3 {
+            let fixed = ln_fixed(x) as f64 / ONE as f64;
+            assert!(
+                (fixed - (x as f64).ln()).abs() < 1e-6,
+                "ln({}) = {} but got {}",
+                x,
+                (x as f64).ln(),
+                fixed
+            );
+            x = x * 3 + 1;
+        }
+    }
+
+    /// 只用钱包余额计算定点分数（无代币持仓）
+    fn fixed_score(
+        lamports: u64,
+        identity_created_at: i64,
+        is_verified: bool,
+        footprint_rent: u64,
+        current_timestamp: i64,
+    ) -> (u8, ScoreLevel) {
+        crate::scoring::calculate_comprehensive_score(
+            &crate::scoring::ScoreInputs {
+                lamports,
+                identity_created_at,
+                is_verified,
+                footprint_rent,
+                current_timestamp,
+                ..Default::default()
+            },
+            &LevelCutoffs::default(),
+        )
+    }
+
+    /// 所有者账户占用的租金豁免总额：身份、信用分和历史账户按完整大小计，
+    /// 加上已存在的其他 PDA（处罚索引除外）和传入的持仓账户
+    fn owner_footprint_rent(svm: &LiteSVM, owner: &Pubkey, holdings: &[Pubkey]) -> u64 {
+        let sidecars = [
+            get_credit_history_pda(owner).0,
+            get_balance_history_pda(owner).0,
+            get_activity_pda(owner).0,
+            get_holding_registry_pda(owner).0,
+            get_tip_vault_pda(owner).0,
+        ];
+        let existing = sidecars
+            .iter()
+            .chain(holdings)
+            .filter_map(|address| svm.get_account(address))
+            .map(|account| account.data.len())
+            .filter(|len| *len > 0);
+        [
+            IdentityAccount::SPACE,
+            CreditScoreAccount::SPACE,
+            ScoreHistory::SPACE,
+        ]
+        .into_iter()
+        .chain(existing)
+        .map(|len| svm.minimum_balance_for_rent_exemption(len))
+        .sum()
+    }
+
+    /// 测试定点评分与旧版浮点评分曲线一致
+    ///
+    /// # 测试场景
+    /// 遍历余额、身份年龄、验证状态和账户大小的组合
+    ///
+    /// # 验证点
+    /// - 定点分数与浮点分数相差不超过 1 分
+    /// - 典型输入的分数被固定下来
+    #[test]
+    fn test_fixed_point_score_matches_float_curve() {
+        let day = 24 * 60 * 60;
+        let now = 1_700_000_000;
+        let balances = [
+            0,
+            1,
+            999,
+            1_000_000,
+            10_000_000,
+            123_456_789,
+            LAMPORTS_PER_SOL,
+            5 * LAMPORTS_PER_SOL,
+            15 * LAMPORTS_PER_SOL,
+            1_000 * LAMPORTS_PER_SOL,
+            u64::MAX,
+        ];
+
+        for &lamports in &balances {
+            for age_days in [0, 1, 30, 182, 365, 1_000] {
+                for is_verified in [false, true] {
+                    for rent in [0, 1_000_000, 100_000_000, 20_480_000_000] {
+                        let created_at = now - age_days * day;
+                        let (fixed, level) =
+                            fixed_score(lamports, created_at, is_verified, rent, now);
+                        let float =
+                            reference_float_score(lamports, created_at, is_verified, rent, now);
+                        assert!(
+                            fixed.abs_diff(float) <= 1,
+                            "lamports={} age={}d verified={} rent={}: fixed {} float {}",
+                            lamports,
+                            age_days,
+                            is_verified,
+                            rent,
+                            fixed,
+                            float
+                        );
+                        assert_eq!(level, LevelCutoffs::default().level(fixed));
+                    }
+                }
+            }
+        }
+
+        assert_eq!(
+            fixed_score(15 * LAMPORTS_PER_SOL, now, true, 100_000_000, now),
+            (72, ScoreLevel::Excellent)
+        );
+        assert_eq!(
+            fixed_score(500_000_000, now, true, 100_000_000, now),
+            (62, ScoreLevel::VeryGood)
+        );
+        assert_eq!(
+            fixed_score(0, now, false, 100_000_000, now),
+            (23, ScoreLevel::Poor)
+        );
+    }
+
+    /// 读取信用分历史账户
+    fn get_score_history(svm: &LiteSVM, owner: &Pubkey) -> ScoreHistory {
+        let account = svm.get_account(&get_score_history_pda(owner).0).unwrap();
+        let mut data_slice = &account.data[..];
+        ScoreHistory::try_deserialize(&mut data_slice).unwrap()
+    }
+
+    /// 测试信用分历史环形缓冲区
+    ///
+    /// # 测试场景
+    /// 1. 用户计算信用分超过缓冲区容量的次数
+    /// 2. 用户将身份转移给接收者
+    /// 3. 接收者删除信用分
+    ///
+    /// # 验证点
+    /// - 历史记录数量不超过容量，且按时间从新到旧排列
+    /// - 转移后历史记录迁移到新身份，旧历史账户被关闭
+    /// - 删除信用分时历史账户一并关闭
+    #[test]
+    fn test_score_history_ring_buffer() {
+        let mut svm = setup_test_environment();
+
+        let user = Keypair::new();
+        let recipient = Keypair::new();
+        svm.airdrop(&user.pubkey(), 15 * LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&recipient.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        let (new_identity_pda, _) = get_identity_pda(&recipient.pubkey());
+        let (new_score_pda, _) = get_score_pda(&recipient.pubkey());
+        let (transfer_request_pda, _) =
+            get_transfer_request_pda(&user.pubkey(), &recipient.pubkey());
+
+        create_verified_identity(&mut svm, &user);
+
+        let calculations = SCORE_HISTORY_LEN + 3;
+        for _ in 0..calculations {
+            let mut clock = svm.get_sysvar::<Clock>();
+            clock.unix_timestamp += 60;
+            svm.set_sysvar::<Clock>(&clock);
+            svm.expire_blockhash();
+
+            let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+            send_ix(&mut svm, calc_ix, &user).unwrap();
+        }
+
+        let history = get_score_history(&svm, &user.pubkey());
+        assert_eq!(history.identity, identity_pda);
+        assert_eq!(history.len as usize, SCORE_HISTORY_LEN);
+        let timestamps: Vec<i64> = history
+            .iter_newest_first()
+            .map(|entry| entry.calculated_at)
+            .collect();
+        assert_eq!(timestamps.len(), SCORE_HISTORY_LEN);
+        assert!(timestamps.windows(2).all(|pair| pair[0] > pair[1]));
+        assert_eq!(timestamps[0], svm.get_sysvar::<Clock>().unix_timestamp);
+
+        let initiate_ix = initiate_transfer_ix(
+            &user.pubkey(),
+            &identity_pda,
+            &transfer_request_pda,
+            &recipient.pubkey(),
+        );
+        send_ix(&mut svm, initiate_ix, &user).unwrap();
+
+        let claim_ix = claim_transfer_ix(
+            &user.pubkey(),
+            &recipient.pubkey(),
+            &identity_pda,
+            &new_identity_pda,
+            &transfer_request_pda,
+            &score_pda,
+            &new_score_pda,
+        );
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[claim_ix],
+            Some(&recipient.pubkey()),
+            &[&recipient, &user],
+            blockhash,
+        );
+        svm.send_transaction(tx).unwrap();
+
+        assert!(svm
+            .get_account(&get_score_history_pda(&user.pubkey()).0)
+            .is_none());
+        let new_history = get_score_history(&svm, &recipient.pubkey());
+        assert_eq!(new_history.identity, new_identity_pda);
+        assert_eq!(new_history.entries, history.entries);
+
+        let delete_ix = delete_score_ix(&recipient.pubkey(), &new_identity_pda, &new_score_pda);
+        send_ix(&mut svm, delete_ix, &recipient).unwrap();
+        assert!(svm
+            .get_account(&get_score_history_pda(&recipient.pubkey()).0)
+            .is_none());
+    }
+
+    /// 测试信用分有效期与过期后的衰减
+    ///
+    /// # 测试场景
+    /// 1. 管理员将信用分有效期设置为 1 天
+    /// 2. 用户计算信用分
+    /// 3. 在有效期内、过期后和衰减期结束后读取有效分数
+    ///
+    /// # 验证点
+    /// - expires_at 等于计算时间加有效期
+    /// - 有效期内有效分数等于原始分数
+    /// - 过期后分数单调衰减，最终降到 SCORE_MIN
+    #[test]
+    fn test_score_expiry_and_decay() {
+        let (mut svm, admin) = setup_test_environment_with_admin();
+
+        let validity = 24 * 60 * 60;
+        let params = ConfigParams {
+            score_validity_seconds: validity,
+            ..test_config_params()
+        };
+        let ix = config_ix("update_config", &admin.pubkey(), &params);
+        send_ix(&mut svm, ix, &admin).unwrap();
+
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), 15 * LAMPORTS_PER_SOL).unwrap();
+
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+
+        create_scored_identity(&mut svm, &user);
+
+        let account = svm.get_account(&score_pda).unwrap();
+        let mut data_slice = &account.data[..];
+        let score_state = CreditScoreAccount::try_deserialize(&mut data_slice).unwrap();
+        assert_eq!(score_state.expires_at, score_state.calculated_at + validity);
+
+        let expires_at = score_state.expires_at;
+        assert_eq!(score_state.effective_score(expires_at), score_state.score);
+        assert!(!score_state.is_expired(expires_at));
+
+        let halfway = score_state.effective_score(expires_at + SCORE_DECAY_SECONDS / 2);
+        assert!(halfway < score_state.score);
+        assert!(halfway > crate::scoring::SCORE_MIN);
+        assert_eq!(
+            score_state.effective_score(expires_at + SCORE_DECAY_SECONDS),
+            crate::scoring::SCORE_MIN
+        );
+        assert_eq!(
+            score_state.effective_level(
+                expires_at + 2 * SCORE_DECAY_SECONDS,
+                &LevelCutoffs::default()
+            ),
+            ScoreLevel::Poor
+        );
+    }
+
+    /// 测试信用分重算频率限制与管理员强制重算
+    ///
+    /// # 测试场景
+    /// 1. 管理员将最小重算间隔设置为负数、超过上限和 1 小时
+    /// 2. 用户计算信用分后立即再次计算
+    /// 3. 非管理员和管理员分别尝试强制重算
+    /// 4. 时间推进超过间隔后用户再次计算
+    /// 5. 用户在间隔内删除信用分，间隔结束后删除并重新计算
+    ///
+    /// # 验证点
+    /// - 负的或超过上限的间隔返回 InvalidScoreInterval
+    /// - 间隔内重算返回 ScoreCooldownActive
+    /// - 只有管理员可以绕过间隔强制重算
+    /// - 间隔结束后可以正常重算
+    /// - 间隔内删除信用分返回 ScoreCooldownActive，不能靠删除绕过间隔
+    #[test]
+    fn test_score_recalculation_cooldown() {
+        let (mut svm, admin) = setup_test_environment_with_admin();
+
+        for min_score_interval_seconds in [-1, MAX_SCORE_INTERVAL_SECONDS + 1, i64::MAX] {
+            let params = ConfigParams {
+                min_score_interval_seconds,
+                ..test_config_params()
+            };
+            let ix = config_ix("update_config", &admin.pubkey(), &params);
+            assert_program_error(
+                send_ix(&mut svm, ix, &admin),
+                IdentityScoreError::InvalidScoreInterval,
+            );
+        }
+
+        let params = ConfigParams {
+            min_score_interval_seconds: 3600,
+            ..test_config_params()
+        };
+        let ix = config_ix("update_config", &admin.pubkey(), &params);
+        send_ix(&mut svm, ix, &admin).unwrap();
+
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), 15 * LAMPORTS_PER_SOL).unwrap();
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+
+        create_scored_identity(&mut svm, &user);
+
+        let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        assert_program_error(
+            send_ix(&mut svm, calc_ix, &user),
+            IdentityScoreError::ScoreCooldownActive,
+        );
+
+        let force_ix = force_calculate_score_ix(&user.pubkey(), &user.pubkey(), &identity_pda);
+        assert_program_error(
+            send_ix(&mut svm, force_ix, &user),
+            IdentityScoreError::Unauthorized,
+        );
+
+        let force_ix = force_calculate_score_ix(&admin.pubkey(), &user.pubkey(), &identity_pda);
+        send_ix(&mut svm, force_ix, &admin).unwrap();
+        assert_eq!(get_stats(&svm).scores_computed, 2);
+
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += 3600;
+        svm.set_sysvar::<Clock>(&clock);
+
+        let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        send_ix(&mut svm, calc_ix, &user).unwrap();
+        assert_eq!(get_stats(&svm).scores_computed, 3);
+
+        let delete_ix = delete_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        assert_program_error(
+            send_ix(&mut svm, delete_ix, &user),
+            IdentityScoreError::ScoreCooldownActive,
+        );
+        assert!(svm.get_account(&score_pda).is_some());
+
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += 3600;
+        svm.set_sysvar::<Clock>(&clock);
+        svm.expire_blockhash();
+
+        let delete_ix = delete_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        send_ix(&mut svm, delete_ix, &user).unwrap();
+        assert!(svm.get_account(&score_pda).is_none());
+        let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        send_ix(&mut svm, calc_ix, &user).unwrap();
+        assert_eq!(get_stats(&svm).scores_computed, 4);
+    }
+
+    /// 获取代币白名单账户的 PDA 地址
+    fn get_allowed_mint_pda(mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SEED_ALLOWED_MINT, mint.as_ref()], &PROGRAM_ID)
+    }
+
+    /// 构建设置代币白名单指令
+    fn set_allowed_mint_ix(
+        admin: &Pubkey,
+        mint: &Pubkey,
+        decimals: u8,
+        weight: u64,
+        usd_weight: u64,
+    ) -> Instruction {
+        let mut data = get_discriminator("set_allowed_mint").to_vec();
+        data.push(decimals);
+        data.extend_from_slice(&weight.to_le_bytes());
+        data.extend_from_slice(&usd_weight.to_le_bytes());
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(get_allowed_mint_pda(mint).0, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new_readonly(get_config_pda().0, false),
+                AccountMeta::new(*admin, true),
+                AccountMeta::new_readonly(Pubkey::from(program::id().to_bytes()), false),
+            ],
+            data,
+        }
+    }
+
+    /// 在 LiteSVM 中写入一个代币 mint 账户（82 字节基础布局）
+    fn set_mint_account(svm: &mut LiteSVM, token_program: &Pubkey, decimals: u8) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        let mut data = vec![0u8; 82];
+        data[44] = decimals;
+        data[45] = 1;
+        svm.set_account(
+            mint,
+            Account {
+                lamports: LAMPORTS_PER_SOL,
+                data,
+                owner: *token_program,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+        mint
+    }
+
+    /// 在 LiteSVM 中写入一个已初始化的代币账户（165 字节基础布局）
+    fn set_token_account(
+        svm: &mut LiteSVM,
+        token_program: &Pubkey,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        amount: u64,
+    ) -> Pubkey {
+        let address = Pubkey::new_unique();
+        let mut data = vec![0u8; 165];
+        data[0..32].copy_from_slice(mint.as_ref());
+        data[32..64].copy_from_slice(owner.as_ref());
+        data[64..72].copy_from_slice(&amount.to_le_bytes());
+        data[108] = 1;
+        svm.set_account(
+            address,
+            Account {
+                lamports: LAMPORTS_PER_SOL,
+                data,
+                owner: *token_program,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+        address
+    }
+
+    /// 测试白名单代币持仓计入资产因子
+    ///
+    /// # 测试场景
+    /// 1. 管理员将一个 SPL Token mint 和一个 Token-2022 mint 加入白名单
+    /// 2. 用户分别传入非白名单代币、他人代币账户、重复代币账户
+    /// 3. 用户传入两个合法的代币账户计算信用分
+    ///
+    /// # 验证点
+    /// - 非管理员不能修改白名单，非 mint 账户和超过 38 位精度的 mint 不能登记
+    /// - 非法持仓分别返回 MintNotAllowed、InvalidHoldingAccount、DuplicateHoldingAccount
+    /// - 合法持仓使分数高于仅凭钱包余额的分数
+    #[test]
+    fn test_token_holdings_raise_score() {
+        let (mut svm, admin) = setup_test_environment_with_admin();
+        let token_program = crate::holdings::TOKEN_PROGRAM_ID;
+        let token_2022_program = crate::holdings::TOKEN_2022_PROGRAM_ID;
+
+        let usdc = set_mint_account(&mut svm, &token_program, 6);
+        let other = set_mint_account(&mut svm, &token_2022_program, 9);
+        let unlisted = set_mint_account(&mut svm, &token_program, 6);
+
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let ix = set_allowed_mint_ix(&user.pubkey(), &usdc, 6, 5_000_000, 1_000_000);
+        assert_program_error(
+            send_ix(&mut svm, ix, &user),
+            IdentityScoreError::Unauthorized,
+        );
+
+        let ix = set_allowed_mint_ix(&admin.pubkey(), &usdc, 9, 5_000_000, 1_000_000);
+        assert_program_error(
+            send_ix(&mut svm, ix, &admin),
+            IdentityScoreError::MintNotAllowed,
+        );
+
+        // 代币账户第 44 字节恰好等于声明的精度，也不能当作 mint 登记
+        let mut token_data = vec![0u8; 165];
+        token_data[44] = 6;
+        token_data[45] = 1;
+        token_data[108] = 1;
+        let not_a_mint = Pubkey::new_unique();
+        svm.set_account(
+            not_a_mint,
+            Account {
+                lamports: LAMPORTS_PER_SOL,
+                data: token_data,
+                owner: token_program,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+        let ix = set_allowed_mint_ix(&admin.pubkey(), &not_a_mint, 6, 5_000_000, 1_000_000);
+        assert_program_error(
+            send_ix(&mut svm, ix, &admin),
+            IdentityScoreError::MintNotAllowed,
+        );
+
+        let huge_decimals = set_mint_account(&mut svm, &token_2022_program, 39);
+        let ix = set_allowed_mint_ix(&admin.pubkey(), &huge_decimals, 39, 5_000_000, 1_000_000);
+        assert_program_error(
+            send_ix(&mut svm, ix, &admin),
+            IdentityScoreError::MintNotAllowed,
+        );
+        let huge = AllowedMint {
+            mint: huge_decimals,
+            decimals: 39,
+            weight: u64::MAX,
+            usd_weight: u64::MAX,
+        };
+        assert_eq!(huge.value_of(u64::MAX), 0);
+        assert_eq!(huge.usd_value_of(u64::MAX), 0);
+
+        // 1 USDC ≈ 0.005 SOL 或 1 美元，1 个 other 代币 ≈ 1 SOL 或 150 美元
+        let ix = set_allowed_mint_ix(&admin.pubkey(), &usdc, 6, 5_000_000, 1_000_000);
+        send_ix(&mut svm, ix, &admin).unwrap();
+        let ix = set_allowed_mint_ix(&admin.pubkey(), &other, 9, LAMPORTS_PER_SOL, 150_000_000);
+        send_ix(&mut svm, ix, &admin).unwrap();
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        create_verified_identity(&mut svm, &user);
+
+        let usdc_account = set_token_account(
+            &mut svm,
+            &token_program,
+            &usdc,
+            &user.pubkey(),
+            2_000_000_000,
+        );
+        let other_account = set_token_account(
+            &mut svm,
+            &token_2022_program,
+            &other,
+            &user.pubkey(),
+            20 * LAMPORTS_PER_SOL,
+        );
+        let unlisted_account = set_token_account(
+            &mut svm,
+            &token_program,
+            &unlisted,
+            &user.pubkey(),
+            1_000_000,
+        );
+        let foreign_account = set_token_account(
+            &mut svm,
+            &token_program,
+            &usdc,
+            &Pubkey::new_unique(),
+            1_000_000,
+        );
+
+        let with_holdings = |pairs: &[(Pubkey, Pubkey)]| {
+            let mut ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+            for (token_account, mint) in pairs {
+                ix.accounts
+                    .push(AccountMeta::new_readonly(*token_account, false));
+                ix.accounts.push(AccountMeta::new_readonly(
+                    get_allowed_mint_pda(mint).0,
+                    false,
+                ));
+            }
+            ix
+        };
+
+        let ix = with_holdings(&[(unlisted_account, usdc)]);
+        assert_program_error(
+            send_ix(&mut svm, ix, &user),
+            IdentityScoreError::MintNotAllowed,
+        );
+
+        let ix = with_holdings(&[(foreign_account, usdc)]);
+        assert_program_error(
+            send_ix(&mut svm, ix, &user),
+            IdentityScoreError::InvalidHoldingAccount,
+        );
+
+        let ix = with_holdings(&[(usdc_account, usdc), (usdc_account, usdc)]);
+        assert_program_error(
+            send_ix(&mut svm, ix, &user),
+            IdentityScoreError::DuplicateHoldingAccount,
+        );
+
+        let ix = with_holdings(&[(usdc_account, usdc), (other_account, other)]);
+        send_ix(&mut svm, ix, &user).unwrap();
+
+        let account = svm.get_account(&score_pda).unwrap();
+        let mut data_slice = &account.data[..];
+        let score_state = CreditScoreAccount::try_deserialize(&mut data_slice).unwrap();
+
+        let identity_account = svm.get_account(&identity_pda).unwrap();
+        let mut data_slice = &identity_account.data[..];
+        let identity_state = IdentityAccount::try_deserialize(&mut data_slice).unwrap();
+        let now = svm.get_sysvar::<Clock>().unix_timestamp;
+        let (wallet_only, _) = fixed_score(
+            svm.get_balance(&user.pubkey()).unwrap(),
+            identity_state.created_at,
+            true,
+            owner_footprint_rent(&svm, &user.pubkey(), &[]),
+            now,
+        );
+        assert!(score_state.score > wallet_only);
+    }
+
+    /// 在 LiteSVM 中写入一个已委托的原生质押账户（200 字节 StakeStateV2 布局）
+    fn set_stake_account(
+        svm: &mut LiteSVM,
+        withdrawer: &Pubkey,
+        stake: u64,
+        activation_epoch: u64,
+        deactivation_epoch: u64,
+    ) -> Pubkey {
+        let address = Pubkey::new_unique();
+        let mut data = vec![0u8; 200];
+        data[0..4].copy_from_slice(&2u32.to_le_bytes());
+        data[12..44].copy_from_slice(withdrawer.as_ref());
+        data[44..76].copy_from_slice(withdrawer.as_ref());
+        data[124..156].copy_from_slice(Pubkey::new_unique().as_ref());
+        data[156..164].copy_from_slice(&stake.to_le_bytes());
+        data[164..172].copy_from_slice(&activation_epoch.to_le_bytes());
+        data[172..180].copy_from_slice(&deactivation_epoch.to_le_bytes());
+        svm.set_account(
+            address,
+            Account {
+                lamports: stake + 2_282_880,
+                data,
+                owner: crate::holdings::STAKE_PROGRAM_ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+        address
+    }
+
+    /// 测试原生质押账户计入资产与稳定性因子
+    ///
+    /// # 测试场景
+    /// 1. 用户传入提款权限属于他人的质押账户
+    /// 2. 用户传入正在解除委托的质押账户
+    /// 3. 用户传入两个自己可提款的质押账户计算信用分
+    ///
+    /// # 验证点
+    /// - 非法质押账户返回 InvalidHoldingAccount
+    /// - 分数与按质押数量和加权激活时长计算的定点分数一致
+    #[test]
+    fn test_stake_accounts_count_toward_score() {
+        let mut svm = setup_test_environment();
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.epoch = 200;
+        svm.set_sysvar::<Clock>(&clock);
+
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        create_verified_identity(&mut svm, &user);
+
+        let foreign = set_stake_account(
+            &mut svm,
+            &Pubkey::new_unique(),
+            100 * LAMPORTS_PER_SOL,
+            0,
+            u64::MAX,
+        );
+        let deactivating =
+            set_stake_account(&mut svm, &user.pubkey(), 100 * LAMPORTS_PER_SOL, 0, 199);
+        let old_stake = set_stake_account(
+            &mut svm,
+            &user.pubkey(),
+            30 * LAMPORTS_PER_SOL,
+            20,
+            u64::MAX,
+        );
+        let new_stake = set_stake_account(
+            &mut svm,
+            &user.pubkey(),
+            10 * LAMPORTS_PER_SOL,
+            180,
+            u64::MAX,
+        );
+
+        let with_stakes = |stakes: &[Pubkey]| {
+            let mut ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+            for stake in stakes {
+                ix.accounts.push(AccountMeta::new_readonly(*stake, false));
+            }
+            ix
+        };
+
+        for invalid in [foreign, deactivating] {
+            let ix = with_stakes(&[invalid]);
+            assert_program_error(
+                send_ix(&mut svm, ix, &user),
+                IdentityScoreError::InvalidHoldingAccount,
+            );
+        }
+
+        let ix = with_stakes(&[old_stake, new_stake]);
+        send_ix(&mut svm, ix, &user).unwrap();
+
+        let account = svm.get_account(&score_pda).unwrap();
+        let mut data_slice = &account.data[..];
+        let score_state = CreditScoreAccount::try_deserialize(&mut data_slice).unwrap();
+
+        let identity_account = svm.get_account(&identity_pda).unwrap();
+        let mut data_slice = &identity_account.data[..];
+        let identity_state = IdentityAccount::try_deserialize(&mut data_slice).unwrap();
+        let inputs = crate::scoring::ScoreInputs {
+            lamports: svm.get_balance(&user.pubkey()).unwrap(),
+            staked_lamports: 40 * LAMPORTS_PER_SOL,
+            // (30 * 180 + 10 * 20) / 40
+            stake_age_epochs: 140,
+            identity_created_at: identity_state.created_at,
+            is_verified: true,
+            footprint_rent: owner_footprint_rent(&svm, &user.pubkey(), &[old_stake, new_stake]),
+            current_timestamp: clock.unix_timestamp,
+            ..Default::default()
+        };
+        let (expected, _) =
+            crate::scoring::calculate_comprehensive_score(&inputs, &LevelCutoffs::default());
+        assert_eq!(score_state.score, expected);
+
+        let (wallet_only, _) = fixed_score(
+            inputs.lamports,
+            inputs.identity_created_at,
+            true,
+            owner_footprint_rent(&svm, &user.pubkey(), &[]),
+            inputs.current_timestamp,
+        );
+        assert!(score_state.score > wallet_only);
+    }
+
+    /// 写入模拟价格预言机账户（MOCK_ORACLE 布局），默认构建不接受该格式，
+    /// 因此直接写入其魔数
+    fn set_mock_oracle(
+        svm: &mut LiteSVM,
+        address: &Pubkey,
+        price: i64,
+        conf: u64,
+        expo: i32,
+        publish_time: i64,
+    ) {
+        let mut data = b"MOCKPRCE".to_vec();
+        data.extend_from_slice(&price.to_le_bytes());
+        data.extend_from_slice(&conf.to_le_bytes());
+        data.extend_from_slice(&expo.to_le_bytes());
+        data.extend_from_slice(&publish_time.to_le_bytes());
+        svm.set_account(
+            *address,
+            Account {
+                lamports: LAMPORTS_PER_SOL,
+                data,
+                owner: Pubkey::new_unique(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+    }
+
+    /// 写入 Pyth v2 价格账户，只填充读取到的字段
+    fn set_pyth_oracle(
+        svm: &mut LiteSVM,
+        address: &Pubkey,
+        price: i64,
+        conf: u64,
+        expo: i32,
+        timestamp: i64,
+    ) {
+        let mut data = vec![0u8; 3312];
+        data[0..4].copy_from_slice(&0xa1b2_c3d4u32.to_le_bytes());
+        data[4..8].copy_from_slice(&2u32.to_le_bytes());
+        data[8..12].copy_from_slice(&3u32.to_le_bytes());
+        data[20..24].copy_from_slice(&expo.to_le_bytes());
+        data[96..104].copy_from_slice(&timestamp.to_le_bytes());
+        data[208..216].copy_from_slice(&price.to_le_bytes());
+        data[216..224].copy_from_slice(&conf.to_le_bytes());
+        data[224..228].copy_from_slice(&1u32.to_le_bytes());
+        svm.set_account(
+            *address,
+            Account {
+                lamports: LAMPORTS_PER_SOL,
+                data,
+                owner: crate::oracle::PYTH_ORACLE_PROGRAM_ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+    }
+
+    /// 测试 lamports 到微美元的换算
+    #[test]
+    fn test_sol_price_conversion() {
+        use crate::oracle::SolPrice;
+
+        // $150.00000000，Pyth 常用的 expo = -8
+        let price = SolPrice {
+            price: 15_000_000_000,
+            expo: -8,
+        };
+        assert_eq!(price.lamports_to_micro_usd(LAMPORTS_PER_SOL), 150_000_000);
+        assert_eq!(price.lamports_to_micro_usd(1), 0);
+        assert_eq!(
+            price.lamports_to_micro_usd(u64::MAX),
+            (u64::MAX as u128 * 15 / 100) as u64
+        );
+
+        let whole_dollars = SolPrice {
+            price: 150,
+            expo: 0,
+        };
+        assert_eq!(
+            whole_dollars.lamports_to_micro_usd(LAMPORTS_PER_SOL),
+            150_000_000
+        );
+        let huge = SolPrice {
+            price: u64::MAX,
+            expo: 20,
+        };
+        assert_eq!(huge.lamports_to_micro_usd(LAMPORTS_PER_SOL), u64::MAX);
+    }
+
+    /// 测试启用价格后白名单代币按自身的美元权重估值
+    ///
+    /// # 验证点
+    /// - 代币的美元价值不随 SOL 价格变化，钱包余额仍随价格变化
+    /// - 启用价格后不再计入按 lamports 计的代币价值
+    /// - 未启用价格时按 lamports 计的代币价值照常计入
+    #[test]
+    fn test_token_usd_value_ignores_sol_price() {
+        use crate::oracle::SolPrice;
+        use crate::scoring::{calculate_score_breakdown, ScoreInputs};
+
+        let tokens_only = ScoreInputs {
+            token_value: 10 * LAMPORTS_PER_SOL,
+            token_usd_value: 500_000_000,
+            footprint_rent: 1,
+            ..Default::default()
+        };
+        let asset_score = |inputs: ScoreInputs, price: Option<u64>| {
+            calculate_score_breakdown(&ScoreInputs {
+                sol_price: price.map(|price| SolPrice { price, expo: -8 }),
+                ..inputs
+            })
+            .asset
+            .score
+        };
+        let (cheap, dear) = (Some(15_000_000_000), Some(30_000_000_000));
+
+        assert_eq!(
+            asset_score(tokens_only, cheap),
+            asset_score(tokens_only, dear)
+        );
+        let usd_only = ScoreInputs {
+            token_value: 0,
+            ..tokens_only
+        };
+        assert_eq!(
+            asset_score(tokens_only, cheap),
+            asset_score(usd_only, cheap)
+        );
+        assert!(asset_score(tokens_only, None) > asset_score(usd_only, None));
+
+        let with_wallet = ScoreInputs {
+            lamports: LAMPORTS_PER_SOL,
+            ..tokens_only
+        };
+        assert!(asset_score(with_wallet, dear) > asset_score(with_wallet, cheap));
+    }
+
+    /// 测试以美元计价的资产估值
+    ///
+    /// # 测试场景
+    /// 1. 管理员在配置中固定一个 Pyth 价格账户地址
+    /// 2. 用户不传、传错、传过期、传置信区间过宽的价格账户
+    /// 3. 固定地址上的账户改为模拟格式，或改由其他程序持有
+    /// 4. 用户用不同价格的 Pyth 价格账户计算信用分
+    ///
+    /// # 验证点
+    /// - 非法价格账户分别返回 InvalidPriceFeed、StalePriceFeed、PriceConfidenceTooWide
+    /// - 默认构建不接受模拟格式，也不接受非 Pyth 程序持有的价格账户
+    /// - 分数与按美元估值计算的定点分数一致
+    #[test]
+    fn test_price_oracle_usd_valuation() {
+        let (mut svm, admin) = setup_test_environment_with_admin();
+        let now = svm.get_sysvar::<Clock>().unix_timestamp;
+
+        let oracle = Pubkey::new_unique();
+        let params = ConfigParams {
+            price_oracle: oracle,
+            ..test_config_params()
+        };
+        let ix = config_ix("update_config", &admin.pubkey(), &params);
+        send_ix(&mut svm, ix, &admin).unwrap();
+
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), 2 * LAMPORTS_PER_SOL).unwrap();
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        create_verified_identity(&mut svm, &user);
+
+        let with_feed = |feed: Pubkey| {
+            let mut ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+            let last = ix.accounts.len() - 1;
+            ix.accounts[last] = AccountMeta::new_readonly(feed, false);
+            ix
+        };
+
+        let ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        assert_program_error(
+            send_ix(&mut svm, ix, &user),
+            IdentityScoreError::InvalidPriceFeed,
+        );
+
+        let impostor = Pubkey::new_unique();
+        set_pyth_oracle(&mut svm, &impostor, 15_000_000_000, 0, -8, now);
+        assert_program_error(
+            send_ix(&mut svm, with_feed(impostor), &user),
+            IdentityScoreError::InvalidPriceFeed,
+        );
+
+        set_mock_oracle(&mut svm, &oracle, 15_000_000_000, 0, -8, now);
+        assert_program_error(
+            send_ix(&mut svm, with_feed(oracle), &user),
+            IdentityScoreError::InvalidPriceFeed,
+        );
+
+        // 布局正确但不归 Pyth 程序所有的账户是伪造的
+        set_pyth_oracle(&mut svm, &oracle, 15_000_000_000, 0, -8, now);
+        let mut forged = svm.get_account(&oracle).unwrap();
+        forged.owner = Pubkey::new_unique();
+        svm.set_account(oracle, forged).unwrap();
+        svm.expire_blockhash();
+        assert_program_error(
+            send_ix(&mut svm, with_feed(oracle), &user),
+            IdentityScoreError::InvalidPriceFeed,
+        );
+
+        set_pyth_oracle(&mut svm, &oracle, 15_000_000_000, 0, -8, now - 61);
+        svm.expire_blockhash();
+        assert_program_error(
+            send_ix(&mut svm, with_feed(oracle), &user),
+            IdentityScoreError::StalePriceFeed,
+        );
+
+        // 置信区间 2%，超过 1% 上限
+        set_pyth_oracle(&mut svm, &oracle, 15_000_000_000, 300_000_000, -8, now);
+        svm.expire_blockhash();
+        assert_program_error(
+            send_ix(&mut svm, with_feed(oracle), &user),
+            IdentityScoreError::PriceConfidenceTooWide,
+        );
+
+        let expected_score = |svm: &LiteSVM, price: u64, expo: i32| {
+            let identity_account = svm.get_account(&identity_pda).unwrap();
+            let mut data_slice = &identity_account.data[..];
+            let identity_state = IdentityAccount::try_deserialize(&mut data_slice).unwrap();
+            crate::scoring::calculate_comprehensive_score(
+                &crate::scoring::ScoreInputs {
+                    lamports: svm.get_balance(&user.pubkey()).unwrap(),
+                    identity_created_at: identity_state.created_at,
+                    is_verified: true,
+                    footprint_rent: owner_footprint_rent(svm, &user.pubkey(), &[]),
+                    current_timestamp: now,
+                    sol_price: Some(crate::oracle::SolPrice { price, expo }),
+                    ..Default::default()
+                },
+                &LevelCutoffs::default(),
+            )
+            .0
+        };
+        let get_score = |svm: &LiteSVM| {
+            let account = svm.get_account(&score_pda).unwrap();
+            let mut data_slice = &account.data[..];
+            CreditScoreAccount::try_deserialize(&mut data_slice)
+                .unwrap()
+                .score
+        };
+
+        set_pyth_oracle(&mut svm, &oracle, 15_000_000_000, 1_000_000, -8, now);
+        svm.expire_blockhash();
+        send_ix(&mut svm, with_feed(oracle), &user).unwrap();
+        assert_eq!(get_score(&svm), expected_score(&svm, 15_000_000_000, -8));
+
+        // 价格上涨后，同样的余额得到更高的资产分
+        let cheap = get_score(&svm);
+        set_pyth_oracle(&mut svm, &oracle, 4_000_000, 100, -4, now);
+        svm.expire_blockhash();
+        send_ix(&mut svm, with_feed(oracle), &user).unwrap();
+        assert_eq!(get_score(&svm), expected_score(&svm, 4_000_000, -4));
+        assert!(get_score(&svm) > cheap);
+    }
+
+    /// 获取放贷程序登记账户的 PDA 地址
+    fn get_reporter_pda(program: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SEED_REPORTER, program.as_ref()], &PROGRAM_ID)
+    }
+
+    /// 获取放贷程序为某用户开立的未结清贷款账户 PDA 地址
+    fn get_reporter_loans_pda(owner: &Pubkey, program: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[SEED_REPORTER_LOANS, owner.as_ref(), program.as_ref()],
+            &PROGRAM_ID,
+        )
+    }
+
+    /// 构建登记放贷程序指令
+    fn add_reporter_ix(admin: &Pubkey, program: &Pubkey) -> Instruction {
+        let mut data = get_discriminator("add_reporter").to_vec();
+        data.extend_from_slice(program.as_ref());
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(get_reporter_pda(program).0, false),
+                AccountMeta::new_readonly(get_config_pda().0, false),
+                AccountMeta::new(*admin, true),
+                AccountMeta::new_readonly(Pubkey::from(program::id().to_bytes()), false),
+            ],
+            data,
+        }
+    }
+
+    /// 构建上报信用事件指令（正常情况下由放贷程序通过 CPI 调用）
+    fn report_credit_event_ix(
+        reporter_program: &Pubkey,
+        authority: &Pubkey,
+        owner: &Pubkey,
+        payer: &Pubkey,
+        kind: CreditEventKind,
+    ) -> Instruction {
+        let mut data = get_discriminator("report_credit_event").to_vec();
+        kind.serialize(&mut data).unwrap();
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(get_reporter_pda(reporter_program).0, false),
+                AccountMeta::new_readonly(*authority, true),
+                AccountMeta::new_readonly(get_identity_pda(owner).0, false),
+                AccountMeta::new(get_credit_history_pda(owner).0, false),
+                AccountMeta::new(*payer, true),
+                AccountMeta::new_readonly(Pubkey::from(program::id().to_bytes()), false),
+                AccountMeta::new(get_reporter_loans_pda(owner, reporter_program).0, false),
+            ],
+            data,
+        }
+    }
+
+    /// 登记放贷程序，并把上报权限改写为普通钱包，以便测试直接签名上报
+    fn add_test_reporter(svm: &mut LiteSVM, admin: &Keypair, program: &Pubkey) -> Keypair {
+        let ix = add_reporter_ix(&admin.pubkey(), program);
+        send_ix(svm, ix, admin).unwrap();
+
+        let authority = Keypair::new();
+        svm.airdrop(&authority.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        let reporter_pda = get_reporter_pda(program).0;
+        let account = svm.get_account(&reporter_pda).unwrap();
+        let mut data_slice = &account.data[..];
+        let mut reporter = Reporter::try_deserialize(&mut data_slice).unwrap();
+        reporter.authority = authority.pubkey();
+        set_program_account(svm, &reporter_pda, &reporter, Reporter::SPACE);
+        authority
+    }
+
+    /// 测试贷款只能由开立它的放贷程序结清
+    ///
+    /// # 测试场景
+    /// 1. 两个放贷程序登记为上报方
+    /// 2. 放贷程序 A 为用户开立一笔贷款
+    /// 3. 放贷程序 B 上报该用户违约，随后 A 上报按时还款
+    /// 4. A 再次上报结清
+    ///
+    /// # 验证点
+    /// - 没有自己开立的贷款时结清返回 NoOutstandingLoan，不影响还款记录
+    /// - 开立方可以结清，结清后未结清数归零
+    #[test]
+    fn test_loans_resolved_only_by_opener() {
+        let (mut svm, admin) = setup_test_environment_with_admin();
+        let lender_a = Pubkey::new_unique();
+        let lender_b = Pubkey::new_unique();
+        let authority_a = add_test_reporter(&mut svm, &admin, &lender_a);
+        let authority_b = add_test_reporter(&mut svm, &admin, &lender_b);
+
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
+        send_ix(&mut svm, create_ix, &user).unwrap();
+
+        let report_ix = |program: &Pubkey, authority: &Keypair, kind| {
+            report_credit_event_ix(
+                program,
+                &authority.pubkey(),
+                &user.pubkey(),
+                &authority.pubkey(),
+                kind,
+            )
+        };
+
+        let ix = report_ix(&lender_a, &authority_a, CreditEventKind::LoanOpened);
+        send_ix(&mut svm, ix, &authority_a).unwrap();
+
+        let ix = report_ix(&lender_b, &authority_b, CreditEventKind::Defaulted);
+        assert_program_error(
+            send_ix(&mut svm, ix, &authority_b),
+            IdentityScoreError::NoOutstandingLoan,
+        );
+
+        let ix = report_ix(&lender_a, &authority_a, CreditEventKind::RepaidOnTime);
+        send_ix(&mut svm, ix, &authority_a).unwrap();
+        svm.expire_blockhash();
+        let ix = report_ix(&lender_a, &authority_a, CreditEventKind::RepaidOnTime);
+        assert_program_error(
+            send_ix(&mut svm, ix, &authority_a),
+            IdentityScoreError::NoOutstandingLoan,
+        );
+
+        let account = svm
+            .get_account(&get_credit_history_pda(&user.pubkey()).0)
+            .unwrap();
+        let mut data_slice = &account.data[..];
+        let history = CreditHistory::try_deserialize(&mut data_slice).unwrap();
+        assert_eq!(history.loans_opened, 1);
+        assert_eq!(history.repaid_on_time, 1);
+        assert_eq!(history.defaulted, 0);
+
+        let account = svm
+            .get_account(&get_reporter_loans_pda(&user.pubkey(), &lender_a).0)
+            .unwrap();
+        let mut data_slice = &account.data[..];
+        let loans = ReporterLoans::try_deserialize(&mut data_slice).unwrap();
+        assert_eq!(loans.owner, user.pubkey());
+        assert_eq!(loans.reporter, lender_a);
+        assert_eq!(loans.open_loans, 0);
+    }
+
+    /// 测试放贷程序登记与上报权限
+    ///
+    /// # 测试场景
+    /// 1. 非管理员尝试登记放贷程序
+    /// 2. 管理员登记放贷程序
+    /// 3. 普通钱包冒充放贷程序上报信用事件
+    ///
+    /// # 验证点
+    /// - 只有管理员可以登记
+    /// - 登记记录了放贷程序派生的上报权限 PDA
+    /// - 上报必须由该 PDA 签名（即只能来自放贷程序的 CPI）
+    #[test]
+    fn test_reporter_registry_authorization() {
+        let (mut svm, admin) = setup_test_environment_with_admin();
+        let lender = Pubkey::new_unique();
+
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
+        send_ix(&mut svm, create_ix, &user).unwrap();
+
+        let ix = add_reporter_ix(&user.pubkey(), &lender);
+        assert_program_error(
+            send_ix(&mut svm, ix, &user),
+            IdentityScoreError::Unauthorized,
+        );
+
+        let ix = add_reporter_ix(&admin.pubkey(), &lender);
+        send_ix(&mut svm, ix, &admin).unwrap();
+
+        let account = svm.get_account(&get_reporter_pda(&lender).0).unwrap();
+        let mut data_slice = &account.data[..];
+        let reporter = Reporter::try_deserialize(&mut data_slice).unwrap();
+        assert_eq!(reporter.program, lender);
+        assert_eq!(
+            reporter.authority,
+            Pubkey::find_program_address(&[SEED_REPORTER_AUTHORITY], &lender).0
+        );
+
+        let ix = report_credit_event_ix(
+            &lender,
+            &user.pubkey(),
+            &user.pubkey(),
+            &user.pubkey(),
+            CreditEventKind::RepaidOnTime,
+        );
+        assert_program_error(
+            send_ix(&mut svm, ix, &user),
+            IdentityScoreError::Unauthorized,
+        );
+        assert!(svm
+            .get_account(&get_credit_history_pda(&user.pubkey()).0)
+            .is_none());
+    }
+
+    /// 测试还款记录因子
+    ///
+    /// # 验证点
+    /// - 没有已结清贷款时不计入还款因子，分数与原公式一致
+    /// - 按时还款提高分数，违约降低分数
+    /// - 只有存在未结清贷款时才能上报结清事件
+    #[test]
+    fn test_repayment_factor() {
+        use crate::scoring::{calculate_comprehensive_score, RepaymentRecord, ScoreInputs};
+
+        let now = 1_700_000_000;
+        let base = ScoreInputs {
+            lamports: LAMPORTS_PER_SOL,
+            identity_created_at: now - 30 * 24 * 60 * 60,
+            is_verified: true,
+            footprint_rent: 100_000_000,
+            current_timestamp: now,
+            ..Default::default()
+        };
+        let with_record = |on_time, late, defaulted| {
+            calculate_comprehensive_score(
+                &ScoreInputs {
+                    repayment: Some(RepaymentRecord {
+                        on_time,
+                        late,
+                        defaulted,
+                    }),
+                    ..base
+                },
+                &LevelCutoffs::default(),
+            )
+            .0
+        };
+
+        let (plain, _) = calculate_comprehensive_score(&base, &LevelCutoffs::default());
+        assert!(with_record(5, 0, 0) > plain);
+        assert!(with_record(0, 0, 2) < plain);
+        assert!(with_record(3, 1, 0) < with_record(4, 0, 0));
+        assert!(with_record(3, 1, 0) > with_record(3, 0, 1));
+
+        let mut history = CreditHistory {
+            owner: Pubkey::new_unique(),
+            loans_opened: 0,
+            repaid_on_time: 0,
+            repaid_late: 0,
+            defaulted: 0,
+            last_event_at: 0,
+        };
+        assert_eq!(history.outstanding_loans(), 0);
+        history.record(CreditEventKind::LoanOpened);
+        assert_eq!(history.repayment_record(), None);
+        history.record(CreditEventKind::LoanOpened);
+        history.record(CreditEventKind::RepaidLate);
+        assert_eq!(history.outstanding_loans(), 1);
+        assert_eq!(
+            history.repayment_record(),
+            Some(RepaymentRecord {
+                on_time: 0,
+                late: 1,
+                defaulted: 0
+            })
+        );
+    }
+
+    /// 获取处罚账户的 PDA 地址
+    fn get_penalty_pda(owner: &Pubkey, index: u32) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[SEED_PENALTY, owner.as_ref(), &index.to_le_bytes()],
+            &PROGRAM_ID,
+        )
+    }
+
+    /// 把程序账户直接写入 LiteSVM，模拟放贷程序已经通过 CPI 写入的状态
+    fn set_program_account<T: AccountSerialize>(
+        svm: &mut LiteSVM,
+        address: &Pubkey,
+        state: &T,
+        space: usize,
+    ) {
+        let mut data = Vec::with_capacity(space);
+        state.try_serialize(&mut data).unwrap();
+        data.resize(space, 0);
+        svm.set_account(
+            *address,
+            Account {
+                lamports: LAMPORTS_PER_SOL,
+                data,
+                owner: PROGRAM_ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+    }
+
+    /// 构建关闭处罚指令
+    fn close_penalty_ix(
+        owner: &Pubkey,
+        index: u32,
+        payer: &Pubkey,
+        closer: &Pubkey,
+    ) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(get_penalty_pda(owner, index).0, false),
+                AccountMeta::new(get_penalty_index_pda(owner).0, false),
+                AccountMeta::new(*payer, false),
+                AccountMeta::new_readonly(*closer, true),
+            ],
+            data: get_discriminator("close_penalty").to_vec(),
+        }
+    }
+
+    /// 测试处罚效果的叠加与过期
+    #[test]
+    fn test_penalty_effect() {
+        use crate::scoring::{apply_penalty_effect, PenaltyEffect};
+
+        let cutoffs = LevelCutoffs::default();
+        let now = 1_700_000_000;
+        let mut index = PenaltyIndex {
+            owner: Pubkey::new_unique(),
+            next_index: 3,
+            open: vec![
+                OpenPenalty {
+                    index: 0,
+                    severity: PenaltySeverity::Minor,
+                    expires_at: now + 10,
+                },
+                OpenPenalty {
+                    index: 1,
+                    severity: PenaltySeverity::Major,
+                    expires_at: now + 20,
+                },
+                OpenPenalty {
+                    index: 2,
+                    severity: PenaltySeverity::Severe,
+                    expires_at: now,
+                },
+            ],
+        };
+
+        assert_eq!(
+            index.effect(now),
+            PenaltyEffect {
+                points: 20,
+                level_cap: Some(ScoreLevel::VeryGood),
+            }
+        );
+        assert_eq!(
+            apply_penalty_effect(85, index.effect(now), &cutoffs),
+            (65, ScoreLevel::VeryGood)
+        );
+        assert_eq!(
+            apply_penalty_effect(95, index.effect(now), &cutoffs),
+            (69, ScoreLevel::VeryGood)
+        );
+        assert_eq!(
+            apply_penalty_effect(80, PenaltySeverity::Severe.effect(), &cutoffs),
+            (49, ScoreLevel::Fair)
+        );
+        assert_eq!(
+            apply_penalty_effect(50, PenaltySeverity::Severe.effect(), &cutoffs),
+            (30, ScoreLevel::Poor)
+        );
+        assert_eq!(
+            apply_penalty_effect(72, PenaltyEffect::default(), &cutoffs),
+            (72, ScoreLevel::Excellent)
+        );
+
+        index.prune_expired(now + 10);
+        assert_eq!(index.open.len(), 1);
+        assert!(index.has_active(now + 19));
+        assert!(!index.has_active(now + 20));
+    }
+
+    /// 测试读取信用分时按当前处罚重新扣分
+    ///
+    /// # 测试场景
+    /// 1. 构造一个计算时没有处罚、基础分为 85 的信用分账户
+    /// 2. 分别按新增的严重处罚、墓碑扣分和处罚解除读取
+    /// 3. 账户过期后再读取
+    ///
+    /// # 验证点
+    /// - 计算之后新增的处罚立即压低分数和等级
+    /// - 计算时已有、之后解除的处罚不再扣分
+    /// - 过期后处罚后的分数同样衰减到 SCORE_MIN
+    #[test]
+    fn test_penalized_score_reads_current_penalties() {
+        use crate::scoring::{PenaltyEffect, SCORE_MIN};
+
+        let cutoffs = LevelCutoffs::default();
+        let now = 1_700_000_000;
+        let score_state = CreditScoreAccount {
+            identity: Pubkey::new_unique(),
+            score: 70,
+            score_level: ScoreLevel::Excellent,
+            calculated_at: now,
+            expires_at: now + 3600,
+            model_version: 1,
+            breakdown: ScoreBreakdown {
+                base_score: 85,
+                penalty_deduction: 15,
+                ..ScoreBreakdown::default()
+            },
+        };
+
+        let severe = PenaltySeverity::Severe.effect();
+        assert_eq!(score_state.penalized_score(now, 0, severe, &cutoffs), 49);
+        assert_eq!(
+            score_state.penalized_level(now, 0, severe, &cutoffs),
+            ScoreLevel::Fair
+        );
+        assert_eq!(
+            score_state.penalized_score(now, 10, PenaltyEffect::default(), &cutoffs),
+            75
+        );
+        assert_eq!(
+            score_state.penalized_score(now, 0, PenaltyEffect::default(), &cutoffs),
+            85
+        );
+
+        let decayed = score_state.penalized_score(
+            score_state.expires_at + SCORE_DECAY_SECONDS / 2,
+            0,
+            severe,
+            &cutoffs,
+        );
+        assert!(decayed < 49 && decayed > SCORE_MIN);
+        assert_eq!(
+            score_state.penalized_score(
+                score_state.expires_at + SCORE_DECAY_SECONDS,
+                0,
+                severe,
+                &cutoffs
+            ),
+            SCORE_MIN
+        );
+    }
+
+    /// 测试有效处罚压低信用分并阻止转移
+    ///
+    /// # 测试场景
+    /// 1. 模拟放贷程序给用户写入一条严重处罚
+    /// 2. 用户计算信用分，并尝试转移身份
+    /// 3. 处罚过期前由他人关闭，过期后关闭并重新计算
+    ///
+    /// # 验证点
+    /// - 严重处罚把等级压到 Low
+    /// - 有效处罚期间转移返回 ActivePenaltiesExist
+    /// - 只有发放者能提前关闭，过期后任何人可以关闭并退还租金
+    /// - 处罚关闭后分数恢复
+    #[test]
+    fn test_active_penalty_lowers_score() {
+        let mut svm = setup_test_environment();
+        let now = svm.get_sysvar::<Clock>().unix_timestamp;
+
+        let user = Keypair::new();
+        let recipient = Keypair::new();
+        let rent_payer = Pubkey::new_unique();
+        svm.airdrop(&user.pubkey(), 15 * LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&recipient.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        create_verified_identity(&mut svm, &user);
+
+        let lender = Pubkey::new_unique();
+        let expires_at = now + 3600;
+        set_program_account(
+            &mut svm,
+            &get_penalty_index_pda(&user.pubkey()).0,
+            &PenaltyIndex {
+                owner: user.pubkey(),
+                next_index: 1,
+                open: vec![OpenPenalty {
+                    index: 0,
+                    severity: PenaltySeverity::Severe,
+                    expires_at,
+                }],
+            },
+            PenaltyIndex::SPACE,
+        );
+        set_program_account(
+            &mut svm,
+            &get_penalty_pda(&user.pubkey(), 0).0,
+            &Penalty {
+                owner: user.pubkey(),
+                index: 0,
+                reporter: lender,
+                severity: PenaltySeverity::Severe,
+                reason: "loan 42 defaulted".to_string(),
+                created_at: now,
+                expires_at,
+                payer: rent_payer,
+            },
+            Penalty::SPACE,
+        );
+
+        let get_score = |svm: &LiteSVM| {
+            let account = svm.get_account(&score_pda).unwrap();
+            let mut data_slice = &account.data[..];
+            CreditScoreAccount::try_deserialize(&mut data_slice).unwrap()
+        };
+
+        let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        send_ix(&mut svm, calc_ix, &user).unwrap();
+        let penalized = get_score(&svm);
+        assert!(penalized.score_level <= ScoreLevel::Fair);
+        assert!(penalized.score <= 49);
+
+        let (transfer_request_pda, _) =
+            get_transfer_request_pda(&user.pubkey(), &recipient.pubkey());
+        let initiate_ix = initiate_transfer_ix(
+            &user.pubkey(),
+            &identity_pda,
+            &transfer_request_pda,
+            &recipient.pubkey(),
+        );
+        send_ix(&mut svm, initiate_ix, &user).unwrap();
+        let claim_ix = claim_transfer_ix(
+            &user.pubkey(),
+            &recipient.pubkey(),
+            &identity_pda,
+            &get_identity_pda(&recipient.pubkey()).0,
+            &transfer_request_pda,
+            &score_pda,
+            &get_score_pda(&recipient.pubkey()).0,
+        );
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[claim_ix],
+            Some(&recipient.pubkey()),
+            &[&recipient, &user],
+            blockhash,
+        );
+        let result = svm
+            .send_transaction(tx)
+            .map(|_| ())
+            .map_err(|e| format!("{:?}", e.err));
+        assert_program_error(result, IdentityScoreError::ActivePenaltiesExist);
+
+        let close_ix = close_penalty_ix(&user.pubkey(), 0, &rent_payer, &user.pubkey());
+        assert_program_error(
+            send_ix(&mut svm, close_ix, &user),
+            IdentityScoreError::PenaltyStillActive,
+        );
+
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = expires_at;
+        svm.set_sysvar::<Clock>(&clock);
+
+        let close_ix = close_penalty_ix(&user.pubkey(), 0, &rent_payer, &user.pubkey());
+        send_ix(&mut svm, close_ix, &user).unwrap();
+        assert!(svm
+            .get_account(&get_penalty_pda(&user.pubkey(), 0).0)
+            .is_none());
+        assert!(svm.get_balance(&rent_payer).unwrap() >= LAMPORTS_PER_SOL);
+
+        let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        send_ix(&mut svm, calc_ix, &user).unwrap();
+        assert!(get_score(&svm).score > penalized.score);
+    }
+
+    /// 构建管理员评分模型指令（创建、启用、退役）
+    fn score_model_ix(
+        name: &str,
+        admin: &Pubkey,
+        version: u32,
+        params: Option<ModelParams>,
+    ) -> Instruction {
+        let mut data = get_discriminator(name).to_vec();
+        let mut accounts = vec![
+            AccountMeta::new(get_score_model_pda(version).0, false),
+            AccountMeta::new(get_config_pda().0, false),
+            AccountMeta::new(*admin, true),
+        ];
+        if let Some(params) = params {
+            version.serialize(&mut data).unwrap();
+            params.serialize(&mut data).unwrap();
+            accounts.push(AccountMeta::new_readonly(
+                Pubkey::from(program::id().to_bytes()),
+                false,
+            ));
+        }
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data,
+        }
+    }
+
+    /// 取出 Anchor 错误的错误码
+    fn anchor_error_code(error: anchor_lang::error::Error) -> u32 {
+        match error {
+            anchor_lang::error::Error::AnchorError(error) => error.error_code_number,
+            anchor_lang::error::Error::ProgramError(error) => panic!("unexpected {:?}", error),
+        }
+    }
+
+    /// 测试评分模型版本管理
+    ///
+    /// # 测试场景
+    /// 1. 管理员创建权重之和不正确的模型，非管理员创建模型
+    /// 2. 用户用初始模型计算信用分
+    /// 3. 管理员启用新模型并退役旧模型
+    /// 4. 用户用新模型重新计算信用分
+    ///
+    /// # 验证点
+    /// - 非法参数返回 InvalidModelParams，非管理员返回 Unauthorized
+    /// - 正在使用的模型不能退役
+    /// - 分数、历史和事件记录了模型版本
+    /// - 旧模型退役后其分数视为过期，消费者可以要求最低模型版本
+    #[test]
+    fn test_score_model_versions() {
+        let (mut svm, admin) = setup_test_environment_with_admin();
+
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), 15 * LAMPORTS_PER_SOL).unwrap();
+
+        let invalid = ModelParams {
+            weight_asset: 5_000,
+            ..ModelParams::default()
+        };
+        let ix = score_model_ix("create_score_model", &admin.pubkey(), 2, Some(invalid));
+        assert_program_error(
+            send_ix(&mut svm, ix, &admin),
+            IdentityScoreError::InvalidModelParams,
+        );
+
+        let v2_params = ModelParams {
+            weight_asset: 2_000,
+            weight_stability: 5_000,
+            ..ModelParams::default()
+        };
+        let ix = score_model_ix("create_score_model", &user.pubkey(), 2, Some(v2_params));
+        assert_program_error(
+            send_ix(&mut svm, ix, &user),
+            IdentityScoreError::Unauthorized,
+        );
+        let ix = score_model_ix("create_score_model", &admin.pubkey(), 2, Some(v2_params));
+        send_ix(&mut svm, ix, &admin).unwrap();
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        create_scored_identity(&mut svm, &user);
+
+        let get_score = |svm: &LiteSVM| {
+            let account = svm.get_account(&score_pda).unwrap();
+            let mut data_slice = &account.data[..];
+            CreditScoreAccount::try_deserialize(&mut data_slice).unwrap()
+        };
+        let get_model = |svm: &LiteSVM, version: u32| {
+            let account = svm.get_account(&get_score_model_pda(version).0).unwrap();
+            let mut data_slice = &account.data[..];
+            ScoreModel::try_deserialize(&mut data_slice).unwrap()
+        };
+        assert_eq!(get_score(&svm).model_version, 1);
+
+        let ix = score_model_ix("retire_score_model", &admin.pubkey(), 1, None);
+        assert_program_error(
+            send_ix(&mut svm, ix, &admin),
+            IdentityScoreError::ScoreModelInUse,
+        );
+
+        let ix = score_model_ix("activate_score_model", &admin.pubkey(), 2, None);
+        send_ix(&mut svm, ix, &admin).unwrap();
+        let ix = score_model_ix("retire_score_model", &admin.pubkey(), 1, None);
+        send_ix(&mut svm, ix, &admin).unwrap();
+
+        let v1 = get_model(&svm, 1);
+        assert!(!v1.active);
+        assert!(v1.retired_at.is_some());
+        assert_eq!(
+            anchor_error_code(get_score(&svm).check_model(&v1, 1).unwrap_err()),
+            u32::from(IdentityScoreError::ScoreModelRetired)
+        );
+
+        let mut calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        let model_position = calc_ix.accounts.len() - 2;
+        calc_ix.accounts[model_position] =
+            AccountMeta::new_readonly(get_score_model_pda(2).0, false);
+        send_ix(&mut svm, calc_ix, &user).unwrap();
+
+        let score = get_score(&svm);
+        let v2 = get_model(&svm, 2);
+        assert_eq!(score.model_version, 2);
+        assert_eq!(
+            get_score_history(&svm, &user.pubkey())
+                .iter_newest_first()
+                .next()
+                .unwrap()
+                .model_version,
+            2
+        );
+        assert!(score.check_model(&v2, 2).is_ok());
+        assert_eq!(
+            anchor_error_code(score.check_model(&v2, 3).unwrap_err()),
+            u32::from(IdentityScoreError::ScoreModelTooOld)
+        );
+        assert_eq!(
+            anchor_error_code(score.check_model(&v1, 1).unwrap_err()),
+            u32::from(IdentityScoreError::ScoreModelMismatch)
+        );
+    }
+
+    /// 测试信用分组成明细
+    ///
+    /// # 验证点
+    /// - 明细的加权平均与综合分数一致
+    /// - 每个因子记录了模型中的权重，没有还款记录时还款因子权重为 0
+    /// - 链上分数账户保存了明细，且基础分减去处罚扣分等于最终分数
+    #[test]
+    fn test_score_breakdown() {
+        use crate::scoring::{
+            calculate_comprehensive_score, calculate_score_breakdown, RepaymentRecord, ScoreInputs,
+        };
+
+        let now = 1_700_000_000;
+        let inputs = ScoreInputs {
+            lamports: 3 * LAMPORTS_PER_SOL,
+            identity_created_at: now - 100 * 24 * 60 * 60,
+            is_verified: true,
+            footprint_rent: 100_000_000,
+            current_timestamp: now,
+            ..Default::default()
+        };
+
+        let breakdown = calculate_score_breakdown(&inputs);
+        let (score, _) = calculate_comprehensive_score(&inputs, &LevelCutoffs::default());
+        assert_eq!(breakdown.base_score, score);
+        assert_eq!(breakdown.asset.weight_bps, 4_000);
+        assert_eq!(breakdown.stability.weight_bps, 3_000);
+        assert_eq!(breakdown.rent_efficiency.weight_bps, 2_000);
+        assert_eq!(breakdown.verification.weight_bps, 1_000);
+        assert_eq!(breakdown.verification.score, 90);
+        assert_eq!(breakdown.activity.weight_bps, 0);
+        assert_eq!(breakdown.repayment.weight_bps, 0);
+        assert_eq!(breakdown.penalty_deduction, 0);
+
+        let weighted = [
+            breakdown.asset,
+            breakdown.stability,
+            breakdown.rent_efficiency,
+            breakdown.verification,
+        ]
+        .iter()
+        .map(|c| c.score as u32 * c.weight_bps as u32)
+        .sum::<u32>();
+        assert_eq!(breakdown.base_score as u32, weighted / 10_000);
+
+        let with_repayment = calculate_score_breakdown(&ScoreInputs {
+            repayment: Some(RepaymentRecord {
+                on_time: 4,
+                late: 0,
+                defaulted: 0,
+            }),
+            ..inputs
+        });
+        assert_eq!(with_repayment.repayment.weight_bps, 2_500);
+        assert_eq!(with_repayment.repayment.score, 90);
+        assert_eq!(with_repayment.asset, breakdown.asset);
+
+        let mut svm = setup_test_environment();
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), 15 * LAMPORTS_PER_SOL).unwrap();
+
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        create_scored_identity(&mut svm, &user);
+
+        let account = svm.get_account(&score_pda).unwrap();
+        let mut data_slice = &account.data[..];
+        let score_state = CreditScoreAccount::try_deserialize(&mut data_slice).unwrap();
+        assert_eq!(score_state.breakdown.asset.weight_bps, 4_000);
+        assert_eq!(
+            score_state.breakdown.base_score - score_state.breakdown.penalty_deduction,
+            score_state.score
+        );
+    }
+
+    /// 构建预览信用分指令
+    fn preview_score_ix(owner: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(get_identity_pda(owner).0, false),
+                AccountMeta::new_readonly(*owner, false),
+                AccountMeta::new_readonly(get_config_pda().0, false),
+                AccountMeta::new_readonly(get_credit_history_pda(owner).0, false),
+                AccountMeta::new_readonly(get_penalty_index_pda(owner).0, false),
+                AccountMeta::new_readonly(get_balance_history_pda(owner).0, false),
+                AccountMeta::new_readonly(get_activity_pda(owner).0, false),
+                AccountMeta::new_readonly(get_holding_registry_pda(owner).0, false),
+                AccountMeta::new_readonly(get_tip_vault_pda(owner).0, false),
+                AccountMeta::new_readonly(get_score_model_pda(1).0, false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+            ],
+            data: get_discriminator("preview_score").to_vec(),
+        }
+    }
+
+    /// 模拟执行预览指令并解析返回数据
+    fn simulate_preview(svm: &LiteSVM, owner: &Pubkey, payer: &Keypair) -> ScorePreview {
+        let tx = Transaction::new_signed_with_payer(
+            &[preview_score_ix(owner)],
+            Some(&payer.pubkey()),
+            &[payer],
+            svm.latest_blockhash(),
+        );
+        let info = svm.simulate_transaction(tx).unwrap();
+        assert_eq!(info.meta.return_data.program_id, PROGRAM_ID);
+        ScorePreview::try_from_slice(&info.meta.return_data.data).unwrap()
+    }
+
+    /// 测试只读的信用分预览
+    ///
+    /// # 测试场景
+    /// 1. 用户创建并验证身份后，模拟执行 preview_score
+    /// 2. 用户计算信用分后，由另一个付款人再次模拟预览
+    ///
+    /// # 验证点
+    /// - 预览通过返回数据给出分数、等级和明细
+    /// - 预览不创建信用分账户，也不修改统计
+    /// - 余额不变时预览结果与链上计算结果一致
+    #[test]
+    fn test_preview_score_is_read_only() {
+        let (mut svm, admin) = setup_test_environment_with_admin();
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), 15 * LAMPORTS_PER_SOL).unwrap();
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        create_verified_identity(&mut svm, &user);
+
+        let preview = simulate_preview(&svm, &user.pubkey(), &user);
+        assert_eq!(preview.model_version, 1);
+        assert_eq!(preview.score, preview.breakdown.base_score);
+        assert!(svm.get_account(&score_pda).is_none());
+        assert_eq!(get_stats(&svm).scores_computed, 0);
+
+        let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        send_ix(&mut svm, calc_ix, &user).unwrap();
+
+        let account = svm.get_account(&score_pda).unwrap();
+        let mut data_slice = &account.data[..];
+        let score_state = CreditScoreAccount::try_deserialize(&mut data_slice).unwrap();
+
+        let preview = simulate_preview(&svm, &user.pubkey(), &admin);
+        assert_eq!(preview.score, score_state.score);
+        assert_eq!(preview.score_level, score_state.score_level);
+        assert_eq!(preview.breakdown, score_state.breakdown);
+        assert_eq!(get_stats(&svm).scores_computed, 1);
+    }
+
+    /// 构建信用分门槛检查指令
+    fn assert_min_score_ix(
+        owner: &Pubkey,
+        min_score: u8,
+        max_age_seconds: i64,
+        min_model_version: u32,
+    ) -> Instruction {
+        let mut data = get_discriminator("assert_min_score").to_vec();
+        data.push(min_score);
+        data.extend_from_slice(&max_age_seconds.to_le_bytes());
+        data.extend_from_slice(&min_model_version.to_le_bytes());
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(get_identity_pda(owner).0, false),
+                AccountMeta::new_readonly(*owner, true),
+                AccountMeta::new_readonly(get_score_pda(owner).0, false),
+                AccountMeta::new_readonly(get_score_model_pda(1).0, false),
+                AccountMeta::new_readonly(get_config_pda().0, false),
+                AccountMeta::new_readonly(get_penalty_index_pda(owner).0, false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+            ],
+            data,
+        }
+    }
+
+    /// 测试用户签名调用的信用分门槛检查
+    ///
+    /// # 测试场景
+    /// 1. 用户计算信用分后，用不同门槛签名调用 assert_min_score
+    /// 2. 他人不经授权直接调用
+    /// 3. 模拟放贷程序写入一条严重处罚后再次检查
+    /// 4. 时间推进后检查分数新鲜度
+    /// 5. 用户取消验证后再次检查
+    ///
+    /// # 验证点
+    /// - 满足门槛时成功，不写入任何账户
+    /// - 用户未签名且没有授权时返回 ConsentRequired
+    /// - 分数不足、分数过旧、模型版本过低、身份未验证分别返回对应错误
+    /// - 计算之后新增的处罚在门槛检查时立即扣分
+    #[test]
+    fn test_assert_min_score_gate() {
+        let (mut svm, admin) = setup_test_environment_with_admin();
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), 15 * LAMPORTS_PER_SOL).unwrap();
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        create_scored_identity(&mut svm, &user);
+
+        let account = svm.get_account(&score_pda).unwrap();
+        let mut data_slice = &account.data[..];
+        let score = CreditScoreAccount::try_deserialize(&mut data_slice)
+            .unwrap()
+            .score;
+        let day = 24 * 60 * 60;
+
+        let ix = assert_min_score_ix(&user.pubkey(), score, day, 1);
+        send_ix(&mut svm, ix, &user).unwrap();
+        assert_eq!(svm.get_account(&score_pda).unwrap(), account);
+
+        let mut ix = assert_min_score_ix(&user.pubkey(), 0, day, 1);
+        ix.accounts[1].is_signer = false;
+        assert_program_error(
+            send_ix(&mut svm, ix, &admin),
+            IdentityScoreError::ConsentRequired,
+        );
+
+        let ix = assert_min_score_ix(&user.pubkey(), score + 1, day, 1);
+        assert_program_error(
+            send_ix(&mut svm, ix, &user),
+            IdentityScoreError::ScoreBelowThreshold,
+        );
+
+        let ix = assert_min_score_ix(&user.pubkey(), score, day, 2);
+        assert_program_error(
+            send_ix(&mut svm, ix, &user),
+            IdentityScoreError::ScoreModelTooOld,
+        );
+
+        // 计算之后新增的严重处罚立即生效，无需重新计算
+        let now = svm.get_sysvar::<Clock>().unix_timestamp;
+        let score_state = CreditScoreAccount::try_deserialize(&mut &account.data[..]).unwrap();
+        let penalized = score_state.penalized_score(
+            now,
+            0,
+            PenaltySeverity::Severe.effect(),
+            &LevelCutoffs::default(),
+        );
+        assert!(penalized < score);
+        set_program_account(
+            &mut svm,
+            &get_penalty_index_pda(&user.pubkey()).0,
+            &PenaltyIndex {
+                owner: user.pubkey(),
+                next_index: 1,
+                open: vec![OpenPenalty {
+                    index: 0,
+                    severity: PenaltySeverity::Severe,
+                    expires_at: now + 60,
+                }],
+            },
+            PenaltyIndex::SPACE,
+        );
+        let ix = assert_min_score_ix(&user.pubkey(), score, day, 1);
+        assert_program_error(
+            send_ix(&mut svm, ix, &user),
+            IdentityScoreError::ScoreBelowThreshold,
+        );
+        let ix = assert_min_score_ix(&user.pubkey(), penalized, day, 1);
+        send_ix(&mut svm, ix, &user).unwrap();
+
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += day + 1;
+        svm.set_sysvar::<Clock>(&clock);
+
+        let ix = assert_min_score_ix(&user.pubkey(), score, day, 1);
+        assert_program_error(
+            send_ix(&mut svm, ix, &user),
+            IdentityScoreError::ScoreTooOld,
+        );
+
+        let unverify_ix = unverify_identity_ix(&user.pubkey(), &identity_pda);
+        send_ix(&mut svm, unverify_ix, &user).unwrap();
+        let ix = assert_min_score_ix(&user.pubkey(), 0, 2 * day, 1);
+        assert_program_error(
+            send_ix(&mut svm, ix, &user),
+            IdentityScoreError::IdentityNotVerified,
+        );
+    }
+
+    /// 测试五级信用等级的分界与显示
+    #[test]
+    fn test_level_cutoffs() {
+        let cutoffs = LevelCutoffs::default();
+        assert!(cutoffs.is_valid());
+
+        let levels = [
+            (0, ScoreLevel::Poor),
+            (39, ScoreLevel::Poor),
+            (40, ScoreLevel::Fair),
+            (49, ScoreLevel::Fair),
+            (50, ScoreLevel::Good),
+            (60, ScoreLevel::VeryGood),
+            (69, ScoreLevel::VeryGood),
+            (70, ScoreLevel::Excellent),
+            (u8::MAX, ScoreLevel::Excellent),
+        ];
+        for (score, level) in levels {
+            assert_eq!(cutoffs.level(score), level, "score {}", score);
+            assert!(score <= cutoffs.ceiling(level));
+        }
+        assert_eq!(cutoffs.ceiling(ScoreLevel::Fair), 49);
+        assert_eq!(cutoffs.ceiling(ScoreLevel::VeryGood), 69);
+        assert!(ScoreLevel::Poor < ScoreLevel::Excellent);
+        assert_eq!(ScoreLevel::VeryGood.to_string(), "Very Good");
+
+        let unordered = LevelCutoffs {
+            good: 60,
+            very_good: 60,
+            ..cutoffs
+        };
+        assert!(!unordered.is_valid());
+        assert!(!LevelCutoffs { fair: 0, ..cutoffs }.is_valid());
+    }
+
+    /// 构建单个信用分等级重新分类指令
+    fn migrate_score_level_ix(owner: &Pubkey, payer: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(get_score_pda(owner).0, false),
+                AccountMeta::new(get_score_history_pda(owner).0, false),
+                AccountMeta::new_readonly(*owner, false),
+                AccountMeta::new(get_stats_pda().0, false),
+                AccountMeta::new_readonly(get_config_pda().0, false),
+                AccountMeta::new(*payer, true),
+                AccountMeta::new_readonly(Pubkey::from(program::id().to_bytes()), false),
+            ],
+            data: get_discriminator("migrate_score_level").to_vec(),
+        }
+    }
+
+    /// 构建身份账户布局迁移指令
+    fn migrate_identity_ix(owner: &Pubkey, payer: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(get_identity_pda(owner).0, false),
+                AccountMeta::new_readonly(*owner, false),
+                AccountMeta::new(*payer, true),
+                AccountMeta::new_readonly(Pubkey::from(program::id().to_bytes()), false),
+                AccountMeta::new(get_stats_pda().0, false),
+            ],
+            data: get_discriminator("migrate_identity").to_vec(),
+        }
+    }
+
+    /// 写入最初版本布局的账户，租金按旧大小计算
+    fn set_legacy_account(svm: &mut LiteSVM, address: &Pubkey, data: Vec<u8>) {
+        let lamports = svm.minimum_balance_for_rent_exemption(data.len());
+        svm.set_account(
+            *address,
+            Account {
+                lamports,
+                data,
+                owner: PROGRAM_ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+    }
+
+    /// 测试最初版本的身份和信用分账户迁移到当前布局
+    ///
+    /// # 测试场景
+    /// 1. 写入 58 字节的已验证身份和 50 字节的信用分，与最初版本的布局一致
+    /// 2. 直接计算信用分
+    /// 3. 第三方支付租金迁移身份和信用分，并重复迁移身份
+    /// 4. 迁移后计算信用分
+    ///
+    /// # 验证点
+    /// - 未迁移的账户无法计算信用分
+    /// - 迁移后账户扩容到当前大小，新字段为默认值
+    /// - 信用分按原计算时间设置过期时间，不对应任何评分模型
+    /// - 身份和等级只计入统计一次
+    #[test]
+    fn test_migrate_legacy_identity_and_score() {
+        use anchor_lang::Discriminator;
+
+        let mut svm = setup_test_environment();
+        let user = Keypair::new();
+        let payer = Keypair::new();
+        svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&payer.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        let now = svm.get_sysvar::<Clock>().unix_timestamp;
+        let created_at = now - 400 * 24 * 60 * 60;
+        let calculated_at = now - 200 * 24 * 60 * 60;
+
+        let mut identity_data = IdentityAccount::DISCRIMINATOR.to_vec();
+        identity_data.extend_from_slice(user.pubkey().as_ref());
+        identity_data.extend_from_slice(&created_at.to_le_bytes());
+        identity_data.push(1);
+        identity_data.push(1);
+        identity_data.extend_from_slice(&created_at.to_le_bytes());
+        assert_eq!(identity_data.len(), IdentityAccount::LEGACY_SPACE);
+        set_legacy_account(&mut svm, &identity_pda, identity_data);
+
+        // 等级字节 2 在最初版本中表示 High
+        let mut score_data = CreditScoreAccount::DISCRIMINATOR.to_vec();
+        score_data.extend_from_slice(identity_pda.as_ref());
+        score_data.push(75);
+        score_data.push(2);
+        score_data.extend_from_slice(&calculated_at.to_le_bytes());
+        assert_eq!(score_data.len(), CreditScoreAccount::LEGACY_SPACE);
+        set_legacy_account(&mut svm, &score_pda, score_data);
+
+        let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        assert!(send_ix(&mut svm, calc_ix, &user).is_err());
+
+        let stats_before = get_stats(&svm);
+        let ix = migrate_identity_ix(&user.pubkey(), &payer.pubkey());
+        send_ix(&mut svm, ix, &payer).unwrap();
+        svm.expire_blockhash();
+        let ix = migrate_identity_ix(&user.pubkey(), &payer.pubkey());
+        send_ix(&mut svm, ix, &payer).unwrap();
+
+        let account = svm.get_account(&identity_pda).unwrap();
+        assert_eq!(account.data.len(), IdentityAccount::SPACE);
+        assert!(account.lamports >= svm.minimum_balance_for_rent_exemption(IdentityAccount::SPACE));
+        let mut data_slice = &account.data[..];
+        let identity_state = IdentityAccount::try_deserialize(&mut data_slice).unwrap();
+        assert_eq!(identity_state.owner, user.pubkey());
+        assert_eq!(identity_state.created_at, created_at);
+        assert!(identity_state.verified);
+        assert_eq!(identity_state.verified_at, Some(created_at));
+        assert_eq!(identity_state.referrer, None);
+        assert!(identity_state.is_active());
+        assert_eq!(identity_state.penalty_points, 0);
+        assert!(!identity_state.keeper_refresh);
+        let stats = get_stats(&svm);
+        assert_eq!(stats.total_identities, stats_before.total_identities + 1);
+        assert_eq!(
+            stats.verified_identities,
+            stats_before.verified_identities + 1
+        );
+
+        let ix = migrate_score_level_ix(&user.pubkey(), &payer.pubkey());
+        send_ix(&mut svm, ix, &payer).unwrap();
+
+        let account = svm.get_account(&score_pda).unwrap();
+        assert_eq!(account.data.len(), CreditScoreAccount::SPACE);
+        let mut data_slice = &account.data[..];
+        let score_state = CreditScoreAccount::try_deserialize(&mut data_slice).unwrap();
+        let level = LevelCutoffs::default().level(75);
+        assert_eq!(score_state.identity, identity_pda);
+        assert_eq!(score_state.score, 75);
+        assert_eq!(score_state.score_level, level);
+        assert_eq!(score_state.calculated_at, calculated_at);
+        assert_eq!(
+            score_state.expires_at,
+            calculated_at + test_config_params().score_validity_seconds
+        );
+        assert_eq!(score_state.model_version, 0);
+        assert_eq!(score_state.breakdown.base_score, 75);
+        let mut level_counts = [0; ScoreLevel::COUNT];
+        level_counts[level as usize] = 1;
+        assert_eq!(get_stats(&svm).level_counts, level_counts);
+
+        let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        send_ix(&mut svm, calc_ix, &user).unwrap();
+        let stats = get_stats(&svm);
+        assert_eq!(stats.level_counts.iter().sum::<u64>(), 1);
+    }
+
+    /// 测试信用分等级按当前分界重新分类
+    ///
+    /// # 测试场景
+    /// 1. 用户计算信用分后，管理员提高分界
+    /// 2. 任何人重新分类该信用分，并重复执行
+    /// 3. 写入 50 字节、等级字节为旧 Low 的最初版本信用分并重新分类
+    ///
+    /// # 验证点
+    /// - 信用分及其历史按新分界重新分级，统计随之移动，重复执行不变
+    /// - 旧信用分扩容后按分数分级，只计入统计一次
+    /// - 非递增的分界被拒绝
+    #[test]
+    fn test_migrate_legacy_levels() {
+        use anchor_lang::Discriminator;
+
+        let (mut svm, admin) = setup_test_environment_with_admin();
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), 15 * LAMPORTS_PER_SOL).unwrap();
+
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        create_scored_identity(&mut svm, &user);
+
+        let get_score = |svm: &LiteSVM, address: &Pubkey| {
+            let account = svm.get_account(address).unwrap();
+            let mut data_slice = &account.data[..];
+            CreditScoreAccount::try_deserialize(&mut data_slice).unwrap()
+        };
+        let score = get_score(&svm, &score_pda).score;
+        let old_level = LevelCutoffs::default().level(score);
+        assert_ne!(old_level, ScoreLevel::Poor);
+        let mut level_counts = [0; ScoreLevel::COUNT];
+        level_counts[old_level as usize] = 1;
+        assert_eq!(get_stats(&svm).level_counts, level_counts);
+
+        // 分数刚好低于新的 Fair 分界
+        let params = ConfigParams {
+            level_cutoffs: LevelCutoffs {
+                fair: score + 1,
+                good: score + 2,
+                very_good: score + 3,
+                excellent: score + 4,
+            },
+            ..test_config_params()
+        };
+        let ix = config_ix("update_config", &admin.pubkey(), &params);
+        send_ix(&mut svm, ix, &admin).unwrap();
+
+        let ix = migrate_score_level_ix(&user.pubkey(), &user.pubkey());
+        send_ix(&mut svm, ix, &user).unwrap();
+        svm.expire_blockhash();
+        let ix = migrate_score_level_ix(&user.pubkey(), &user.pubkey());
+        send_ix(&mut svm, ix, &user).unwrap();
+
+        assert_eq!(get_score(&svm, &score_pda).score_level, ScoreLevel::Poor);
+        assert_eq!(get_stats(&svm).level_counts, [1, 0, 0, 0, 0]);
+        let history = get_score_history(&svm, &user.pubkey());
+        assert!(history
+            .iter_newest_first()
+            .all(|entry| entry.score_level == ScoreLevel::Poor));
+
+        let ix = config_ix("update_config", &admin.pubkey(), &test_config_params());
+        send_ix(&mut svm, ix, &admin).unwrap();
+
+        // 最初版本的等级字节 0 表示 Low
+        let legacy_owner = Pubkey::new_unique();
+        let legacy_score_pda = get_score_pda(&legacy_owner).0;
+        let now = svm.get_sysvar::<Clock>().unix_timestamp;
+        let mut score_data = CreditScoreAccount::DISCRIMINATOR.to_vec();
+        score_data.extend_from_slice(get_identity_pda(&legacy_owner).0.as_ref());
+        score_data.push(85);
+        score_data.push(0);
+        score_data.extend_from_slice(&now.to_le_bytes());
+        assert_eq!(score_data.len(), CreditScoreAccount::LEGACY_SPACE);
+        set_legacy_account(&mut svm, &legacy_score_pda, score_data);
+
+        let ix = migrate_score_level_ix(&legacy_owner, &admin.pubkey());
+        send_ix(&mut svm, ix, &admin).unwrap();
+        let score_state = get_score(&svm, &legacy_score_pda);
+        assert_eq!(score_state.score, 85);
+        assert_eq!(score_state.score_level, ScoreLevel::Excellent);
+        assert_eq!(
+            svm.get_account(&legacy_score_pda).unwrap().data.len(),
+            CreditScoreAccount::SPACE
+        );
+        assert_eq!(get_stats(&svm).level_counts, [1, 0, 0, 0, 1]);
+
+        let params = ConfigParams {
+            level_cutoffs: LevelCutoffs {
+                fair: 50,
+                good: 40,
+                very_good: 60,
+                excellent: 70,
+            },
+            ..test_config_params()
+        };
+        let ix = config_ix("update_config", &admin.pubkey(), &params);
+        assert_program_error(
+            send_ix(&mut svm, ix, &admin),
+            IdentityScoreError::InvalidLevelCutoffs,
+        );
+    }
+
+    /// 测试余额检查点的时间加权平均
+    #[test]
+    fn test_balance_time_weighted_average() {
+        let now = 1_700_000_000;
+        let hour = 60 * 60;
+        let mut history = BalanceHistory {
+            identity: Pubkey::new_unique(),
+            head: 0,
+            len: 0,
+            checkpoints: [BalanceCheckpoint::default(); BALANCE_HISTORY_LEN],
+        };
+        assert_eq!(history.time_weighted_average(now, 10 * hour, 0), None);
+
+        // 窗口开始前 100，窗口内 6 小时前变为 400，1 小时前变为 1000
+        history.push(BalanceCheckpoint {
+            lamports: 100,
+            timestamp: now - 20 * hour,
+        });
+        history.push(BalanceCheckpoint {
+            lamports: 400,
+            timestamp: now - 6 * hour,
+        });
+        history.push(BalanceCheckpoint {
+            lamports: 1_000,
+            timestamp: now - hour,
+        });
+
+        // (100 * 4 + 400 * 5 + 1000 * 1) / 10
+        assert_eq!(history.time_weighted_average(now, 10 * hour, 2), Some(340));
+        assert_eq!(history.time_weighted_average(now, 10 * hour, 3), None);
+        // 窗口早于所有检查点时只平均有记录的时段
+        assert_eq!(
+            history.time_weighted_average(now, 40 * hour, 3),
+            Some((100 * 14 + 400 * 5 + 1_000) / 20)
+        );
+
+        // 唯一的检查点恰好在当前时刻
+        let mut fresh = history.clone();
+        fresh.len = 0;
+        fresh.push(BalanceCheckpoint {
+            lamports: 7,
+            timestamp: now,
+        });
+        assert_eq!(fresh.time_weighted_average(now, 10 * hour, 1), Some(7));
+
+        for i in 0..BALANCE_HISTORY_LEN as i64 {
+            history.push(BalanceCheckpoint {
+                lamports: 50,
+                timestamp: now + i,
+            });
+        }
+        assert_eq!(history.len as usize, BALANCE_HISTORY_LEN);
+        assert_eq!(history.latest().unwrap().timestamp, now + 31);
+        assert!(history.iter_newest_first().all(|c| c.lamports == 50));
+
+        // 间隔内的记录并入最新检查点，只会压低余额
+        history.record(
+            BalanceCheckpoint {
+                lamports: 80,
+                timestamp: now + 40,
+            },
+            hour,
+        );
+        history.record(
+            BalanceCheckpoint {
+                lamports: 20,
+                timestamp: now + 50,
+            },
+            hour,
+        );
+        assert_eq!(
+            history.latest(),
+            Some(&BalanceCheckpoint {
+                lamports: 20,
+                timestamp: now + 31,
+            })
+        );
+        history.record(
+            BalanceCheckpoint {
+                lamports: 80,
+                timestamp: now + 31 + hour,
+            },
+            hour,
+        );
+        assert_eq!(history.latest().unwrap().lamports, 80);
+    }
+
+    /// 构建余额检查点指令
+    fn checkpoint_balance_ix(owner: &Pubkey, payer: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(get_balance_history_pda(owner).0, false),
+                AccountMeta::new_readonly(get_identity_pda(owner).0, false),
+                AccountMeta::new_readonly(*owner, false),
+                AccountMeta::new(*payer, true),
+                AccountMeta::new_readonly(Pubkey::from(program::id().to_bytes()), false),
+                AccountMeta::new_readonly(solana_instructions_sysvar::ID, false),
+            ],
+            data: get_discriminator("checkpoint_balance").to_vec(),
+        }
+    }
+
+    /// 测试时间加权余额抵御闪电借入的余额
+    ///
+    /// # 测试场景
+    /// 1. 管理员要求窗口内至少 2 个余额检查点
+    /// 2. 任何人为用户记录检查点，用户转出部分余额后在间隔内再次记录
+    /// 3. 用户在计算信用分前临时转入大额 SOL
+    ///
+    /// # 验证点
+    /// - 检查点数量超过历史容量的配置被拒绝
+    /// - 检查点不足时无法计算信用分
+    /// - 间隔内重复记录不报错，只保留较低的余额
+    /// - 与为所有者入账的指令同一交易时返回 SuspiciousTransaction
+    /// - 信用分按时间加权余额计算，临时余额不抬高资产分
+    #[test]
+    fn test_balance_checkpoints_resist_flash_balance() {
+        let (mut svm, admin) = setup_test_environment_with_admin();
+        let user = Keypair::new();
+        let keeper = Keypair::new();
+        svm.airdrop(&user.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&keeper.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let day = 24 * 60 * 60;
+        let params = ConfigParams {
+            balance_window_seconds: 3 * day,
+            min_balance_checkpoints: BALANCE_HISTORY_LEN as u8 + 1,
+            ..test_config_params()
+        };
+        let ix = config_ix("update_config", &admin.pubkey(), &params);
+        assert_program_error(
+            send_ix(&mut svm, ix, &admin),
+            IdentityScoreError::InvalidBalanceWindow,
+        );
+
+        let params = ConfigParams {
+            min_balance_checkpoints: 2,
+            ..params
+        };
+        let ix = config_ix("update_config", &admin.pubkey(), &params);
+        send_ix(&mut svm, ix, &admin).unwrap();
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        create_verified_identity(&mut svm, &user);
+
+        let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        assert_program_error(
+            send_ix(&mut svm, calc_ix, &user),
+            IdentityScoreError::InsufficientBalanceCheckpoints,
+        );
+
+        let ix = checkpoint_balance_ix(&user.pubkey(), &keeper.pubkey());
+        send_ix(&mut svm, ix, &keeper).unwrap();
+        let get_history = |svm: &LiteSVM| {
+            let account = svm
+                .get_account(&get_balance_history_pda(&user.pubkey()).0)
+                .unwrap();
+            let mut data_slice = &account.data[..];
+            BalanceHistory::try_deserialize(&mut data_slice).unwrap()
+        };
+
+        // 间隔内再次记录只保留较低的余额
+        let transfer_ix =
+            system_transfer_ix(&user.pubkey(), &keeper.pubkey(), LAMPORTS_PER_SOL / 2);
+        send_ix(&mut svm, transfer_ix, &user).unwrap();
+        let ix = checkpoint_balance_ix(&user.pubkey(), &keeper.pubkey());
+        send_ix(&mut svm, ix, &keeper).unwrap();
+        let history = get_history(&svm);
+        assert_eq!(history.len, 1);
+        assert_eq!(
+            history.latest().unwrap().lamports,
+            svm.get_balance(&user.pubkey()).unwrap()
+        );
+
+        let credit_ix = system_transfer_ix(&keeper.pubkey(), &user.pubkey(), LAMPORTS_PER_SOL / 4);
+        let ix = checkpoint_balance_ix(&user.pubkey(), &keeper.pubkey());
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[credit_ix, ix],
+            Some(&keeper.pubkey()),
+            &[&keeper],
+            blockhash,
+        );
+        let result = svm
+            .send_transaction(tx)
+            .map(|_| ())
+            .map_err(|e| format!("{:?}", e.err));
+        assert_program_error(result, IdentityScoreError::SuspiciousTransaction);
+
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += day;
+        svm.set_sysvar::<Clock>(&clock);
+        let ix = checkpoint_balance_ix(&user.pubkey(), &keeper.pubkey());
+        send_ix(&mut svm, ix, &keeper).unwrap();
+
+        clock.unix_timestamp += day;
+        svm.set_sysvar::<Clock>(&clock);
+        svm.airdrop(&user.pubkey(), 1_000 * LAMPORTS_PER_SOL)
+            .unwrap();
+        let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        send_ix(&mut svm, calc_ix, &user).unwrap();
+
+        let account = svm
+            .get_account(&get_balance_history_pda(&user.pubkey()).0)
+            .unwrap();
+        let mut data_slice = &account.data[..];
+        let history = BalanceHistory::try_deserialize(&mut data_slice).unwrap();
+        assert_eq!(history.identity, identity_pda);
+        assert_eq!(history.len, 2);
+        let average = history
+            .time_weighted_average(clock.unix_timestamp, 3 * day, 2)
+            .unwrap();
+        assert!(average < 2 * LAMPORTS_PER_SOL);
+
+        let account = svm.get_account(&score_pda).unwrap();
+        let mut data_slice = &account.data[..];
+        let score_state = CreditScoreAccount::try_deserialize(&mut data_slice).unwrap();
+        let identity_account = svm.get_account(&identity_pda).unwrap();
+        let mut data_slice = &identity_account.data[..];
+        let identity_state = IdentityAccount::try_deserialize(&mut data_slice).unwrap();
+        let (expected, _) = fixed_score(
+            average,
+            identity_state.created_at,
+            true,
+            owner_footprint_rent(&svm, &user.pubkey(), &[]),
+            clock.unix_timestamp,
+        );
+        assert_eq!(score_state.score, expected);
+        let (spot, _) = fixed_score(
+            svm.get_balance(&user.pubkey()).unwrap(),
+            identity_state.created_at,
+            true,
+            owner_footprint_rent(&svm, &user.pubkey(), &[]),
+            clock.unix_timestamp,
+        );
+        assert!(score_state.score < spot);
+    }
+
+    /// 构建系统程序转账指令
+    fn system_transfer_ix(from: &Pubkey, to: &Pubkey, lamports: u64) -> Instruction {
+        let mut data = 2u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&lamports.to_le_bytes());
+        Instruction {
+            program_id: Pubkey::from(program::id().to_bytes()),
+            accounts: vec![AccountMeta::new(*from, true), AccountMeta::new(*to, false)],
+            data,
+        }
+    }
+
+    /// 测试信用分计算拒绝同一交易中为所有者入账的指令
+    ///
+    /// # 测试场景
+    /// 1. 他人转账给所有者后在同一交易中计算信用分
+    /// 2. 所有者转出资金后在同一交易中计算信用分
+    /// 3. 计算预算指令与计算信用分同一交易
+    ///
+    /// # 验证点
+    /// - 转入所有者的交易返回 SuspiciousTransaction
+    /// - 所有者作为付款方的转账和计算预算指令被允许
+    #[test]
+    fn test_calculate_score_rejects_owner_credit() {
+        let mut svm = setup_test_environment();
+        let user = Keypair::new();
+        let lender = Keypair::new();
+        svm.airdrop(&user.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&lender.pubkey(), 200 * LAMPORTS_PER_SOL)
+            .unwrap();
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        create_verified_identity(&mut svm, &user);
+
+        let borrow_ix =
+            system_transfer_ix(&lender.pubkey(), &user.pubkey(), 100 * LAMPORTS_PER_SOL);
+        let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[borrow_ix, calc_ix],
+            Some(&user.pubkey()),
+            &[&user, &lender],
+            blockhash,
+        );
+        let result = svm
+            .send_transaction(tx)
+            .map(|_| ())
+            .map_err(|e| format!("{:?}", e));
+        assert_program_error(result, IdentityScoreError::SuspiciousTransaction);
+        assert!(svm.get_account(&score_pda).is_none());
+
+        let repay_ix = system_transfer_ix(&user.pubkey(), &lender.pubkey(), 1_000);
+        let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[repay_ix, calc_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).unwrap();
+
+        let mut data = vec![2];
+        data.extend_from_slice(&400_000u32.to_le_bytes());
+        let budget_ix = Instruction {
+            program_id: crate::introspection::COMPUTE_BUDGET_PROGRAM_ID,
+            accounts: vec![],
+            data,
+        };
+        let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[budget_ix, calc_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).unwrap();
+    }
+
+    /// 测试租金效率因子按所有者账户占用的租金计算
+    ///
+    /// # 测试场景
+    /// 1. 固定余额下比较不同的账户租金占用
+    /// 2. 两个用户以相同余额计算信用分后，按各自的账户重算
+    /// 3. 他人为其中一个用户记录余额检查点后预览两人的信用分
+    ///
+    /// # 验证点
+    /// - 余额不超过占用租金时为最低分，达到 10 倍时为满分
+    /// - 占用越多，租金效率越低
+    /// - 链上明细与所有者实际保有账户的租金占用一致
+    /// - 余额相同时，多出余额历史的用户租金效率更低
+    #[test]
+    fn test_rent_efficiency_measures_footprint() {
+        use crate::scoring::{calculate_score_breakdown, ScoreInputs, SCORE_MIN};
+
+        let rent_efficiency = |lamports, footprint_rent| {
+            calculate_score_breakdown(&ScoreInputs {
+                lamports,
+                footprint_rent,
+                ..Default::default()
+            })
+            .rent_efficiency
+            .score
+        };
+        let footprint = 10_000_000;
+        assert_eq!(rent_efficiency(footprint, footprint), SCORE_MIN);
+        assert_eq!(rent_efficiency(footprint / 2, footprint), SCORE_MIN);
+        assert_eq!(rent_efficiency(10 * footprint, footprint), 90);
+        assert_eq!(rent_efficiency(u64::MAX, footprint), 90);
+        assert!(
+            rent_efficiency(5 * footprint, 2 * footprint)
+                < rent_efficiency(5 * footprint, footprint)
+        );
+
+        let mut svm = setup_test_environment();
+        let users = [Keypair::new(), Keypair::new()];
+        for user in &users {
+            svm.airdrop(&user.pubkey(), 50_000_000).unwrap();
+            create_scored_identity(&mut svm, user);
+        }
+        let [plain, tracked] = &users;
+        let balance = svm.get_balance(&plain.pubkey()).unwrap();
+        assert_eq!(svm.get_balance(&tracked.pubkey()).unwrap(), balance);
+
+        let account = svm.get_account(&get_score_pda(&plain.pubkey()).0).unwrap();
+        let mut data_slice = &account.data[..];
+        let score_state = CreditScoreAccount::try_deserialize(&mut data_slice).unwrap();
+        assert_eq!(
+            score_state.breakdown.rent_efficiency.score,
+            rent_efficiency(balance, owner_footprint_rent(&svm, &plain.pubkey(), &[]))
+        );
+        assert!(score_state.breakdown.rent_efficiency.score < 90);
+
+        // 由守护者付费创建余额历史，两人余额仍然相同
+        let keeper = Keypair::new();
+        svm.airdrop(&keeper.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        let ix = checkpoint_balance_ix(&tracked.pubkey(), &keeper.pubkey());
+        send_ix(&mut svm, ix, &keeper).unwrap();
+        assert_eq!(svm.get_balance(&tracked.pubkey()).unwrap(), balance);
+
+        let plain_preview = simulate_preview(&svm, &plain.pubkey(), &keeper);
+        let tracked_preview = simulate_preview(&svm, &tracked.pubkey(), &keeper);
+        assert_eq!(
+            plain_preview.breakdown.rent_efficiency,
+            score_state.breakdown.rent_efficiency
+        );
+        let tracked_footprint = owner_footprint_rent(&svm, &tracked.pubkey(), &[]);
+        assert!(tracked_footprint > owner_footprint_rent(&svm, &plain.pubkey(), &[]));
+        assert_eq!(
+            tracked_preview.breakdown.rent_efficiency.score,
+            rent_efficiency(balance, tracked_footprint)
+        );
+        assert!(
+            tracked_preview.breakdown.rent_efficiency.score
+                < plain_preview.breakdown.rent_efficiency.score
+        );
+    }
+
+    /// 测试签到连续天数与活跃度因子
+    ///
+    /// # 验证点
+    /// - 同一天重复签到被拒绝，隔天签到延续连续天数，中断后重新计数
+    /// - 超过一天未签到时连续天数读作 0，累计天数不变
+    /// - 从未签到为最低分，连续 30 天且累计 180 天为满分
+    /// - 超过 30 天未签到后累计天数不再计分
+    /// - 活跃度权重计入基础权重总和
+    #[test]
+    fn test_activity_streaks() {
+        use crate::scoring::{calculate_score_breakdown, ActivityRecord, ScoreInputs, SCORE_MIN};
+
+        let day = ACTIVITY_DAY_SECONDS;
+        let start = 1_700_000_000 / day * day + 100;
+        let mut activity = Activity {
+            identity: Pubkey::new_unique(),
+            last_check_in: 0,
+            current_streak: 0,
+            total_days: 0,
+        };
+        assert_eq!(activity.record(start), None);
+
+        activity.check_in(start).unwrap();
+        assert!(activity.check_in(start + day - 200).is_err());
+        activity.check_in(start + day - 50).unwrap();
+        activity.check_in(start + 2 * day).unwrap();
+        assert_eq!(activity.current_streak, 3);
+        assert_eq!(activity.streak_at(start + 3 * day), 3);
+        assert_eq!(activity.streak_at(start + 4 * day), 0);
+
+        activity.check_in(start + 5 * day).unwrap();
+        assert_eq!(activity.current_streak, 1);
+        assert_eq!(activity.total_days, 4);
+        assert_eq!(
+            activity.record(start + 7 * day),
+            Some(ActivityRecord {
+                streak_days: 0,
+                total_days: 4,
+                days_since_check_in: 2,
+            })
+        );
+
+        let activity_score = |activity| {
+            calculate_score_breakdown(&ScoreInputs {
+                activity,
+                ..Default::default()
+            })
+            .activity
+            .score
+        };
+        let record = |streak_days, total_days, days_since_check_in| {
+            Some(ActivityRecord {
+                streak_days,
+                total_days,
+                days_since_check_in,
+            })
+        };
+        assert_eq!(activity_score(None), SCORE_MIN);
+        assert_eq!(activity_score(record(30, 180, 0)), 90);
+        assert_eq!(activity_score(record(365, 1_000, 1)), 90);
+        assert_eq!(activity_score(record(15, 0, 0)), 45);
+        assert_eq!(activity_score(record(0, 90, 30)), 45);
+        assert_eq!(activity_score(record(0, 90, 31)), SCORE_MIN);
+
+        let params = ModelParams {
+            weight_asset: 3_000,
+            weight_activity: 1_000,
+            ..ModelParams::default()
+        };
+        assert!(params.is_valid());
+        assert!(!ModelParams {
+            weight_activity: 1_000,
+            ..ModelParams::default()
+        }
+        .is_valid());
+    }
+
+    /// 构建签到指令
+    fn check_in_ix(owner: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(get_activity_pda(owner).0, false),
+                AccountMeta::new_readonly(get_identity_pda(owner).0, false),
+                AccountMeta::new(*owner, true),
+                AccountMeta::new_readonly(Pubkey::from(program::id().to_bytes()), false),
+            ],
+            data: get_discriminator("check_in").to_vec(),
+        }
+    }
+
+    /// 测试链上签到与活跃度明细
+    ///
+    /// # 测试场景
+    /// 1. 用户创建身份后连续两天签到
+    /// 2. 同一天再次签到
+    /// 3. 计算信用分
+    ///
+    /// # 验证点
+    /// - 签到记录保存连续天数与累计天数
+    /// - 同一天重复签到被拒绝
+    /// - 信用分明细中的活跃度分数来自签到记录
+    #[test]
+    fn test_check_in() {
+        use crate::scoring::SCORE_MIN;
+
+        let mut svm = setup_test_environment();
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), 15 * LAMPORTS_PER_SOL).unwrap();
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
+        let verify_ix = verify_identity_ix(&user.pubkey(), &identity_pda);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[create_ix, verify_ix, check_in_ix(&user.pubkey())],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).unwrap();
+
+        svm.expire_blockhash();
+        assert_program_error(
+            send_ix(&mut svm, check_in_ix(&user.pubkey()), &user),
+            IdentityScoreError::AlreadyCheckedIn,
+        );
+
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += ACTIVITY_DAY_SECONDS;
+        svm.set_sysvar::<Clock>(&clock);
+        send_ix(&mut svm, check_in_ix(&user.pubkey()), &user).unwrap();
+
+        let account = svm
+            .get_account(&get_activity_pda(&user.pubkey()).0)
+            .unwrap();
+        let mut data_slice = &account.data[..];
+        let activity = Activity::try_deserialize(&mut data_slice).unwrap();
+        assert_eq!(activity.identity, identity_pda);
+        assert_eq!(activity.current_streak, 2);
+        assert_eq!(activity.total_days, 2);
+
+        let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        send_ix(&mut svm, calc_ix, &user).unwrap();
+
+        let account = svm.get_account(&score_pda).unwrap();
+        let mut data_slice = &account.data[..];
+        let score_state = CreditScoreAccount::try_deserialize(&mut data_slice).unwrap();
+        assert!(score_state.breakdown.activity.score > SCORE_MIN);
+        assert_eq!(score_state.breakdown.activity.weight_bps, 0);
+    }
+
+    /// 获取守护者小费保管库的 PDA 地址
+    fn get_tip_vault_pda(owner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SEED_TIP_VAULT, owner.as_ref()], &PROGRAM_ID)
+    }
+
+    /// 构建守护者刷新信用分指令
+    fn refresh_score_ix(keeper: &Pubkey, owner: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(get_score_pda(owner).0, false),
+                AccountMeta::new(get_score_history_pda(owner).0, false),
+                AccountMeta::new_readonly(get_identity_pda(owner).0, false),
+                AccountMeta::new_readonly(*owner, false),
+                AccountMeta::new(*keeper, true),
+                AccountMeta::new(get_stats_pda().0, false),
+                AccountMeta::new_readonly(get_config_pda().0, false),
+                AccountMeta::new_readonly(get_credit_history_pda(owner).0, false),
+                AccountMeta::new_readonly(get_penalty_index_pda(owner).0, false),
+                AccountMeta::new_readonly(get_balance_history_pda(owner).0, false),
+                AccountMeta::new_readonly(get_activity_pda(owner).0, false),
+                AccountMeta::new_readonly(get_holding_registry_pda(owner).0, false),
+                AccountMeta::new_readonly(get_score_model_pda(1).0, false),
+                AccountMeta::new_readonly(solana_instructions_sysvar::ID, false),
+                AccountMeta::new(get_tip_vault_pda(owner).0, false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+            ],
+            data: get_discriminator("refresh_score").to_vec(),
+        }
+    }
+
+    /// 获取守护者刷新持仓登记账户的 PDA 地址
+    fn get_holding_registry_pda(owner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SEED_HOLDING_REGISTRY, owner.as_ref()], &PROGRAM_ID)
+    }
+
+    /// 构建开启或关闭守护者刷新的指令，同时登记守护者必须传入的持仓账户
+    fn set_keeper_refresh_ix(owner: &Pubkey, enabled: bool, holdings: &[Pubkey]) -> Instruction {
+        let mut data = get_discriminator("set_keeper_refresh").to_vec();
+        data.push(enabled as u8);
+        holdings.to_vec().serialize(&mut data).unwrap();
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(get_identity_pda(owner).0, false),
+                AccountMeta::new(get_holding_registry_pda(owner).0, false),
+                AccountMeta::new(*owner, true),
+                AccountMeta::new_readonly(Pubkey::from(program::id().to_bytes()), false),
+            ],
+            data,
+        }
+    }
+
+    /// 构建为小费保管库充值的指令
+    fn fund_tip_vault_ix(owner: &Pubkey, amount: u64, tip_lamports: u64) -> Instruction {
+        let mut data = get_discriminator("fund_tip_vault").to_vec();
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&tip_lamports.to_le_bytes());
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(get_tip_vault_pda(owner).0, false),
+                AccountMeta::new(*owner, true),
+                AccountMeta::new_readonly(Pubkey::from(program::id().to_bytes()), false),
+            ],
+            data,
+        }
+    }
+
+    /// 测试守护者刷新他人的信用分
+    ///
+    /// # 测试场景
+    /// 1. 用户计算信用分后，守护者在用户授权前刷新
+    /// 2. 用户授权并登记质押账户、为保管库充值，守护者漏传持仓后在冷却期内外刷新
+    /// 3. 保管库余额不足以支付小费时刷新
+    /// 4. 用户关闭保管库并取消授权
+    ///
+    /// # 验证点
+    /// - 未授权时刷新被拒绝
+    /// - 未传入登记的持仓时返回 UnregisteredHoldings
+    /// - 刷新遵守重新计算冷却期
+    /// - 守护者每次刷新获得小费，余额不足时仍可刷新但不获得小费
+    /// - 关闭保管库后余额全部退回用户，取消授权后不能再刷新
+    #[test]
+    fn test_keeper_refresh_score() {
+        let (mut svm, admin) = setup_test_environment_with_admin();
+        let params = ConfigParams {
+            min_score_interval_seconds: 3600,
+            ..test_config_params()
+        };
+        let ix = config_ix("update_config", &admin.pubkey(), &params);
+        send_ix(&mut svm, ix, &admin).unwrap();
+
+        let user = Keypair::new();
+        let keeper = Keypair::new();
+        svm.airdrop(&user.pubkey(), 15 * LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&keeper.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        create_scored_identity(&mut svm, &user);
+
+        let advance = |svm: &mut LiteSVM| {
+            let mut clock = svm.get_sysvar::<Clock>();
+            clock.unix_timestamp += 3600;
+            svm.set_sysvar::<Clock>(&clock);
+            svm.expire_blockhash();
+        };
+        advance(&mut svm);
+        let refresh_ix = refresh_score_ix(&keeper.pubkey(), &user.pubkey());
+        assert_program_error(
+            send_ix(&mut svm, refresh_ix, &keeper),
+            IdentityScoreError::KeeperRefreshDisabled,
+        );
+
+        let tip = 10_000;
+        let stake = set_stake_account(&mut svm, &user.pubkey(), LAMPORTS_PER_SOL, 0, u64::MAX);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                set_keeper_refresh_ix(&user.pubkey(), true, &[stake]),
+                fund_tip_vault_ix(&user.pubkey(), tip + tip / 2, tip),
+            ],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).unwrap();
+        let registry = svm
+            .get_account(&get_holding_registry_pda(&user.pubkey()).0)
+            .unwrap();
+        let mut data_slice = &registry.data[..];
+        let registry = HoldingRegistry::try_deserialize(&mut data_slice).unwrap();
+        assert_eq!(registry.owner, user.pubkey());
+        assert_eq!(registry.holdings, vec![stake]);
+
+        let with_stake = || {
+            let mut ix = refresh_score_ix(&keeper.pubkey(), &user.pubkey());
+            ix.accounts.push(AccountMeta::new_readonly(stake, false));
+            ix
+        };
+        // 守护者漏传登记的持仓
+        let refresh_ix = refresh_score_ix(&keeper.pubkey(), &user.pubkey());
+        assert_program_error(
+            send_ix(&mut svm, refresh_ix, &keeper),
+            IdentityScoreError::UnregisteredHoldings,
+        );
+
+        let keeper_before = svm.get_balance(&keeper.pubkey()).unwrap();
+        send_ix(&mut svm, with_stake(), &keeper).unwrap();
+        // 守护者获得小费，扣除交易手续费
+        assert_eq!(
+            svm.get_balance(&keeper.pubkey()).unwrap(),
+            keeper_before + tip - 5_000
+        );
+        assert_eq!(get_stats(&svm).scores_computed, 2);
+
+        svm.expire_blockhash();
+        assert_program_error(
+            send_ix(&mut svm, with_stake(), &keeper),
+            IdentityScoreError::ScoreCooldownActive,
+        );
+
+        // 剩余余额不足一次小费
+        advance(&mut svm);
+        let keeper_before = svm.get_balance(&keeper.pubkey()).unwrap();
+        send_ix(&mut svm, with_stake(), &keeper).unwrap();
+        assert_eq!(
+            svm.get_balance(&keeper.pubkey()).unwrap(),
+            keeper_before - 5_000
+        );
+        assert_eq!(get_stats(&svm).scores_computed, 3);
+
+        let (tip_vault_pda, _) = get_tip_vault_pda(&user.pubkey());
+        let vault_lamports = svm.get_balance(&tip_vault_pda).unwrap();
+        let user_before = svm.get_balance(&user.pubkey()).unwrap();
+        let close_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(tip_vault_pda, false),
+                AccountMeta::new(user.pubkey(), true),
+            ],
+            data: get_discriminator("close_tip_vault").to_vec(),
+        };
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[close_ix, set_keeper_refresh_ix(&user.pubkey(), false, &[])],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).unwrap();
+        assert_eq!(
+            svm.get_balance(&user.pubkey()).unwrap(),
+            user_before + vault_lamports - 5_000
+        );
+        assert!(svm.get_account(&tip_vault_pda).is_none());
+
+        advance(&mut svm);
+        let refresh_ix = refresh_score_ix(&keeper.pubkey(), &user.pubkey());
+        assert_program_error(
+            send_ix(&mut svm, refresh_ix, &keeper),
+            IdentityScoreError::KeeperRefreshDisabled,
+        );
+    }
+
+    /// 构建批量刷新信用分指令，每个所有者不带持仓账户
+    fn batch_refresh_scores_ix(keeper: &Pubkey, owners: &[Pubkey]) -> Instruction {
+        let mut data = get_discriminator("batch_refresh_scores").to_vec();
+        vec![0u8; owners.len()].serialize(&mut data).unwrap();
+
+        let mut accounts = vec![
+            AccountMeta::new(*keeper, true),
+            AccountMeta::new(get_stats_pda().0, false),
+            AccountMeta::new_readonly(get_config_pda().0, false),
+            AccountMeta::new_readonly(get_score_model_pda(1).0, false),
+            AccountMeta::new_readonly(solana_instructions_sysvar::ID, false),
+            AccountMeta::new_readonly(PROGRAM_ID, false),
+        ];
+        for owner in owners {
+            accounts.extend([
+                AccountMeta::new_readonly(*owner, false),
+                AccountMeta::new_readonly(get_identity_pda(owner).0, false),
+                AccountMeta::new(get_score_pda(owner).0, false),
+                AccountMeta::new(get_score_history_pda(owner).0, false),
+                AccountMeta::new_readonly(get_credit_history_pda(owner).0, false),
+                AccountMeta::new_readonly(get_penalty_index_pda(owner).0, false),
+                AccountMeta::new_readonly(get_balance_history_pda(owner).0, false),
+                AccountMeta::new_readonly(get_activity_pda(owner).0, false),
+                AccountMeta::new_readonly(get_holding_registry_pda(owner).0, false),
+                AccountMeta::new(get_tip_vault_pda(owner).0, false),
+            ]);
+        }
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data,
+        }
+    }
+
+    /// 测试批量刷新多个身份的信用分
+    ///
+    /// # 测试场景
+    /// 1. 三个用户计算信用分，其中两个授权守护者刷新，一个为保管库充值
+    /// 2. 守护者在一笔交易中批量刷新三个用户
+    /// 3. 冷却期内再次批量刷新
+    /// 4. 一个用户登记质押账户后，分别不带和带上该持仓批量刷新
+    ///
+    /// # 验证点
+    /// - 未授权的用户被跳过，返回数据给出其序号和错误码，其余用户照常刷新
+    /// - 守护者获得有保管库用户的小费
+    /// - 冷却期内所有用户都被跳过，批量交易本身仍然成功
+    /// - 账户数量与持仓数量不符、同一所有者出现两次时整批失败
+    /// - 登记了持仓的用户在持仓数量为 0 时被跳过，传入登记的持仓后刷新
+    #[test]
+    fn test_batch_refresh_scores() {
+        let (mut svm, admin) = setup_test_environment_with_admin();
+        let params = ConfigParams {
+            min_score_interval_seconds: 3600,
+            ..test_config_params()
+        };
+        let ix = config_ix("update_config", &admin.pubkey(), &params);
+        send_ix(&mut svm, ix, &admin).unwrap();
+
+        let keeper = Keypair::new();
+        svm.airdrop(&keeper.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        let users: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+        let tip = 10_000;
+        for (i, user) in users.iter().enumerate() {
+            svm.airdrop(&user.pubkey(), 15 * LAMPORTS_PER_SOL).unwrap();
+            let (identity_pda, _) = get_identity_pda(&user.pubkey());
+            let (score_pda, _) = get_score_pda(&user.pubkey());
+            let mut ixs = vec![
+                create_identity_ix(&user.pubkey(), &identity_pda),
+                verify_identity_ix(&user.pubkey(), &identity_pda),
+                calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda),
+            ];
+            if i != 1 {
+                ixs.push(set_keeper_refresh_ix(&user.pubkey(), true, &[]));
+            }
+            if i == 2 {
+                ixs.push(fund_tip_vault_ix(&user.pubkey(), 10 * tip, tip));
+            }
+            let blockhash = svm.latest_blockhash();
+            let tx =
+                Transaction::new_signed_with_payer(&ixs, Some(&user.pubkey()), &[user], blockhash);
+            svm.send_transaction(tx).unwrap();
+        }
+        let owners: Vec<Pubkey> = users.iter().map(|user| user.pubkey()).collect();
+
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += 3600;
+        svm.set_sysvar::<Clock>(&clock);
+
+        let keeper_before = svm.get_balance(&keeper.pubkey()).unwrap();
+        let tx = Transaction::new_signed_with_payer(
+            &[batch_refresh_scores_ix(&keeper.pubkey(), &owners)],
+            Some(&keeper.pubkey()),
+            &[&keeper],
+            svm.latest_blockhash(),
+        );
+        let meta = svm.send_transaction(tx).unwrap();
+        let report = BatchRefreshReport::try_from_slice(&meta.return_data.data).unwrap();
+        assert_eq!(report.refreshed, 2);
+        assert_eq!(
+            report.skipped,
+            vec![SkippedRefresh {
+                index: 1,
+                error_code: u32::from(IdentityScoreError::KeeperRefreshDisabled) as u64,
+            }]
+        );
+        assert_eq!(
+            svm.get_balance(&keeper.pubkey()).unwrap(),
+            keeper_before + tip - 5_000
+        );
+        let stats = get_stats(&svm);
+        assert_eq!(stats.scores_computed, 5);
+        assert_eq!(stats.level_counts.iter().sum::<u64>(), 3);
+        assert_eq!(get_score_history(&svm, &owners[0]).len, 2);
+        assert_eq!(get_score_history(&svm, &owners[1]).len, 1);
+
+        svm.expire_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[batch_refresh_scores_ix(&keeper.pubkey(), &owners)],
+            Some(&keeper.pubkey()),
+            &[&keeper],
+            svm.latest_blockhash(),
+        );
+        let meta = svm.send_transaction(tx).unwrap();
+        let report = BatchRefreshReport::try_from_slice(&meta.return_data.data).unwrap();
+        assert_eq!(report.refreshed, 0);
+        assert_eq!(report.skipped.len(), 3);
+        assert_eq!(get_stats(&svm).scores_computed, 5);
+
+        let mut ix = batch_refresh_scores_ix(&keeper.pubkey(), &owners);
+        ix.accounts.pop();
+        assert_program_error(
+            send_ix(&mut svm, ix, &keeper),
+            IdentityScoreError::InvalidBatchAccounts,
+        );
+
+        let ix = batch_refresh_scores_ix(&keeper.pubkey(), &[owners[2], owners[0], owners[2]]);
+        assert_program_error(
+            send_ix(&mut svm, ix, &keeper),
+            IdentityScoreError::DuplicateBatchOwner,
+        );
+
+        // 登记了持仓的用户，守护者必须在批量中传入
+        let stake = set_stake_account(&mut svm, &owners[0], LAMPORTS_PER_SOL, 0, u64::MAX);
+        let ix = set_keeper_refresh_ix(&owners[0], true, &[stake]);
+        send_ix(&mut svm, ix, &users[0]).unwrap();
+        clock.unix_timestamp += 3600;
+        svm.set_sysvar::<Clock>(&clock);
+
+        let ix = batch_refresh_scores_ix(&keeper.pubkey(), &owners[..1]);
+        let meta = svm
+            .send_transaction(Transaction::new_signed_with_payer(
+                &[ix],
+                Some(&keeper.pubkey()),
+                &[&keeper],
+                svm.latest_blockhash(),
+            ))
+            .unwrap();
+        let report = BatchRefreshReport::try_from_slice(&meta.return_data.data).unwrap();
+        assert_eq!(
+            report.skipped,
+            vec![SkippedRefresh {
+                index: 0,
+                error_code: u32::from(IdentityScoreError::UnregisteredHoldings) as u64,
+            }]
+        );
+
+        let mut ix = batch_refresh_scores_ix(&keeper.pubkey(), &owners[..1]);
+        ix.data.truncate(8);
+        vec![1u8].serialize(&mut ix.data).unwrap();
+        ix.accounts.push(AccountMeta::new_readonly(stake, false));
+        let meta = svm
+            .send_transaction(Transaction::new_signed_with_payer(
+                &[ix],
+                Some(&keeper.pubkey()),
+                &[&keeper],
+                svm.latest_blockhash(),
+            ))
+            .unwrap();
+        let report = BatchRefreshReport::try_from_slice(&meta.return_data.data).unwrap();
+        assert_eq!(report.refreshed, 1);
+    }
+
+    /// 获取授权记录账户的 PDA 地址
+    fn get_consent_pda(owner: &Pubkey, grantee: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[SEED_CONSENT, owner.as_ref(), grantee.as_ref()],
+            &PROGRAM_ID,
+        )
+    }
+
+    /// 构建授权第三方程序读取信用分的指令
+    fn grant_consent_ix(
+        owner: &Pubkey,
+        grantee: &Pubkey,
+        scope: u8,
+        expires_at: Option<i64>,
+    ) -> Instruction {
+        let mut data = get_discriminator("grant_consent").to_vec();
+        grantee.serialize(&mut data).unwrap();
+        data.push(scope);
+        expires_at.serialize(&mut data).unwrap();
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(get_consent_pda(owner, grantee).0, false),
+                AccountMeta::new(*owner, true),
+                AccountMeta::new_readonly(Pubkey::from(program::id().to_bytes()), false),
+            ],
+            data,
+        }
+    }
+
+    /// 构建撤销授权的指令
+    fn revoke_consent_ix(owner: &Pubkey, grantee: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(get_consent_pda(owner, grantee).0, false),
+                AccountMeta::new(*owner, true),
+            ],
+            data: get_discriminator("revoke_consent").to_vec(),
+        }
+    }
+
+    /// 测试用户授权和撤销第三方程序读取信用分
+    ///
+    /// # 测试场景
+    /// 1. 检查授权记录在到期前后的有效性
+    /// 2. 用户以无效范围或已过期的时间授权
+    /// 3. 用户授权后重新授权，再撤销
+    ///
+    /// # 验证点
+    /// - 授权只在到期前且范围覆盖时有效，未设到期时间则一直有效
+    /// - 无效范围和过去的到期时间被拒绝
+    /// - 重新授权覆盖范围和到期时间
+    /// - 撤销后授权账户关闭，租金退回用户
+    /// - 用户自己签名调用信用分门槛检查不需要授权
+    #[test]
+    fn test_consent_grant_and_revoke() {
+        let now = 1_700_000_000;
+        let consent = Consent {
+            owner: Pubkey::new_unique(),
+            grantee: Pubkey::new_unique(),
+            scope: CONSENT_SCOPE_MIN_SCORE,
+            granted_at: now,
+            expires_at: Some(now + 100),
+        };
+        assert!(consent.allows(CONSENT_SCOPE_MIN_SCORE, now + 99));
+        assert!(!consent.allows(CONSENT_SCOPE_MIN_SCORE, now + 100));
+        assert!(!consent.allows(CONSENT_SCOPE_MIN_SCORE | 1 << 7, now));
+        assert!(Consent {
+            expires_at: None,
+            ..consent
+        }
+        .allows(CONSENT_SCOPE_MIN_SCORE, i64::MAX));
+
+        let mut svm = setup_test_environment();
+        let user = Keypair::new();
+        let grantee = Pubkey::new_unique();
+        svm.airdrop(&user.pubkey(), 15 * LAMPORTS_PER_SOL).unwrap();
+        let now = svm.get_sysvar::<Clock>().unix_timestamp;
+
+        let ix = grant_consent_ix(&user.pubkey(), &grantee, 0, None);
+        assert_program_error(
+            send_ix(&mut svm, ix, &user),
+            IdentityScoreError::InvalidConsentScope,
+        );
+        let ix = grant_consent_ix(&user.pubkey(), &grantee, 1 << 7, None);
+        assert_program_error(
+            send_ix(&mut svm, ix, &user),
+            IdentityScoreError::InvalidConsentScope,
+        );
+        let ix = grant_consent_ix(&user.pubkey(), &grantee, CONSENT_SCOPE_MIN_SCORE, Some(now));
+        assert_program_error(
+            send_ix(&mut svm, ix, &user),
+            IdentityScoreError::InvalidConsentExpiry,
+        );
+
+        let get_consent = |svm: &LiteSVM| {
+            let account = svm
+                .get_account(&get_consent_pda(&user.pubkey(), &grantee).0)
+                .unwrap();
+            let mut data_slice = &account.data[..];
+            Consent::try_deserialize(&mut data_slice).unwrap()
+        };
+        let ix = grant_consent_ix(&user.pubkey(), &grantee, CONSENT_SCOPE_MIN_SCORE, None);
+        send_ix(&mut svm, ix, &user).unwrap();
+        let consent = get_consent(&svm);
+        assert_eq!(consent.owner, user.pubkey());
+        assert_eq!(consent.grantee, grantee);
+        assert_eq!(consent.expires_at, None);
+
+        let ix = grant_consent_ix(
+            &user.pubkey(),
+            &grantee,
+            CONSENT_SCOPE_MIN_SCORE,
+            Some(now + 3600),
+        );
+        send_ix(&mut svm, ix, &user).unwrap();
+        assert_eq!(get_consent(&svm).expires_at, Some(now + 3600));
+
+        let balance_before = svm.get_balance(&user.pubkey()).unwrap();
+        let consent_pda = get_consent_pda(&user.pubkey(), &grantee).0;
+        let consent_lamports = svm.get_balance(&consent_pda).unwrap();
+        send_ix(&mut svm, revoke_consent_ix(&user.pubkey(), &grantee), &user).unwrap();
+        assert!(svm.get_account(&consent_pda).is_none());
+        assert_eq!(
+            svm.get_balance(&user.pubkey()).unwrap(),
+            balance_before + consent_lamports - 5_000
+        );
+
+        let (identity_pda, _) = get_identity_pda(&user.pubkey());
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        let create_ix = create_identity_ix(&user.pubkey(), &identity_pda);
+        let verify_ix = verify_identity_ix(&user.pubkey(), &identity_pda);
+        let calc_ix = calculate_score_ix(&user.pubkey(), &identity_pda, &score_pda);
+        let gate_ix = assert_min_score_ix(&user.pubkey(), 40, 3600, 1);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[create_ix, verify_ix, calc_ix, gate_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).unwrap();
+    }
+
+    /// 测试用的门槛消费程序，源码在 programs/score_gate_consumer
+    const SCORE_GATE_CONSUMER_ID: Pubkey =
+        solana_sdk::pubkey!("F15H9aKE1noHu369xw3KH4mYyhwk9h6Np1HHxgVMsDrV");
+
+    /// 构建消费程序经 CPI 检查借款人信用分的指令
+    fn check_borrower_ix(owner: &Pubkey, consent: &Pubkey, min_score: u8) -> Instruction {
+        let mut data = get_discriminator("check_borrower").to_vec();
+        data.push(min_score);
+        Instruction {
+            program_id: SCORE_GATE_CONSUMER_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+                AccountMeta::new_readonly(get_identity_pda(owner).0, false),
+                AccountMeta::new_readonly(*owner, false),
+                AccountMeta::new_readonly(get_score_pda(owner).0, false),
+                AccountMeta::new_readonly(get_score_model_pda(1).0, false),
+                AccountMeta::new_readonly(get_config_pda().0, false),
+                AccountMeta::new_readonly(get_penalty_index_pda(owner).0, false),
+                AccountMeta::new_readonly(
+                    Pubkey::find_program_address(&[SEED_SCORE_GATE], &SCORE_GATE_CONSUMER_ID).0,
+                    false,
+                ),
+                AccountMeta::new_readonly(*consent, false),
+            ],
+            data,
+        }
+    }
+
+    /// 测试消费程序经 CPI 调用信用分门槛检查
+    ///
+    /// # 测试场景
+    /// 1. 部署消费程序，用户计算信用分，由第三方支付手续费调用消费程序
+    /// 2. 未传授权、用户签名但未传授权、传入授予其他程序的授权
+    /// 3. 用户授权消费程序后调用，再推进时间到授权过期
+    /// 4. 重新授权后撤销
+    ///
+    /// # 验证点
+    /// - 没有授权或授权属于其他程序时返回 ConsentRequired
+    /// - 用户的签名经 CPI 传入时不能代替授权
+    /// - 有效授权下按门槛放行或返回 ScoreBelowThreshold
+    /// - 过期授权返回 ConsentRequired，撤销后的授权账户不再可用
+    #[test]
+    fn test_assert_min_score_via_consumer_program() {
+        let (mut svm, admin) = setup_test_environment_with_admin();
+        deploy_program(
+            &mut svm,
+            &SCORE_GATE_CONSUMER_ID,
+            include_bytes!("../../../target/deploy/score_gate_consumer.so"),
+            &admin.pubkey(),
+        );
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), 15 * LAMPORTS_PER_SOL).unwrap();
+
+        let (score_pda, _) = get_score_pda(&user.pubkey());
+        create_scored_identity(&mut svm, &user);
+        let account = svm.get_account(&score_pda).unwrap();
+        let score = CreditScoreAccount::try_deserialize(&mut &account.data[..])
+            .unwrap()
+            .score;
+
+        // 不传授权
+        let ix = check_borrower_ix(&user.pubkey(), &PROGRAM_ID, 0);
+        assert_program_error(
+            send_ix(&mut svm, ix, &admin),
+            IdentityScoreError::ConsentRequired,
+        );
+
+        // 用户签了外层交易，签名随 CPI 传入门槛检查
+        let mut ix = check_borrower_ix(&user.pubkey(), &PROGRAM_ID, 0);
+        ix.accounts[2].is_signer = true;
+        assert_program_error(
+            send_ix(&mut svm, ix, &user),
+            IdentityScoreError::ConsentRequired,
+        );
+
+        // 授予其他程序的授权不能被消费程序冒用
+        let other_program = Pubkey::new_unique();
+        let ix = grant_consent_ix(
+            &user.pubkey(),
+            &other_program,
+            CONSENT_SCOPE_MIN_SCORE,
+            None,
+        );
+        send_ix(&mut svm, ix, &user).unwrap();
+        let other_consent = get_consent_pda(&user.pubkey(), &other_program).0;
+        let ix = check_borrower_ix(&user.pubkey(), &other_consent, 0);
+        assert_program_error(
+            send_ix(&mut svm, ix, &admin),
+            IdentityScoreError::ConsentRequired,
+        );
+
+        let consent_pda = get_consent_pda(&user.pubkey(), &SCORE_GATE_CONSUMER_ID).0;
+        let now = svm.get_sysvar::<Clock>().unix_timestamp;
+        let ix = grant_consent_ix(
+            &user.pubkey(),
+            &SCORE_GATE_CONSUMER_ID,
+            CONSENT_SCOPE_MIN_SCORE,
+            Some(now + 3600),
+        );
+        send_ix(&mut svm, ix, &user).unwrap();
+        let ix = check_borrower_ix(&user.pubkey(), &consent_pda, score);
+        send_ix(&mut svm, ix, &admin).unwrap();
+        let ix = check_borrower_ix(&user.pubkey(), &consent_pda, score + 1);
+        assert_program_error(
+            send_ix(&mut svm, ix, &admin),
+            IdentityScoreError::ScoreBelowThreshold,
+        );
+
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = now + 3600;
+        svm.set_sysvar::<Clock>(&clock);
+        svm.expire_blockhash();
+        let ix = check_borrower_ix(&user.pubkey(), &consent_pda, 0);
+        assert_program_error(
+            send_ix(&mut svm, ix, &admin),
+            IdentityScoreError::ConsentRequired,
+        );
+
+        let ix = grant_consent_ix(
+            &user.pubkey(),
+            &SCORE_GATE_CONSUMER_ID,
+            CONSENT_SCOPE_MIN_SCORE,
+            None,
+        );
+        send_ix(&mut svm, ix, &user).unwrap();
+        let ix = check_borrower_ix(&user.pubkey(), &consent_pda, 0);
+        send_ix(&mut svm, ix, &admin).unwrap();
+
+        let ix = revoke_consent_ix(&user.pubkey(), &SCORE_GATE_CONSUMER_ID);
+        send_ix(&mut svm, ix, &user).unwrap();
+        svm.expire_blockhash();
+        let ix = check_borrower_ix(&user.pubkey(), &consent_pda, 0);
+        let err = send_ix(&mut svm, ix, &admin).expect_err("revoked consent should fail");
+        let code = u32::from(anchor_lang::error::ErrorCode::AccountNotInitialized);
+        assert!(err.contains(&format!("Custom({})", code)), "{}", err);
+    }
 }
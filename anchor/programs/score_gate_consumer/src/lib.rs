@@ -0,0 +1,60 @@
+//! Minimal consumer of the score gate, used by the identity_score tests to
+//! exercise `assert_min_score` through a real CPI.
+
+use anchor_lang::prelude::*;
+use identity_score::constants::SEED_SCORE_GATE;
+use identity_score::gate::{GateAccounts, ScoreRequirement};
+
+declare_id!("F15H9aKE1noHu369xw3KH4mYyhwk9h6Np1HHxgVMsDrV");
+
+#[program]
+pub mod score_gate_consumer {
+    use super::*;
+
+    /// Succeeds only when the owner has consented to this program and meets
+    /// `min_score`
+    pub fn check_borrower(ctx: Context<CheckBorrower>, min_score: u8) -> Result<()> {
+        let accounts = ctx.accounts;
+        identity_score::gate::assert_min_score(
+            GateAccounts {
+                identity_score_program: accounts.identity_score_program.to_account_info(),
+                identity: accounts.identity.to_account_info(),
+                owner: accounts.owner.to_account_info(),
+                score_account: accounts.score_account.to_account_info(),
+                score_model: accounts.score_model.to_account_info(),
+                config: accounts.config.to_account_info(),
+                penalty_index: accounts.penalty_index.to_account_info(),
+                gate_authority: accounts.gate_authority.to_account_info(),
+                consent: accounts.consent.to_account_info(),
+            },
+            ScoreRequirement {
+                min_score,
+                max_age_seconds: i64::MAX,
+                min_model_version: 1,
+            },
+            ctx.program_id,
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct CheckBorrower<'info> {
+    pub identity_score_program: Program<'info, identity_score::program::IdentityScore>,
+    /// CHECK: Validated by the gate
+    pub identity: UncheckedAccount<'info>,
+    /// CHECK: Validated by the gate
+    pub owner: UncheckedAccount<'info>,
+    /// CHECK: Validated by the gate
+    pub score_account: UncheckedAccount<'info>,
+    /// CHECK: Validated by the gate
+    pub score_model: UncheckedAccount<'info>,
+    /// CHECK: Validated by the gate
+    pub config: UncheckedAccount<'info>,
+    /// CHECK: Validated by the gate
+    pub penalty_index: UncheckedAccount<'info>,
+    /// CHECK: PDA this program signs the gate call with
+    #[account(seeds = [SEED_SCORE_GATE], bump)]
+    pub gate_authority: UncheckedAccount<'info>,
+    /// CHECK: Validated by the gate
+    pub consent: UncheckedAccount<'info>,
+}